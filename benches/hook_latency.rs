@@ -0,0 +1,61 @@
+// The time the panic hook spends capturing the exception on the panicking thread, with the
+// symbols resolved right away (`Eager`) or only the addresses captured (`Lazy`)
+
+use app_center::__private::capture_panic;
+use app_center::ResolutionMode;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::panic;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// The mode used by the hook and the time it took, read back after each panic
+static MODE: Mutex<ResolutionMode> = Mutex::new(ResolutionMode::Eager);
+static ELAPSED: Mutex<Duration> = Mutex::new(Duration::ZERO);
+
+// A few frames deep, like a panic in application code
+#[inline(never)]
+fn nested(depth: usize) {
+    if depth == 0 {
+        panic!("hook latency");
+    }
+    nested(depth - 1);
+    std::hint::black_box(depth);
+}
+
+fn hook_latency(c: &mut Criterion) {
+    panic::set_hook(Box::new(|info| {
+        let mode = *MODE.lock().unwrap();
+        let start = Instant::now();
+        std::hint::black_box(capture_panic(info, mode));
+        *ELAPSED.lock().unwrap() = start.elapsed();
+    }));
+
+    let mut group = c.benchmark_group("hook_latency");
+    for (name, mode) in [
+        ("eager", ResolutionMode::Eager),
+        ("lazy", ResolutionMode::Lazy),
+    ] {
+        *MODE.lock().unwrap() = mode;
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter_custom(|iters| {
+                let mut total = Duration::ZERO;
+                for _ in 0..iters {
+                    let _ = panic::catch_unwind(|| nested(8));
+                    total += *ELAPSED.lock().unwrap();
+                }
+                total
+            })
+        });
+    }
+    group.finish();
+
+    let _ = panic::take_hook();
+}
+
+criterion_group! {
+    name = benches;
+    // Each sample panics thousands of times, keep the run short
+    config = Criterion::default().sample_size(20).measurement_time(Duration::from_secs(5));
+    targets = hook_latency
+}
+criterion_main!(benches);
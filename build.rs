@@ -0,0 +1,82 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+// Keep in sync with `rust-version` in Cargo.toml
+const MSRV_MINOR: u32 = 82;
+
+// Expose the target and the optimization level to the crate, they are only known to build scripts
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    for var in &["TARGET", "OPT_LEVEL", "PROFILE"] {
+        let value = env::var(var).unwrap_or_default();
+        println!("cargo:rustc-env=APP_CENTER_BUILD_{}={}", var, value);
+    }
+
+    // Set by the CI of the application, see the `build-info` feature
+    if env::var_os("CARGO_FEATURE_BUILD_INFO").is_some() {
+        for var in &["GIT_COMMIT", "GIT_BRANCH", "CI_RUN"] {
+            println!("cargo:rerun-if-env-changed=APP_CENTER_{}", var);
+            let value = env::var(format!("APP_CENTER_{}", var)).unwrap_or_default();
+            println!("cargo:rustc-env=APP_CENTER_BUILD_{}={}", var, value);
+        }
+    }
+
+    // Where the sources of the build machine are, stripped from the file names of frames
+    println!("cargo:rerun-if-env-changed=CARGO_HOME");
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let cargo_home = env::var("CARGO_HOME")
+        .ok()
+        .or_else(|| cargo_home_of(&manifest_dir))
+        .unwrap_or_default();
+    // Only a local checkout sits next to the sources of the application
+    let workspace_parent = if cargo_home.is_empty() || !manifest_dir.starts_with(&cargo_home) {
+        Path::new(&manifest_dir)
+            .parent()
+            .and_then(Path::to_str)
+            .unwrap_or_default()
+    } else {
+        ""
+    };
+    let toolchain_dir = match (env::var("RUSTUP_HOME"), env::var("RUSTUP_TOOLCHAIN")) {
+        (Ok(home), Ok(toolchain)) => Path::new(&home)
+            .join("toolchains")
+            .join(toolchain)
+            .to_string_lossy()
+            .into_owned(),
+        _ => String::new(),
+    };
+    println!("cargo:rustc-env=APP_CENTER_BUILD_CARGO_HOME={}", cargo_home);
+    println!(
+        "cargo:rustc-env=APP_CENTER_BUILD_WORKSPACE_PARENT={}",
+        workspace_parent
+    );
+    println!(
+        "cargo:rustc-env=APP_CENTER_BUILD_TOOLCHAIN_DIR={}",
+        toolchain_dir
+    );
+
+    // Cargo already refuses older compilers, unless `--ignore-rust-version` is passed
+    println!("cargo:rustc-check-cfg=cfg(app_center_unsupported_rustc)");
+    if rustc_minor().is_some_and(|minor| minor < MSRV_MINOR) {
+        println!("cargo:rustc-cfg=app_center_unsupported_rustc");
+    }
+}
+
+// The cargo home a dependency was unpacked into, `~/.cargo` for `~/.cargo/registry/src/...`
+fn cargo_home_of(manifest_dir: &str) -> Option<String> {
+    ["registry", "git"].iter().find_map(|dir| {
+        let sep = std::path::MAIN_SEPARATOR;
+        let idx = manifest_dir.find(&format!("{}{}{}", sep, dir, sep))?;
+        Some(manifest_dir[..idx].to_string())
+    })
+}
+
+// The minor version of the compiler, from `rustc 1.82.0 (f6e511eec 2024-10-15)`
+fn rustc_minor() -> Option<u32> {
+    let rustc = env::var_os("RUSTC")?;
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    version.split(['.', ' ']).nth(2)?.parse().ok()
+}
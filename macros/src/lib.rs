@@ -0,0 +1,108 @@
+//! Attribute macros for the `app-center` crate, re-exported by it with the `macros` feature.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Error, ItemFn, LitBool, Path, ReturnType, Type};
+
+///
+/// Report the panics of a function to AppCenter.
+///
+/// By default a panic is caught, reported as a handled error with its backtrace, and the
+/// function returns `Default::default()`, so the return type must implement `Default`.
+/// With `fatal = true` the panic is reported as a crash and keeps unwinding, as without the
+/// attribute, even when called from a non-fatal function. This is useful on threads whose
+/// panics are otherwise caught and dropped.
+/// Async functions are supported, the panics of every poll of their body are caught.
+///
+/// Panics are only reported once the panic hook is installed, with `app_center::start!` or
+/// `AppCenterBuilder::build`.
+///
+/// The expanded code refers to the crate as `::app_center`. When the dependency is renamed,
+/// or reached through another crate, pass its path with `crate = path`.
+///
+/// ```ignore
+/// #[app_center::catch]
+/// fn refresh_cache() -> Option<usize> {
+///     let entries = load_entries()?;
+///     Some(entries.len())
+/// }
+///
+/// #[app_center::catch(fatal = true)]
+/// async fn serve() {
+///     // ...
+/// }
+///
+/// // With `crash = { package = "app-center", ... }` in Cargo.toml
+/// #[crash::catch(crate = crash)]
+/// fn render() -> String {
+///     // ...
+/// }
+/// ```
+///
+#[proc_macro_attribute]
+pub fn catch(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut fatal = false;
+    let mut krate: Path = syn::parse_quote!(::app_center);
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("fatal") {
+            fatal = meta.value()?.parse::<LitBool>()?.value;
+            Ok(())
+        } else if meta.path.is_ident("crate") {
+            krate = meta.value()?.parse::<Path>()?;
+            Ok(())
+        } else {
+            Err(meta
+                .error("unsupported `catch` argument, expected `fatal = bool` or `crate = path`"))
+        }
+    });
+    parse_macro_input!(attr with parser);
+
+    let mut function = parse_macro_input!(item as ItemFn);
+    match expand(&mut function, fatal, &krate) {
+        Ok(()) => quote!(#function).into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(function: &mut ItemFn, fatal: bool, krate: &Path) -> syn::Result<()> {
+    if function.sig.constness.is_some() {
+        return Err(Error::new_spanned(
+            function.sig.constness,
+            "`catch` can't be used on a const fn",
+        ));
+    }
+
+    let block = &function.block;
+    // The return type is spelled out so `?` in the body converts errors as in the function,
+    // `impl Trait` can't be and is left to inference
+    let ty = match &function.sig.output {
+        ReturnType::Default => Some(quote!(())),
+        ReturnType::Type(_, ty) if matches!(**ty, Type::ImplTrait(_)) => None,
+        ReturnType::Type(_, ty) => Some(quote!(#ty)),
+    };
+
+    let wrapped = if function.sig.asyncness.is_some() {
+        let run = if fatal {
+            quote!(#krate::__private::catch_async_fatal)
+        } else {
+            quote!(#krate::__private::catch_async)
+        };
+        match ty {
+            Some(ty) => quote!({ #run::<#ty, _>(async move #block).await }),
+            None => quote!({ #run(async move #block).await }),
+        }
+    } else {
+        let run = if fatal {
+            quote!(#krate::__private::catch_fatal)
+        } else {
+            quote!(#krate::__private::catch)
+        };
+        match ty {
+            Some(ty) => quote!({ #run(move || -> #ty #block) }),
+            None => quote!({ #run(move || #block) }),
+        }
+    };
+
+    *function.block = syn::parse2(wrapped)?;
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+#[cfg(not(feature = "tokio"))]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+#[cfg(feature = "tokio")]
+use tokio::sync::{Mutex, MutexGuard};
+
+///
+/// How significant a breadcrumb is, `Info` unless set with `Breadcrumb::with_level`
+///
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BreadcrumbLevel {
+    /// Diagnostic detail
+    Debug,
+    /// A regular step, such as opening a document
+    #[default]
+    Info,
+    /// Something unexpected the application recovered from
+    Warning,
+    /// A failed operation
+    Error,
+}
+
+///
+/// A step on the path of the user to the crash, such as opening a document
+///
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Breadcrumb {
+    timestamp: chrono::DateTime<chrono::Utc>,
+    session_id: uuid::Uuid,
+    level: BreadcrumbLevel,
+    category: String,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<BTreeMap<String, String>>,
+}
+
+impl Breadcrumb {
+    /// A breadcrumb for `AppCenter::add_breadcrumb_async`, the timestamp and the session are
+    /// set when it is added
+    pub fn new<C: Into<String>, M: Into<String>>(category: C, message: M) -> Self {
+        Breadcrumb {
+            timestamp: chrono::Utc::now(),
+            session_id: uuid::Uuid::nil(),
+            level: BreadcrumbLevel::default(),
+            category: category.into(),
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    /// Set how significant the breadcrumb is
+    pub fn with_level(mut self, level: BreadcrumbLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Attach additional key/value data
+    pub fn with_data(mut self, data: BTreeMap<String, String>) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    pub(crate) fn stamp(
+        &mut self,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        session_id: uuid::Uuid,
+    ) {
+        self.timestamp = timestamp;
+        self.session_id = session_id;
+    }
+
+    /// When the breadcrumb was added
+    pub fn timestamp(&self) -> chrono::DateTime<chrono::Utc> {
+        self.timestamp
+    }
+
+    /// The session that was current when the breadcrumb was added, see `AppCenter::renew_session`
+    pub fn session_id(&self) -> uuid::Uuid {
+        self.session_id
+    }
+
+    /// How significant the breadcrumb is
+    pub fn level(&self) -> BreadcrumbLevel {
+        self.level
+    }
+
+    /// The category, such as `"navigation"`
+    pub fn category(&self) -> &str {
+        &self.category
+    }
+
+    /// The description of the step
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Additional key/value data
+    pub fn data(&self) -> Option<&BTreeMap<String, String>> {
+        self.data.as_ref()
+    }
+}
+
+// The breadcrumbs of one session in `breadcrumbs.json`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionBreadcrumbs<'a> {
+    session_id: uuid::Uuid,
+    breadcrumbs: Vec<&'a Breadcrumb>,
+}
+
+// The most recent breadcrumbs, the oldest are dropped when full. The lock is only held to
+// push or copy the breadcrumbs, never across an await point. With the `tokio` feature it is a
+// `tokio::sync::Mutex`, so async tasks wait for it without blocking their worker thread.
+pub(crate) struct BreadcrumbBuffer {
+    capacity: usize,
+    breadcrumbs: Mutex<VecDeque<Breadcrumb>>,
+}
+
+impl BreadcrumbBuffer {
+    pub(crate) fn new(capacity: usize) -> Self {
+        BreadcrumbBuffer {
+            capacity,
+            breadcrumbs: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub(crate) fn push(
+        &self,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        session_id: uuid::Uuid,
+        category: &str,
+        message: &str,
+        data: Option<BTreeMap<String, String>>,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let breadcrumb = Breadcrumb {
+            timestamp,
+            session_id,
+            level: BreadcrumbLevel::default(),
+            category: category.to_string(),
+            message: message.to_string(),
+            data,
+        };
+        self.push_locked(&mut self.lock(), breadcrumb);
+    }
+
+    pub(crate) async fn push_async(&self, breadcrumb: Breadcrumb) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        #[cfg(feature = "tokio")]
+        let mut breadcrumbs = self.breadcrumbs.lock().await;
+        #[cfg(not(feature = "tokio"))]
+        let mut breadcrumbs = self.lock();
+        self.push_locked(&mut breadcrumbs, breadcrumb);
+    }
+
+    fn push_locked(&self, breadcrumbs: &mut VecDeque<Breadcrumb>, breadcrumb: Breadcrumb) {
+        if breadcrumbs.len() == self.capacity {
+            breadcrumbs.pop_front();
+        }
+        breadcrumbs.push_back(breadcrumb);
+    }
+
+    // A panic elsewhere must not lose the breadcrumbs of the report
+    #[cfg(not(feature = "tokio"))]
+    fn lock(&self) -> MutexGuard<'_, VecDeque<Breadcrumb>> {
+        self.breadcrumbs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // `blocking_lock` panics on the threads of a runtime, where reports are built too. The
+    // lock is held briefly, spinning is cheaper than handing the work to another thread.
+    #[cfg(feature = "tokio")]
+    fn lock(&self) -> MutexGuard<'_, VecDeque<Breadcrumb>> {
+        loop {
+            match self.breadcrumbs.try_lock() {
+                Ok(breadcrumbs) => return breadcrumbs,
+                Err(_) => std::thread::yield_now(),
+            }
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        self.lock().clear();
+    }
+
+    // The breadcrumbs oldest first, consecutive ones grouped by session so the steps that
+    // led to the crash can be told apart from those of an earlier session, `None` if there are none
+    pub(crate) fn to_json(&self) -> Option<Vec<u8>> {
+        let breadcrumbs = self.lock();
+        if breadcrumbs.is_empty() {
+            return None;
+        }
+
+        let mut sessions: Vec<SessionBreadcrumbs> = Vec::new();
+        for breadcrumb in breadcrumbs.iter() {
+            match sessions.last_mut() {
+                Some(session) if session.session_id == breadcrumb.session_id => {
+                    session.breadcrumbs.push(breadcrumb)
+                }
+                _ => sessions.push(SessionBreadcrumbs {
+                    session_id: breadcrumb.session_id,
+                    breadcrumbs: vec![breadcrumb],
+                }),
+            }
+        }
+        serde_json::to_vec(&sessions).ok()
+    }
+}
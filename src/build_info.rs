@@ -0,0 +1,64 @@
+use serde::Serialize;
+
+// How the binary was built and the logging verbosity it runs with, attached as `build_info.json`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BuildInfo {
+    debug_assertions: bool,
+    target: &'static str,
+    opt_level: &'static str,
+    profile: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rust_log: Option<String>,
+    #[serde(flatten)]
+    metadata: BuildMetadata,
+}
+
+// The source revision and the CI run the application was built from
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BuildMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) commit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) ci_run: Option<String>,
+}
+
+impl BuildInfo {
+    pub(crate) fn current(metadata: &BuildMetadata) -> Self {
+        BuildInfo {
+            debug_assertions: cfg!(debug_assertions),
+            target: env!("APP_CENTER_BUILD_TARGET"),
+            opt_level: env!("APP_CENTER_BUILD_OPT_LEVEL"),
+            profile: env!("APP_CENTER_BUILD_PROFILE"),
+            rust_log: std::env::var("RUST_LOG").ok(),
+            metadata: metadata.clone(),
+        }
+    }
+}
+
+impl BuildMetadata {
+    // Empty values are left out
+    pub(crate) fn new(commit: String, branch: String, ci_run: String) -> Self {
+        let non_empty = |s: String| Some(s).filter(|s| !s.is_empty());
+        BuildMetadata {
+            commit: non_empty(commit),
+            branch: non_empty(branch),
+            ci_run: non_empty(ci_run),
+        }
+    }
+
+    // The values captured by the build script, only with the `build-info` feature
+    pub(crate) fn from_build_env() -> Self {
+        #[cfg(feature = "build-info")]
+        return BuildMetadata::new(
+            env!("APP_CENTER_BUILD_GIT_COMMIT").to_string(),
+            env!("APP_CENTER_BUILD_GIT_BRANCH").to_string(),
+            env!("APP_CENTER_BUILD_CI_RUN").to_string(),
+        );
+        #[cfg(not(feature = "build-info"))]
+        BuildMetadata::default()
+    }
+}
@@ -0,0 +1,895 @@
+use super::breadcrumbs::BreadcrumbBuffer;
+use super::build_info::BuildMetadata;
+use super::clock::{self, Clock, IdSource};
+use super::events::{EventQueue, EventValidation};
+use super::exception::{self, IgnoreList};
+use super::logging::{self, LogLevel, LogSink};
+use super::secret;
+use super::settings::SettingsStore;
+#[cfg(feature = "send")]
+use super::transport::{ClientConfigurator, ReqwestSender};
+use super::{
+    AppCenter, AppCenterInner, AppSecret, Base64Variant, DeviceBuilder, HttpSender, ReportingMode,
+    ResolutionMode, UserIdMode, Utils, DEFAULT_ENDPOINT, SHARED,
+};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(4);
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_SEND_ATTEMPTS: u32 = 10;
+const DEFAULT_MAX_BREADCRUMBS: usize = 100;
+const DISABLE_ENV_VAR: &str = "APPCENTER_DISABLE";
+
+///
+/// The reason an `AppCenterBuilder` failed to build
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The app secret is empty
+    EmptyAppSecret,
+    /// The app secret is not a UUID such as `0a1b2c3d-4e5f-6071-8293-a4b5c6d7e8f9`,
+    /// only its first 8 characters are displayed
+    InvalidAppSecret(String),
+    /// The combined app secret has no entry for the platform with the provided key
+    MissingPlatformSecret(String),
+    /// No application version was provided
+    MissingAppVersion,
+    /// The endpoint is not an absolute http(s) URL
+    InvalidEndpoint(String),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::EmptyAppSecret => write!(f, "the app secret is empty"),
+            ConfigError::InvalidAppSecret(secret) => {
+                write!(f, "the app secret {:?} is not a UUID", secret::mask(secret))
+            }
+            ConfigError::MissingPlatformSecret(key) => {
+                write!(f, "the app secret has no {:?} entry", key)
+            }
+            ConfigError::MissingAppVersion => write!(f, "the app version is not set"),
+            ConfigError::InvalidEndpoint(url) => write!(f, "invalid endpoint URL: {}", url),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+///
+/// Configure the crash reporter before installing the panic hook, created with
+/// `AppCenter::builder`
+///
+pub struct AppCenterBuilder {
+    app_secret: String,
+    mirror_app_secrets: Vec<String>,
+    app_version: Option<&'static str>,
+    app_build: Option<String>,
+    build_metadata: BuildMetadata,
+    device: Option<DeviceBuilder>,
+    launch_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    process_name: Option<String>,
+    process_name_from_arg0: bool,
+    endpoint: String,
+    dry_run: bool,
+    dry_run_pretty: Option<bool>,
+    test_crash_in_release: bool,
+    pretty_panic_output: bool,
+    #[cfg_attr(not(feature = "send"), allow(dead_code))]
+    connect_timeout: Duration,
+    timeout: Duration,
+    #[cfg(feature = "http2")]
+    use_http2: bool,
+    #[cfg(feature = "send")]
+    configure_http_client: Option<ClientConfigurator>,
+    http_sender: Option<Box<dyn HttpSender>>,
+    ids: Option<Arc<dyn IdSource>>,
+    clock: Option<Arc<dyn Clock>>,
+    crash_dir: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    enabled: bool,
+    persist_enabled: bool,
+    auto_start_session: bool,
+    env_patterns: Vec<String>,
+    include_cwd: bool,
+    attach_system_state: bool,
+    ignore_list: IgnoreList,
+    persist_user_id: bool,
+    resolution_mode: ResolutionMode,
+    include_raw_symbols: bool,
+    skip_frame_patterns: Vec<String>,
+    path_prefixes: Vec<String>,
+    strip_default_path_prefixes: bool,
+    sample_rate: f32,
+    event_validation: EventValidation,
+    reporting_mode: ReportingMode,
+    user_id_mode: UserIdMode,
+    base64_variant: Base64Variant,
+    max_send_attempts: u32,
+    max_breadcrumbs: usize,
+    log_level: Option<LogLevel>,
+    log_sink: Option<LogSink>,
+    #[cfg(feature = "encryption")]
+    encryption_key: Option<[u8; 32]>,
+}
+
+impl AppCenterBuilder {
+    pub(crate) fn new(app_secret: String) -> Self {
+        AppCenterBuilder {
+            app_secret,
+            mirror_app_secrets: Vec::new(),
+            app_version: None,
+            app_build: None,
+            build_metadata: BuildMetadata::from_build_env(),
+            device: None,
+            launch_timestamp: None,
+            process_name: None,
+            process_name_from_arg0: false,
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            dry_run: false,
+            dry_run_pretty: None,
+            test_crash_in_release: false,
+            pretty_panic_output: false,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            timeout: DEFAULT_TIMEOUT,
+            #[cfg(feature = "http2")]
+            use_http2: false,
+            #[cfg(feature = "send")]
+            configure_http_client: None,
+            http_sender: None,
+            ids: None,
+            clock: None,
+            crash_dir: None,
+            data_dir: None,
+            enabled: true,
+            persist_enabled: false,
+            auto_start_session: true,
+            env_patterns: Vec::new(),
+            include_cwd: false,
+            attach_system_state: true,
+            ignore_list: IgnoreList::default(),
+            persist_user_id: false,
+            resolution_mode: ResolutionMode::default(),
+            include_raw_symbols: false,
+            skip_frame_patterns: Vec::new(),
+            path_prefixes: Vec::new(),
+            strip_default_path_prefixes: true,
+            sample_rate: 1.0,
+            event_validation: EventValidation::default(),
+            reporting_mode: ReportingMode::default(),
+            user_id_mode: UserIdMode::default(),
+            base64_variant: Base64Variant::default(),
+            max_send_attempts: DEFAULT_MAX_SEND_ATTEMPTS,
+            max_breadcrumbs: DEFAULT_MAX_BREADCRUMBS,
+            log_level: None,
+            log_sink: None,
+            #[cfg(feature = "encryption")]
+            encryption_key: None,
+        }
+    }
+
+    ///
+    /// The application version reported with every crash, usually `env!("CARGO_PKG_VERSION")`
+    ///
+    pub fn app_version(mut self, version: &'static str) -> Self {
+        self.app_version = Some(version);
+        self
+    }
+
+    ///
+    /// The application build number reported with every crash
+    ///
+    pub fn app_build<S: Into<String>>(mut self, build: S) -> Self {
+        self.app_build = Some(build.into());
+        self
+    }
+
+    ///
+    /// The commit, branch and CI run the application was built from, attached to every crash
+    /// as `build_info.json`. Empty values are left out. The commit is reported as the app build
+    /// unless one is set with `app_build`.
+    ///
+    /// With the `build-info` feature these default to the `APP_CENTER_GIT_COMMIT`,
+    /// `APP_CENTER_GIT_BRANCH` and `APP_CENTER_CI_RUN` environment variables at compile time,
+    /// for CI pipelines that can't pass them at runtime.
+    ///
+    pub fn build_metadata<S: Into<String>>(mut self, commit: S, branch: S, ci_run: S) -> Self {
+        self.build_metadata = BuildMetadata::new(commit.into(), branch.into(), ci_run.into());
+        self
+    }
+
+    ///
+    /// The launch timestamp reported with every crash, for applications that track their own
+    /// start time before the crash reporter is started. Defaults to the time of `build`.
+    ///
+    pub fn launch_timestamp(mut self, timestamp: chrono::DateTime<chrono::Utc>) -> Self {
+        self.launch_timestamp = Some(timestamp);
+        self
+    }
+
+    ///
+    /// Send every report to these apps as well, with the same payload and install ID,
+    /// for example while migrating between AppCenter organizations. When an upload fails,
+    /// only the apps that failed are retried from the crash directory.
+    ///
+    pub fn mirror_app_secrets(mut self, secrets: Vec<String>) -> Self {
+        self.mirror_app_secrets = secrets;
+        self
+    }
+
+    ///
+    /// Report the provided device instead of querying the operating system. The app version
+    /// and build of the device are used as is, `AppCenter::set_app_build` has no effect.
+    ///
+    pub fn device(mut self, device: DeviceBuilder) -> Self {
+        self.device = Some(device);
+        self
+    }
+
+    ///
+    /// Override the process name reported with every crash, by default it is the name of the
+    /// executable
+    ///
+    pub fn process_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.process_name = Some(name.into());
+        self
+    }
+
+    ///
+    /// Detect the process name from the first command line argument instead of the executable
+    /// path reported by the OS. Ignored when `process_name` is set. Defaults to false.
+    ///
+    pub fn process_name_from_arg0(mut self, from_arg0: bool) -> Self {
+        self.process_name_from_arg0 = from_arg0;
+        self
+    }
+
+    ///
+    /// Override the ingestion endpoint the reports are sent to
+    ///
+    pub fn endpoint<S: Into<String>>(mut self, endpoint: S) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    ///
+    /// Write the reports to stderr instead of sending them, for debugging the integration
+    ///
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    ///
+    /// Force the dry-run output to be indented (or not). By default the output is indented
+    /// when stderr is a terminal.
+    ///
+    pub fn dry_run_pretty(mut self, pretty: bool) -> Self {
+        self.dry_run_pretty = Some(pretty);
+        self
+    }
+
+    ///
+    /// Let `AppCenter::generate_test_crash` crash release builds too, for verifying the
+    /// integration in a staging build compiled in release mode
+    ///
+    pub fn test_crash_in_release(mut self, allow: bool) -> Self {
+        self.test_crash_in_release = allow;
+        self
+    }
+
+    ///
+    /// Print the panic with `color-backtrace` after the report is sent, instead of running the
+    /// previously installed panic hook. Requires the `color-backtrace` feature, ignored otherwise.
+    ///
+    pub fn pretty_panic_output(mut self, pretty: bool) -> Self {
+        self.pretty_panic_output = pretty;
+        self
+    }
+
+    ///
+    /// Set the connection timeout and the total request timeout used to upload reports.
+    /// Defaults to 4 and 30 seconds.
+    ///
+    pub fn timeouts(mut self, connect: Duration, total: Duration) -> Self {
+        self.connect_timeout = connect;
+        self.timeout = total;
+        self
+    }
+
+    ///
+    /// Talk HTTP/2 to the endpoint without negotiating it first. Defaults to false.
+    ///
+    #[cfg(feature = "http2")]
+    pub fn use_http2(mut self, use_http2: bool) -> Self {
+        self.use_http2 = use_http2;
+        self
+    }
+
+    ///
+    /// Customize the HTTP client before it is built, for example to set a proxy, add a root
+    /// certificate or change the user agent. The other options, such as the timeouts, are
+    /// applied first and can be overridden here. The client is built for every upload, so
+    /// `configure` can run several times. Requires the `send` feature, ignored when a sender is
+    /// set with `http_sender`.
+    ///
+    #[cfg(feature = "send")]
+    pub fn configure_http_client<F>(mut self, configure: F) -> Self
+    where
+        F: Fn(reqwest::blocking::ClientBuilder) -> reqwest::blocking::ClientBuilder
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.configure_http_client = Some(Box::new(configure));
+        self
+    }
+
+    ///
+    /// Upload with `sender` instead of the built-in `reqwest` client, for example to use the
+    /// HTTP stack of the application or to capture reports in tests. The timeouts are passed
+    /// with every request. Without the `send` feature and without a sender, reports are kept in
+    /// the crash directory, or written to stderr when there is none.
+    ///
+    pub fn http_sender(mut self, sender: impl HttpSender + 'static) -> Self {
+        self.http_sender = Some(Box::new(sender));
+        self
+    }
+
+    ///
+    /// Generate the ids of logs and sessions with `ids`, for example `SequentialIds`, so
+    /// snapshot tests can compare the serialized reports as they are. The install id is kept
+    /// in the settings and is not affected. Requires the `testing` feature.
+    ///
+    #[cfg(feature = "testing")]
+    pub fn id_source(mut self, ids: impl IdSource + 'static) -> Self {
+        self.ids = Some(Arc::new(ids));
+        self
+    }
+
+    ///
+    /// Stamp logs, breadcrumbs and the launch with the time of `clock`, for example a
+    /// `FixedClock`, see `id_source`. Requires the `testing` feature.
+    ///
+    #[cfg(feature = "testing")]
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    ///
+    /// Store reports that failed to upload in this directory, see `AppCenter::set_crash_dir`
+    ///
+    pub fn crash_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.crash_dir = Some(dir.into());
+        self
+    }
+
+    ///
+    /// Store the settings in this directory: the install id, the launch count, and the user
+    /// ID and enabled state when they are persisted. Defaults to a directory named after the
+    /// app secret in the application data directory of the user, such as
+    /// `~/.local/share/appcenter/{secret}` on Linux.
+    ///
+    pub fn data_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.data_dir = Some(dir.into());
+        self
+    }
+
+    ///
+    /// Whether crash reporting starts enabled, see `AppCenter::set_enabled`. Defaults to true.
+    ///
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    ///
+    /// Persist the state set by `AppCenter::set_enabled`, `AppCenter::set_crashes_enabled` and
+    /// `AppCenter::set_analytics_enabled` across launches. Once persisted, the stored state
+    /// takes precedence over `enabled`.
+    ///
+    pub fn persist_enabled(mut self, persist: bool) -> Self {
+        self.persist_enabled = persist;
+        self
+    }
+
+    ///
+    /// Send a `startSession` log when the hook is installed, which the AppCenter portal uses
+    /// to count active users. The log is sent from a background thread. Defaults to true.
+    ///
+    pub fn auto_start_session(mut self, auto_start_session: bool) -> Self {
+        self.auto_start_session = auto_start_session;
+        self
+    }
+
+    ///
+    /// Snapshot the environment variables matching any of the glob patterns (such as `"APP_*"`)
+    /// when the hook is installed, and attach the snapshot to every report as `environment.json`.
+    /// The snapshot reflects the environment before any code had a chance to modify it.
+    ///
+    pub fn snapshot_env_vars(mut self, patterns: Vec<String>) -> Self {
+        self.env_patterns = patterns;
+        self
+    }
+
+    ///
+    /// Add the working directory at the time of the crash to the custom properties as `cwd`.
+    /// Defaults to false, since the path may contain sensitive information.
+    ///
+    pub fn include_cwd(mut self, include: bool) -> Self {
+        self.include_cwd = include;
+        self
+    }
+
+    ///
+    /// Attach the memory, free disk space, thread count and uptime at the time of the crash
+    /// as `system.json`. Defaults to true.
+    ///
+    pub fn attach_system_state(mut self, attach: bool) -> Self {
+        self.attach_system_state = attach;
+        self
+    }
+
+    ///
+    /// Don't report panics whose message contains any of the provided strings,
+    /// see `AppCenter::set_ignored_messages`
+    ///
+    pub fn ignore_messages(mut self, messages: Vec<String>) -> Self {
+        self.ignore_list.messages = messages;
+        self
+    }
+
+    ///
+    /// Don't report panics originating from modules with the provided prefixes,
+    /// see `AppCenter::set_ignored_modules`
+    ///
+    pub fn ignore_modules(mut self, modules: Vec<String>) -> Self {
+        self.ignore_list.modules = modules;
+        self
+    }
+
+    ///
+    /// Persist the user ID across launches, see `AppCenter::set_persist_user_id`
+    ///
+    pub fn persist_user_id(mut self, persist: bool) -> Self {
+        self.persist_user_id = persist;
+        self
+    }
+
+    ///
+    /// Select when the backtrace symbols are resolved, see `AppCenter::set_resolution_mode`
+    ///
+    pub fn resolution_mode(mut self, mode: ResolutionMode) -> Self {
+        self.resolution_mode = mode;
+        self
+    }
+
+    ///
+    /// Include the mangled symbol name of every frame as `rawSymbol`, for cross referencing
+    /// with tools such as `nm`. Defaults to false.
+    ///
+    pub fn include_raw_symbols(mut self, include: bool) -> Self {
+        self.include_raw_symbols = include;
+        self
+    }
+
+    ///
+    /// Leave out the frames whose method or file name matches `pattern`, such as `"tokio::"`.
+    /// Patterns containing `*` or `?` are globs matching the whole name, the others match
+    /// any part of it. Can be called several times to add patterns.
+    ///
+    pub fn skip_frames_matching(mut self, pattern: &str) -> Self {
+        self.skip_frame_patterns.push(pattern.to_string());
+        self
+    }
+
+    ///
+    /// Strip `prefix` from the file names of the frames and from the location in the panic
+    /// message, so they don't carry user names and group the same across build machines.
+    /// The rest of the path is joined with `/`. A trailing `..` component strips the parent
+    /// directory, to keep the name of the crate:
+    ///
+    /// ```no_run
+    /// let app_center = app_center::AppCenter::builder("00000000-0000-0000-0000-000000000000")
+    ///     .strip_path_prefix(concat!(env!("CARGO_MANIFEST_DIR"), "/.."))
+    ///     .start();
+    /// ```
+    ///
+    /// Can be called several times to add prefixes, the longest matching one is stripped.
+    /// They come in addition to the default ones, see `strip_default_path_prefixes`.
+    ///
+    pub fn strip_path_prefix(mut self, prefix: &str) -> Self {
+        let path = std::path::Path::new(prefix);
+        let prefix = match path.file_name() {
+            None if prefix.ends_with("..") => path.parent().and_then(|p| p.parent()),
+            _ => Some(path),
+        };
+        if let Some(prefix) = prefix.and_then(|p| p.to_str()) {
+            self.path_prefixes.push(prefix.to_string());
+        }
+        self
+    }
+
+    ///
+    /// Strip the cargo home and the rustup toolchain of the build machine, the directory
+    /// holding this crate when it is built from a local checkout, and the cargo home of the
+    /// running machine from the file names of the frames. Registry paths become
+    /// `registry/serde-1.0.0/src/de.rs`. Defaults to true.
+    ///
+    pub fn strip_default_path_prefixes(mut self, strip: bool) -> Self {
+        self.strip_default_path_prefixes = strip;
+        self
+    }
+
+    ///
+    /// Upload only a fraction of the crashes, between 0.0 and 1.0. Crashes outside of the
+    /// sample are neither sent nor stored, but the previous panic hook still runs.
+    /// A report callback can bypass sampling with `AppCenterLogs::set_force_send`.
+    /// Defaults to 1.0, every crash is sent. A NaN rate is ignored.
+    ///
+    pub fn sample_rate(mut self, rate: f32) -> Self {
+        if rate.is_nan() {
+            app_log!(Warn, "Ignoring NaN crash sample rate");
+            return self;
+        }
+        self.sample_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    ///
+    /// Choose between rejecting and truncating the events that exceed the limits of AppCenter,
+    /// which would otherwise be dropped by the server without notice.
+    /// Defaults to `EventValidation::Truncate`.
+    ///
+    pub fn event_validation(mut self, validation: EventValidation) -> Self {
+        self.event_validation = validation;
+        self
+    }
+
+    ///
+    /// Select which builds send reports, for example `ReportingMode::ReleaseOnly` to keep
+    /// debug builds out of the production portal. Defaults to `ReportingMode::Always`.
+    ///
+    pub fn reporting_mode(mut self, mode: ReportingMode) -> Self {
+        self.reporting_mode = mode;
+        self
+    }
+
+    ///
+    /// Select how the user ID is sent, see `AppCenter::set_user_id_mode`.
+    /// Defaults to `UserIdMode::Raw`.
+    ///
+    pub fn user_id_mode(mut self, mode: UserIdMode) -> Self {
+        self.user_id_mode = mode;
+        self
+    }
+
+    ///
+    /// Select the Base64 alphabet used to encode the attachments
+    ///
+    pub fn base64_variant(mut self, variant: Base64Variant) -> Self {
+        self.base64_variant = variant;
+        self
+    }
+
+    ///
+    /// Set the number of upload attempts after which a stored crash report is dropped
+    ///
+    pub fn max_send_attempts(mut self, attempts: u32) -> Self {
+        self.max_send_attempts = attempts;
+        self
+    }
+
+    ///
+    /// Set the number of breadcrumbs kept for the reports, see `AppCenter::add_breadcrumb`.
+    /// Defaults to 100, 0 disables breadcrumbs.
+    ///
+    pub fn max_breadcrumbs(mut self, max: usize) -> Self {
+        self.max_breadcrumbs = max;
+        self
+    }
+
+    ///
+    /// Set how much the crate logs about its own operation, see `AppCenter::set_log_level`
+    ///
+    pub fn log_level(mut self, level: LogLevel) -> Self {
+        self.log_level = Some(level);
+        self
+    }
+
+    ///
+    /// Send the messages of the crate to `sink` instead of the `log` crate, for applications
+    /// that don't use `log` or want to keep the messages away from their output.
+    /// The sink is shared by all instances in the process.
+    ///
+    pub fn log_sink<F: Fn(log::Level, &str) + Send + Sync + 'static>(mut self, sink: F) -> Self {
+        self.log_sink = Some(Arc::new(sink));
+        self
+    }
+
+    ///
+    /// Encrypt the crash reports stored on disk, see `AppCenter::set_encryption_key`
+    ///
+    #[cfg(feature = "encryption")]
+    pub fn encryption_key(mut self, key: [u8; 32]) -> Self {
+        self.encryption_key = Some(key);
+        self
+    }
+
+    ///
+    /// Validate the configuration and install the panic hook.
+    ///
+    /// The app secret must be the UUID shown in the AppCenter portal, for example
+    /// `0a1b2c3d-4e5f-6071-8293-a4b5c6d7e8f9`, otherwise `ConfigError::InvalidAppSecret`
+    /// is returned. A combined secret such as `"windowsdesktop={guid};macos={guid}"` must
+    /// have an entry for the current platform, see `AppSecret`.
+    ///
+    pub fn build(self) -> Result<AppCenter, ConfigError> {
+        self.app_secret.parse::<AppSecret>()?;
+
+        if self.app_version.is_none() {
+            return Err(ConfigError::MissingAppVersion);
+        }
+
+        if !is_valid_endpoint(&self.endpoint) {
+            return Err(ConfigError::InvalidEndpoint(self.endpoint));
+        }
+
+        Ok(self.start())
+    }
+
+    // An empty or all-zero secret is what usually ends up in development builds
+    fn is_placeholder_secret(secret: &str) -> bool {
+        secret.is_empty() || uuid::Uuid::parse_str(secret).is_ok_and(|u| u.is_nil())
+    }
+
+    ///
+    /// Install the panic hook without validating the configuration, like `AppCenter::start`.
+    /// An invalid configuration only surfaces when reports fail to upload.
+    ///
+    pub fn start(self) -> AppCenter {
+        if let Some(level) = self.log_level {
+            logging::set_level(level);
+        }
+        if self.log_sink.is_some() {
+            logging::set_sink(self.log_sink.clone());
+        }
+        app_log!(
+            Debug,
+            "AppCenter capabilities: {}",
+            AppCenter::capabilities()
+        );
+
+        let app_secret = secret::for_platform(&self.app_secret)
+            .unwrap_or(&self.app_secret)
+            .to_string();
+
+        let mirror_app_secrets = self
+            .mirror_app_secrets
+            .iter()
+            .map(|s| secret::for_platform(s).unwrap_or(s).to_string())
+            .collect();
+
+        // Only a well formed secret is joined to the default path
+        let data_dir = self.data_dir.clone().or_else(|| {
+            uuid::Uuid::parse_str(&app_secret)
+                .ok()
+                .and_then(|app_id| Utils::get_data_dir(&app_id))
+        });
+        let settings = SettingsStore::new(data_dir);
+        let mut stored = settings.load();
+
+        let install_id = *stored.install_id.get_or_insert_with(uuid::Uuid::new_v4);
+        let launch_count = stored.launch_count.saturating_add(1);
+        settings.update(|s| {
+            s.install_id = Some(install_id);
+            s.launch_count = launch_count;
+        });
+
+        let user_id = if self.persist_user_id {
+            stored.user_id
+        } else {
+            None
+        };
+
+        let env_snapshot = if self.env_patterns.is_empty() {
+            None
+        } else {
+            let snapshot: BTreeMap<_, _> = std::env::vars()
+                .filter(|(k, _)| self.env_patterns.iter().any(|p| Utils::glob_match(p, k)))
+                .collect();
+            Some(snapshot)
+        };
+
+        let enabled = match stored.enabled {
+            Some(enabled) if self.persist_enabled => enabled,
+            _ => self.enabled,
+        };
+        let crashes_enabled = match stored.crashes_enabled {
+            Some(enabled) if self.persist_enabled => enabled,
+            _ => true,
+        };
+        let analytics_enabled = match stored.analytics_enabled {
+            Some(enabled) if self.persist_enabled => enabled,
+            _ => true,
+        };
+
+        // Built without the `enabled` feature the instance only keeps its configuration
+        let noop = if cfg!(not(feature = "enabled")) {
+            true
+        } else if std::env::var(DISABLE_ENV_VAR).is_ok_and(|v| v == "1") {
+            app_log!(
+                Warn,
+                "AppCenter crash reporting disabled by {}",
+                DISABLE_ENV_VAR
+            );
+            true
+        } else if Self::is_placeholder_secret(&app_secret) {
+            app_log!(
+                Warn,
+                "AppCenter crash reporting disabled, the app secret is a placeholder"
+            );
+            true
+        } else if let Err(err) = self.app_secret.parse::<AppSecret>() {
+            app_log!(Error, "AppCenter crash reporting disabled: {}", err);
+            true
+        } else {
+            false
+        };
+
+        let process_name = match self.process_name {
+            Some(process_name) => Some(process_name),
+            None if self.process_name_from_arg0 => Utils::get_process_name_from_arg0(),
+            None => Utils::get_process_name(),
+        };
+
+        #[cfg(feature = "send")]
+        let http_sender: Option<Box<dyn HttpSender>> = match self.http_sender {
+            Some(sender) => Some(sender),
+            None => Some(Box::new(ReqwestSender {
+                connect_timeout: self.connect_timeout,
+                #[cfg(feature = "http2")]
+                use_http2: self.use_http2,
+                configure_http_client: self.configure_http_client,
+            })),
+        };
+        #[cfg(not(feature = "send"))]
+        let http_sender = self.http_sender;
+
+        let mut path_prefixes = self.path_prefixes;
+        if self.strip_default_path_prefixes {
+            path_prefixes.extend(exception::default_path_prefixes());
+        }
+        exception::sort_path_prefixes(&mut path_prefixes);
+
+        let ids = self.ids.unwrap_or_else(clock::random_ids);
+        let clock = self.clock.unwrap_or_else(clock::system_clock);
+
+        let inner = Arc::new(AppCenterInner {
+            app_secret,
+            mirror_app_secrets,
+            install_id: Mutex::new(install_id),
+            settings,
+            persist_user_id: AtomicBool::new(self.persist_user_id),
+            enabled: AtomicBool::new(enabled),
+            crashes_enabled: AtomicBool::new(crashes_enabled),
+            analytics_enabled: AtomicBool::new(analytics_enabled),
+            persist_enabled: self.persist_enabled,
+            noop,
+            app_version: self.app_version.unwrap_or_default(),
+            app_build: Mutex::new(self.app_build.or(self.build_metadata.commit.clone())),
+            build_metadata: self.build_metadata,
+            device: self.device.map(DeviceBuilder::build),
+            country_code: Mutex::new(None),
+            detected_country_code: Utils::get_country_code(),
+            app_launch_timestamp: Mutex::new(self.launch_timestamp.unwrap_or_else(|| clock.now())),
+            launch_count,
+            process_name: process_name.unwrap_or_default(),
+            session_id: Mutex::new(ids.next_id()),
+            last_error_id: Mutex::new(None),
+            ids,
+            clock,
+            events: Arc::new(EventQueue::new()),
+            event_sender: Mutex::new(None),
+            last_heartbeat_epoch_ms: Arc::new(AtomicU64::new(0)),
+            endpoint: self.endpoint,
+            dry_run: self.dry_run,
+            dry_run_pretty: self.dry_run_pretty,
+            test_crash_in_release: self.test_crash_in_release,
+            pretty_panic_output: self.pretty_panic_output,
+            timeout: self.timeout,
+            http_sender,
+            env_snapshot,
+            include_cwd: self.include_cwd,
+            attach_system_state: self.attach_system_state,
+            user_id: Mutex::new(user_id),
+            user_id_mode: Mutex::new(self.user_id_mode),
+            properties: Mutex::new(BTreeMap::new()),
+            tags: Mutex::new(BTreeMap::new()),
+            current_page: Mutex::new(None),
+            event_validation: self.event_validation,
+            breadcrumbs: BreadcrumbBuffer::new(self.max_breadcrumbs),
+            on_report: Mutex::new(Vec::new()),
+            next_callback_id: AtomicU64::new(0),
+            report_filter: Mutex::new(None),
+            scrubber: Mutex::new(None),
+            grouping_key_fn: Mutex::new(None),
+            ignore_list: Mutex::new(self.ignore_list),
+            resolution_mode: Mutex::new(self.resolution_mode),
+            include_raw_symbols: self.include_raw_symbols,
+            skip_frame_patterns: self.skip_frame_patterns,
+            path_prefixes,
+            sample_rate: self.sample_rate,
+            reporting_mode: self.reporting_mode.effective(),
+            base64_variant: Mutex::new(self.base64_variant),
+            crash_dir: Mutex::new(self.crash_dir),
+            max_send_attempts: AtomicU32::new(self.max_send_attempts),
+            #[cfg(feature = "encryption")]
+            encryption_key: Mutex::new(self.encryption_key),
+            #[cfg(feature = "enabled")]
+            reporter: std::sync::OnceLock::new(),
+        });
+
+        #[cfg(feature = "enabled")]
+        inner.set_panic_hook();
+
+        if self.auto_start_session {
+            inner.start_session();
+        }
+
+        // Only the first instance becomes the shared one
+        let _ = SHARED.set(Arc::downgrade(&inner));
+
+        AppCenter { inner }
+    }
+}
+
+// An absolute http(s) URL with a host
+#[cfg(feature = "send")]
+fn is_valid_endpoint(endpoint: &str) -> bool {
+    match reqwest::Url::parse(endpoint) {
+        Ok(url) => (url.scheme() == "https" || url.scheme() == "http") && url.has_host(),
+        Err(_) => false,
+    }
+}
+
+// Without reqwest there is no URL parser at hand, check the scheme and that a host follows
+#[cfg(not(feature = "send"))]
+fn is_valid_endpoint(endpoint: &str) -> bool {
+    endpoint
+        .strip_prefix("https://")
+        .or_else(|| endpoint.strip_prefix("http://"))
+        .is_some_and(|rest| !rest.is_empty() && !rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> AppCenterBuilder {
+        AppCenter::builder("0a1b2c3d-4e5f-6071-8293-a4b5c6d7e8f9")
+    }
+
+    #[test]
+    fn sample_rate_is_clamped() {
+        assert_eq!(builder().sample_rate(0.0).sample_rate, 0.0);
+        assert_eq!(builder().sample_rate(0.5).sample_rate, 0.5);
+        assert_eq!(builder().sample_rate(1.0).sample_rate, 1.0);
+        assert_eq!(builder().sample_rate(-1.0).sample_rate, 0.0);
+        assert_eq!(builder().sample_rate(2.0).sample_rate, 1.0);
+    }
+
+    #[test]
+    fn nan_sample_rate_is_ignored() {
+        assert_eq!(builder().sample_rate(f32::NAN).sample_rate, 1.0);
+        assert_eq!(
+            builder().sample_rate(0.5).sample_rate(f32::NAN).sample_rate,
+            0.5
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use bitflags::bitflags;
+
+bitflags! {
+    ///
+    /// The optional parts of the crate compiled in, see `AppCenter::capabilities`. Prints
+    /// as `REPORTING | SEND | DISK_QUEUE | LOG_INTEGRATION`.
+    ///
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct CapabilitySet: u32 {
+        /// The panic hook is installed, the `enabled` feature
+        const REPORTING = 1 << 0;
+        /// Reports are uploaded with the built-in client, the `send` feature
+        const SEND = 1 << 1;
+        /// The built-in client can use HTTP/2, the `http2` feature
+        const HTTP2 = 1 << 2;
+        /// Reports that failed to upload are kept on disk and retried, with `REPORTING`
+        const DISK_QUEUE = 1 << 3;
+        /// Stored reports can be encrypted, the `encryption` feature
+        const ENCRYPTION = 1 << 4;
+        /// `AppCenterLayer` records `tracing` events, the `tracing` feature
+        const TRACING = 1 << 5;
+        /// `AppCenterLogger` captures `log` records, always available
+        const LOG_INTEGRATION = 1 << 6;
+        /// Panics can be printed with `color-backtrace`, the `color-backtrace` feature
+        const COLOR_BACKTRACE = 1 << 7;
+        /// Build metadata is captured from the environment, the `build-info` feature
+        const BUILD_INFO = 1 << 8;
+        /// `AppCenterLogs::json_schema` is available, the `schema` feature
+        const SCHEMA = 1 << 9;
+        /// The `catch` attribute is available, the `macros` feature
+        const MACROS = 1 << 10;
+        /// `CapturingTransport` and deterministic ids and timestamps, the `testing` feature
+        const TESTING = 1 << 11;
+        /// `AppCenter::report_anyhow` is available, the `anyhow` feature
+        const ANYHOW = 1 << 12;
+        /// `AppCenter::report_eyre` and `AppCenterEyreHook` are available, the `eyre` feature
+        const EYRE = 1 << 13;
+    }
+}
+
+impl CapabilitySet {
+    // Every flag is decided by the features the crate was compiled with
+    pub(crate) fn compiled() -> Self {
+        let features = [
+            (cfg!(feature = "enabled"), CapabilitySet::REPORTING),
+            (cfg!(feature = "send"), CapabilitySet::SEND),
+            (cfg!(feature = "http2"), CapabilitySet::HTTP2),
+            (cfg!(feature = "enabled"), CapabilitySet::DISK_QUEUE),
+            (cfg!(feature = "encryption"), CapabilitySet::ENCRYPTION),
+            (cfg!(feature = "tracing"), CapabilitySet::TRACING),
+            (true, CapabilitySet::LOG_INTEGRATION),
+            (
+                cfg!(feature = "color-backtrace"),
+                CapabilitySet::COLOR_BACKTRACE,
+            ),
+            (cfg!(feature = "build-info"), CapabilitySet::BUILD_INFO),
+            (cfg!(feature = "schema"), CapabilitySet::SCHEMA),
+            (cfg!(feature = "macros"), CapabilitySet::MACROS),
+            (cfg!(feature = "testing"), CapabilitySet::TESTING),
+            (cfg!(feature = "anyhow"), CapabilitySet::ANYHOW),
+            (cfg!(feature = "eyre"), CapabilitySet::EYRE),
+        ];
+
+        features
+            .iter()
+            .filter(|(enabled, _)| *enabled)
+            .fold(CapabilitySet::empty(), |set, (_, flag)| set | *flag)
+    }
+}
+
+impl std::fmt::Display for CapabilitySet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        bitflags::parser::to_writer(self, f)
+    }
+}
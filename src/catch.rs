@@ -0,0 +1,133 @@
+use super::{AppCenterException, AppCenterInner};
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+thread_local! {
+    // Non-fatal `catch` scopes entered on this thread, reset to 0 inside fatal scopes
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    // The panic captured by the hook inside a scope, reported once it is caught
+    static CAUGHT: RefCell<Option<(Arc<AppCenterInner>, AppCenterException)>> =
+        const { RefCell::new(None) };
+}
+
+// Called from the panic hook, keeps the exception for the enclosing non-fatal scope, with
+// the backtrace that is gone once the panic is caught. Returns the exception otherwise.
+#[cfg(feature = "enabled")]
+pub(crate) fn stash(
+    inner: &Arc<AppCenterInner>,
+    exception: AppCenterException,
+) -> Option<AppCenterException> {
+    if DEPTH.with(Cell::get) == 0 {
+        return Some(exception);
+    }
+    CAUGHT.with(|caught| *caught.borrow_mut() = Some((Arc::clone(inner), exception)));
+    None
+}
+
+// Sets the depth until dropped, also when unwinding, then restores the previous one. Leaving
+// the outermost scope forgets a panic stashed for a scope that never caught it, such as one
+// swallowed by `catch_unwind` inside the scope.
+struct DepthGuard(usize);
+
+impl DepthGuard {
+    fn set(depth: usize) -> Self {
+        DepthGuard(DEPTH.with(|d| d.replace(depth)))
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(self.0));
+        if self.0 == 0 {
+            CAUGHT.with(|caught| caught.borrow_mut().take());
+        }
+    }
+}
+
+// Run `f` in a non-fatal scope and report its panic, `None` when it panicked
+fn run<R>(f: impl FnOnce() -> R) -> Option<R> {
+    let _scope = DepthGuard::set(DEPTH.with(Cell::get) + 1);
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => Some(result),
+        Err(_) => {
+            report_caught();
+            None
+        }
+    }
+}
+
+// Report the stashed panic as a handled error, there is none when the hook is not installed
+fn report_caught() {
+    if let Some((inner, exception)) = CAUGHT.with(|caught| caught.borrow_mut().take()) {
+        let _ = inner.report(inner.new_payload(exception, false), Instant::now());
+    }
+}
+
+/// Run `f`, a panic is reported as a handled error and `R::default()` is returned
+pub fn catch<R: Default>(f: impl FnOnce() -> R) -> R {
+    run(f).unwrap_or_default()
+}
+
+/// Run `f`, a panic is reported as a crash by the panic hook and keeps unwinding, even
+/// inside a non-fatal `catch`
+pub fn catch_fatal<R>(f: impl FnOnce() -> R) -> R {
+    // Outside of the enclosing non-fatal scopes, the hook doesn't stash the panic
+    let _scope = DepthGuard::set(0);
+    f()
+}
+
+/// Await `future`, a panic is reported as a handled error and `Default::default()` is returned
+pub async fn catch_async<T: Default, F: Future<Output = T>>(future: F) -> T {
+    CatchUnwind::new(future, false).await.unwrap_or_default()
+}
+
+/// Await `future`, a panic is reported as a crash by the panic hook and keeps unwinding, even
+/// inside a non-fatal `catch`
+pub async fn catch_async_fatal<T, F: Future<Output = T>>(future: F) -> T {
+    match CatchUnwind::new(future, true).await {
+        Some(output) => output,
+        None => unreachable!(),
+    }
+}
+
+// Catches the panics of every poll, `None` when the future panicked. Fatal futures are polled
+// outside of the enclosing non-fatal scopes instead, their panics keep unwinding.
+struct CatchUnwind<F> {
+    future: Pin<Box<F>>,
+    fatal: bool,
+}
+
+impl<F> CatchUnwind<F> {
+    fn new(future: F, fatal: bool) -> Self {
+        CatchUnwind {
+            future: Box::pin(future),
+            fatal,
+        }
+    }
+}
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = Option<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let fatal = self.fatal;
+        let future = self.future.as_mut();
+        let poll = || future.poll(cx);
+
+        let result = if fatal {
+            Some(catch_fatal(poll))
+        } else {
+            run(poll)
+        };
+
+        match result {
+            Some(poll) => poll.map(Some),
+            None => Poll::Ready(None),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+///
+/// Generates the ids of logs and sessions, see `AppCenterBuilder::id_source`
+///
+pub trait IdSource: Send + Sync {
+    fn next_id(&self) -> uuid::Uuid;
+}
+
+///
+/// The time logs and breadcrumbs are stamped with, see `AppCenterBuilder::clock`
+///
+pub trait Clock: Send + Sync {
+    fn now(&self) -> chrono::DateTime<chrono::Utc>;
+}
+
+pub(crate) struct RandomIds;
+
+impl IdSource for RandomIds {
+    fn next_id(&self) -> uuid::Uuid {
+        uuid::Uuid::new_v4()
+    }
+}
+
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now()
+    }
+}
+
+pub(crate) fn random_ids() -> Arc<dyn IdSource> {
+    Arc::new(RandomIds)
+}
+
+pub(crate) fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+///
+/// Ids counting up from `00000000-0000-0000-0000-000000000001`, for snapshot tests
+///
+#[cfg(feature = "testing")]
+#[derive(Debug, Default)]
+pub struct SequentialIds(std::sync::atomic::AtomicU64);
+
+#[cfg(feature = "testing")]
+impl SequentialIds {
+    pub fn new() -> Self {
+        SequentialIds::default()
+    }
+}
+
+#[cfg(feature = "testing")]
+impl IdSource for SequentialIds {
+    fn next_id(&self) -> uuid::Uuid {
+        let id = self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+        uuid::Uuid::from_u128(id as u128)
+    }
+}
+
+///
+/// A clock that always returns the same time, for snapshot tests
+///
+#[cfg(feature = "testing")]
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub chrono::DateTime<chrono::Utc>);
+
+#[cfg(feature = "testing")]
+impl Clock for FixedClock {
+    fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        self.0
+    }
+}
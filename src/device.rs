@@ -1,5 +1,5 @@
 use super::Utils;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[cfg(windows)]
 const OS_NAME: &str = "Windows";
@@ -8,30 +8,240 @@ const OS_NAME: &str = "macOS";
 #[cfg(target_os = "linux")]
 const OS_NAME: &str = "Linux";
 
-#[derive(Serialize, Clone)]
+const SDK_NAME: &str = "appcenter.custom";
+const SDK_VERSION: &str = "3.2.2";
+
+///
+/// The device and application the report originates from
+///
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct Device {
+pub struct Device {
     model: String,
-    sdk_name: &'static str,
-    sdk_version: &'static str,
-    os_name: &'static str,
+    sdk_name: String,
+    sdk_version: String,
+    os_name: String,
     os_version: String,
+    os_build: String,
     locale: String,
     app_version: String,
     app_build: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    carrier_country: Option<String>,
+    // Windows only, for correlation with Windows Error Reporting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    windows_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    windows_build: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu_bits: Option<u32>,
 }
 
 impl Device {
-    pub(crate) fn current_device(app_version: &str, app_build: &Option<String>) -> Self {
+    pub(crate) fn current_device(
+        app_version: &str,
+        app_build: &Option<String>,
+        country_code: Option<String>,
+    ) -> Self {
+        #[cfg(all(feature = "enabled", windows))]
+        let (windows_version, windows_build, cpu_bits) = {
+            let (major, minor, build) = Utils::get_nt_version();
+            let cpu_bits = if Utils::is_64bit_os() { 64 } else { 32 };
+            (
+                Some(format!("{}.{}", major, minor)),
+                Some(build),
+                Some(cpu_bits),
+            )
+        };
+        #[cfg(not(all(feature = "enabled", windows)))]
+        let (windows_version, windows_build, cpu_bits) = (None, None, None);
+
         Device {
             model: Utils::get_model(),
-            sdk_name: "appcenter.custom",
-            sdk_version: "3.2.2",
-            os_name: OS_NAME,
+            sdk_name: SDK_NAME.to_string(),
+            sdk_version: SDK_VERSION.to_string(),
+            os_name: OS_NAME.to_string(),
             os_version: Utils::get_os_version(),
+            os_build: Utils::get_os_build(),
             locale: Utils::get_locale(),
             app_version: app_version.to_string(),
-            app_build: app_build.clone().unwrap_or(String::new()),
+            app_build: app_build.clone().unwrap_or_default(),
+            carrier_country: country_code,
+            windows_version,
+            windows_build,
+            cpu_bits,
+        }
+    }
+
+    pub(crate) fn set_country_code(&mut self, country_code: Option<String>) {
+        self.carrier_country = country_code;
+    }
+
+    // The fields the ingestion API requires to be set, with their JSON names. The OS version,
+    // the locale and the app build are required too, but may be empty.
+    pub(crate) fn required_fields(&self) -> [(&'static str, &str); 4] {
+        [
+            ("sdkName", &self.sdk_name),
+            ("sdkVersion", &self.sdk_version),
+            ("osName", &self.os_name),
+            ("appVersion", &self.app_version),
+        ]
+    }
+}
+
+impl Device {
+    /// The hardware model
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// The name of the operating system
+    pub fn os_name(&self) -> &str {
+        &self.os_name
+    }
+
+    /// The version of the operating system
+    pub fn os_version(&self) -> &str {
+        &self.os_version
+    }
+
+    /// The build of the operating system: the kernel release on Linux, the feature update
+    /// on Windows and the build number on macOS
+    pub fn os_build(&self) -> &str {
+        &self.os_build
+    }
+
+    /// The system locale
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// The application version
+    pub fn app_version(&self) -> &str {
+        &self.app_version
+    }
+
+    /// The application build number, empty if not set
+    pub fn app_build(&self) -> &str {
+        &self.app_build
+    }
+
+    /// The two letter ISO country code, sent as `carrierCountry` like the official SDKs do
+    pub fn country_code(&self) -> Option<&str> {
+        self.carrier_country.as_deref()
+    }
+
+    /// The major and minor version of Windows, such as `10.0`, `None` on other systems
+    pub fn windows_version(&self) -> Option<&str> {
+        self.windows_version.as_deref()
+    }
+
+    /// The build number of Windows, such as `22621`, `None` on other systems
+    pub fn windows_build(&self) -> Option<u32> {
+        self.windows_build
+    }
+
+    /// True if Windows is 64-bit, even when the process is 32-bit, `None` on other systems
+    pub fn is_64bit(&self) -> Option<bool> {
+        self.cpu_bits.map(|bits| bits == 64)
+    }
+}
+
+///
+/// Describe the device explicitly instead of querying the operating system, for deterministic
+/// reports in tests or to report a device other than the host. Fields that are not set are
+/// left empty, except for the SDK name and version which default to the ones of this crate.
+///
+#[derive(Debug, Clone, Default)]
+pub struct DeviceBuilder {
+    model: String,
+    os_name: String,
+    os_version: String,
+    os_build: String,
+    locale: String,
+    app_version: String,
+    app_build: String,
+    sdk_name: Option<String>,
+    sdk_version: Option<String>,
+}
+
+impl DeviceBuilder {
+    /// A builder with every field empty
+    pub fn new() -> Self {
+        DeviceBuilder::default()
+    }
+
+    /// The hardware model
+    pub fn model<S: Into<String>>(mut self, model: S) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// The name of the operating system
+    pub fn os_name<S: Into<String>>(mut self, os_name: S) -> Self {
+        self.os_name = os_name.into();
+        self
+    }
+
+    /// The version of the operating system
+    pub fn os_version<S: Into<String>>(mut self, os_version: S) -> Self {
+        self.os_version = os_version.into();
+        self
+    }
+
+    /// The build of the operating system
+    pub fn os_build<S: Into<String>>(mut self, os_build: S) -> Self {
+        self.os_build = os_build.into();
+        self
+    }
+
+    /// The system locale
+    pub fn locale<S: Into<String>>(mut self, locale: S) -> Self {
+        self.locale = locale.into();
+        self
+    }
+
+    /// The application version
+    pub fn app_version<S: Into<String>>(mut self, app_version: S) -> Self {
+        self.app_version = app_version.into();
+        self
+    }
+
+    /// The application build number
+    pub fn app_build<S: Into<String>>(mut self, app_build: S) -> Self {
+        self.app_build = app_build.into();
+        self
+    }
+
+    /// The name of the SDK that sent the report
+    pub fn sdk_name<S: Into<String>>(mut self, sdk_name: S) -> Self {
+        self.sdk_name = Some(sdk_name.into());
+        self
+    }
+
+    /// The version of the SDK that sent the report
+    pub fn sdk_version<S: Into<String>>(mut self, sdk_version: S) -> Self {
+        self.sdk_version = Some(sdk_version.into());
+        self
+    }
+
+    /// Build the device
+    pub fn build(self) -> Device {
+        Device {
+            model: self.model,
+            sdk_name: self.sdk_name.unwrap_or_else(|| SDK_NAME.to_string()),
+            sdk_version: self.sdk_version.unwrap_or_else(|| SDK_VERSION.to_string()),
+            os_name: self.os_name,
+            os_version: self.os_version,
+            os_build: self.os_build,
+            locale: self.locale,
+            app_version: self.app_version,
+            app_build: self.app_build,
+            carrier_country: None,
+            windows_version: None,
+            windows_build: None,
+            cpu_bits: None,
         }
     }
 }
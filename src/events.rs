@@ -0,0 +1,666 @@
+use super::{truncate, MAX_EVENT_NAME_LENGTH, MAX_PROPERTIES, MAX_PROPERTY_LENGTH};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+///
+/// Properties of an analytics event with typed values, so numbers can be broken down in the
+/// portal. Setting a key again replaces its value. The limits on the number of properties and
+/// on the length of keys and string values are applied when the event is tracked, see
+/// `EventValidation`.
+///
+/// ```no_run
+/// let app_center = app_center::start!("00000000-0000-0000-0000-000000000000");
+/// let properties = app_center::EventProperties::new()
+///     .string("format", "pdf")
+///     .long("pages", 12)
+///     .bool("compressed", true);
+/// app_center.track_event_with_properties("export_completed", properties)?;
+/// # Ok::<(), app_center::EventValidationError>(())
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventProperties {
+    properties: Vec<TypedProperty>,
+}
+
+// An entry of `typedProperties` in the ingestion schema
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum TypedProperty {
+    String {
+        name: String,
+        value: String,
+    },
+    Long {
+        name: String,
+        value: i64,
+    },
+    Double {
+        name: String,
+        value: f64,
+    },
+    Boolean {
+        name: String,
+        value: bool,
+    },
+    DateTime {
+        name: String,
+        value: chrono::DateTime<chrono::Utc>,
+    },
+}
+
+impl TypedProperty {
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            TypedProperty::String { name, .. }
+            | TypedProperty::Long { name, .. }
+            | TypedProperty::Double { name, .. }
+            | TypedProperty::Boolean { name, .. }
+            | TypedProperty::DateTime { name, .. } => name,
+        }
+    }
+
+    // Check the key, and the value when it is a string
+    fn validate(self, mode: EventValidation) -> Result<Self, EventValidationError> {
+        Ok(match self {
+            TypedProperty::String { name, value } => {
+                let name = mode.key(name)?;
+                let value = mode.value(&name, value)?;
+                TypedProperty::String { name, value }
+            }
+            TypedProperty::Long { name, value } => TypedProperty::Long {
+                name: mode.key(name)?,
+                value,
+            },
+            TypedProperty::Double { name, value } => TypedProperty::Double {
+                name: mode.key(name)?,
+                value,
+            },
+            TypedProperty::Boolean { name, value } => TypedProperty::Boolean {
+                name: mode.key(name)?,
+                value,
+            },
+            TypedProperty::DateTime { name, value } => TypedProperty::DateTime {
+                name: mode.key(name)?,
+                value,
+            },
+        })
+    }
+}
+
+///
+/// What `track_event` does with events that exceed the limits documented by AppCenter:
+/// names of at most 256 characters, at most 20 properties, keys and string values of at
+/// most 125 characters, and no control characters in names and keys
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EventValidation {
+    /// Reject the event with an `EventValidationError`
+    Strict,
+    /// Truncate names, keys and values, drop the extra properties and the control characters,
+    /// and only reject events without a name
+    #[default]
+    Truncate,
+}
+
+///
+/// The limit of AppCenter an event exceeds
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventValidationError {
+    /// The event name is empty
+    EmptyName,
+    /// The event name is longer than `max` characters
+    NameTooLong { length: usize, max: usize },
+    /// The event has more than `max` properties
+    TooManyProperties { count: usize, max: usize },
+    /// The property key is longer than `max` characters
+    KeyTooLong { key: String, max: usize },
+    /// The value of the property `key` is longer than `max` characters
+    ValueTooLong { key: String, max: usize },
+    /// The event name or property key contains a control character
+    InvalidCharacter { text: String },
+}
+
+impl std::fmt::Display for EventValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventValidationError::EmptyName => write!(f, "the event name is empty"),
+            EventValidationError::NameTooLong { length, max } => write!(
+                f,
+                "the event name is {} characters long, the limit is {}",
+                length, max
+            ),
+            EventValidationError::TooManyProperties { count, max } => write!(
+                f,
+                "the event has {} properties, the limit is {}",
+                count, max
+            ),
+            EventValidationError::KeyTooLong { key, max } => write!(
+                f,
+                "the property key {:?} is longer than {} characters",
+                key, max
+            ),
+            EventValidationError::ValueTooLong { key, max } => write!(
+                f,
+                "the value of the property {:?} is longer than {} characters",
+                key, max
+            ),
+            EventValidationError::InvalidCharacter { text } => {
+                write!(f, "{:?} contains a control character", text)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EventValidationError {}
+
+impl EventValidation {
+    pub(crate) fn name(self, name: &str) -> Result<String, EventValidationError> {
+        let name = self.characters(name.to_string())?;
+        let length = name.chars().count();
+
+        if name.is_empty() {
+            Err(EventValidationError::EmptyName)
+        } else if length <= MAX_EVENT_NAME_LENGTH {
+            Ok(name)
+        } else if self == EventValidation::Strict {
+            Err(EventValidationError::NameTooLong {
+                length,
+                max: MAX_EVENT_NAME_LENGTH,
+            })
+        } else {
+            app_log!(Debug, "Truncating the event name {:?}", name);
+            Ok(truncate(name, MAX_EVENT_NAME_LENGTH))
+        }
+    }
+
+    pub(crate) fn properties(
+        self,
+        properties: BTreeMap<String, String>,
+    ) -> Result<BTreeMap<String, String>, EventValidationError> {
+        let count = self.count(properties.len())?;
+        properties
+            .into_iter()
+            .take(count)
+            .map(|(key, value)| {
+                let key = self.key(key)?;
+                let value = self.value(&key, value)?;
+                Ok((key, value))
+            })
+            .collect()
+    }
+
+    pub(crate) fn typed_properties(
+        self,
+        properties: EventProperties,
+    ) -> Result<Vec<TypedProperty>, EventValidationError> {
+        let count = self.count(properties.len())?;
+        properties
+            .properties
+            .into_iter()
+            .take(count)
+            .map(|property| property.validate(self))
+            .collect()
+    }
+
+    // The number of properties that are kept
+    fn count(self, count: usize) -> Result<usize, EventValidationError> {
+        if count <= MAX_PROPERTIES {
+            Ok(count)
+        } else if self == EventValidation::Strict {
+            Err(EventValidationError::TooManyProperties {
+                count,
+                max: MAX_PROPERTIES,
+            })
+        } else {
+            app_log!(
+                Debug,
+                "Dropping {} event properties",
+                count - MAX_PROPERTIES
+            );
+            Ok(MAX_PROPERTIES)
+        }
+    }
+
+    fn key(self, key: String) -> Result<String, EventValidationError> {
+        let key = self.characters(key)?;
+        if key.chars().count() <= MAX_PROPERTY_LENGTH {
+            Ok(key)
+        } else if self == EventValidation::Strict {
+            Err(EventValidationError::KeyTooLong {
+                key,
+                max: MAX_PROPERTY_LENGTH,
+            })
+        } else {
+            app_log!(Debug, "Truncating the event property key {:?}", key);
+            Ok(truncate(key, MAX_PROPERTY_LENGTH))
+        }
+    }
+
+    fn value(self, key: &str, value: String) -> Result<String, EventValidationError> {
+        if value.chars().count() <= MAX_PROPERTY_LENGTH {
+            Ok(value)
+        } else if self == EventValidation::Strict {
+            Err(EventValidationError::ValueTooLong {
+                key: key.to_string(),
+                max: MAX_PROPERTY_LENGTH,
+            })
+        } else {
+            app_log!(
+                Debug,
+                "Truncating the value of the event property {:?}",
+                key
+            );
+            Ok(truncate(value, MAX_PROPERTY_LENGTH))
+        }
+    }
+
+    fn characters(self, text: String) -> Result<String, EventValidationError> {
+        if !text.chars().any(char::is_control) {
+            Ok(text)
+        } else if self == EventValidation::Strict {
+            Err(EventValidationError::InvalidCharacter { text })
+        } else {
+            app_log!(Debug, "Removing control characters from {:?}", text);
+            Ok(text.chars().filter(|c| !c.is_control()).collect())
+        }
+    }
+}
+
+impl EventProperties {
+    /// No properties
+    pub fn new() -> Self {
+        EventProperties::default()
+    }
+
+    /// A string value
+    pub fn string<K: Into<String>, V: Into<String>>(self, key: K, value: V) -> Self {
+        self.insert(TypedProperty::String {
+            name: key.into(),
+            value: value.into(),
+        })
+    }
+
+    /// An integer value
+    pub fn long<K: Into<String>>(self, key: K, value: i64) -> Self {
+        self.insert(TypedProperty::Long {
+            name: key.into(),
+            value,
+        })
+    }
+
+    /// A floating point value
+    pub fn double<K: Into<String>>(self, key: K, value: f64) -> Self {
+        self.insert(TypedProperty::Double {
+            name: key.into(),
+            value,
+        })
+    }
+
+    /// A boolean value
+    pub fn bool<K: Into<String>>(self, key: K, value: bool) -> Self {
+        self.insert(TypedProperty::Boolean {
+            name: key.into(),
+            value,
+        })
+    }
+
+    /// A date and time value
+    pub fn date_time<K: Into<String>>(self, key: K, value: chrono::DateTime<chrono::Utc>) -> Self {
+        self.insert(TypedProperty::DateTime {
+            name: key.into(),
+            value,
+        })
+    }
+
+    /// The number of properties
+    pub fn len(&self) -> usize {
+        self.properties.len()
+    }
+
+    /// True if there are no properties
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    fn insert(mut self, property: TypedProperty) -> Self {
+        let existing = self
+            .properties
+            .iter()
+            .position(|p| p.name() == property.name());
+
+        match existing {
+            Some(i) => self.properties[i] = property,
+            None => self.properties.push(property),
+        }
+        self
+    }
+}
+
+// Events waiting for the background sender, shared with its thread so waiting for events
+// doesn't keep the instance alive
+pub(crate) struct EventQueue<T> {
+    state: Mutex<QueueState<T>>,
+    ready: Condvar,
+}
+
+struct QueueState<T> {
+    events: VecDeque<T>,
+    closed: bool,
+}
+
+impl<T> EventQueue<T> {
+    pub(crate) fn new() -> Self {
+        EventQueue {
+            state: Mutex::new(QueueState {
+                events: VecDeque::new(),
+                closed: false,
+            }),
+            ready: Condvar::new(),
+        }
+    }
+
+    // Returns the number of queued events
+    pub(crate) fn push(&self, event: T) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.events.push_back(event);
+        self.ready.notify_one();
+        state.events.len()
+    }
+
+    // Wait up to `idle` for the first event, then until `max` events are queued or `interval`
+    // has passed. Empty when no event came, None once the queue is closed.
+    pub(crate) fn next_batch(
+        &self,
+        max: usize,
+        interval: Duration,
+        idle: Duration,
+    ) -> Option<Vec<T>> {
+        let mut state = self.state.lock().unwrap();
+        let idle_deadline = Instant::now() + idle;
+        while state.events.is_empty() && !state.closed {
+            let timeout = idle_deadline.saturating_duration_since(Instant::now());
+            if timeout.is_zero() {
+                return Some(Vec::new());
+            }
+            state = self.ready.wait_timeout(state, timeout).unwrap().0;
+        }
+
+        let deadline = Instant::now() + interval;
+        while state.events.len() < max && !state.closed {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if timeout.is_zero() {
+                break;
+            }
+            state = self.ready.wait_timeout(state, timeout).unwrap().0;
+        }
+
+        if state.closed {
+            return None;
+        }
+        let count = state.events.len().min(max);
+        Some(state.events.drain(..count).collect())
+    }
+
+    // Remove the `count` oldest events
+    pub(crate) fn take(&self, count: usize) -> Vec<T> {
+        let mut state = self.state.lock().unwrap();
+        let count = state.events.len().min(count);
+        state.events.drain(..count).collect()
+    }
+
+    pub(crate) fn take_all(&self) -> Vec<T> {
+        self.take(usize::MAX)
+    }
+
+    // A thread that panicked holding the lock poisoned it, the events are still intact
+    pub(crate) fn clear_poison(&self) {
+        self.state.clear_poison();
+    }
+
+    // Poison the lock like a thread panicking while holding it, and wake the sender thread
+    #[cfg(all(test, feature = "enabled"))]
+    pub(crate) fn poison(&self)
+    where
+        T: Send,
+    {
+        std::thread::scope(|scope| {
+            let _ = scope
+                .spawn(|| {
+                    let _state = self.state.lock();
+                    panic!("poisoning the event queue");
+                })
+                .join();
+        });
+        self.ready.notify_all();
+    }
+
+    // Stop the sender thread, the events still queued are left for `take_all`
+    pub(crate) fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.ready.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRICT: EventValidation = EventValidation::Strict;
+    const TRUNCATE: EventValidation = EventValidation::Truncate;
+
+    fn properties(count: usize) -> BTreeMap<String, String> {
+        (0..count)
+            .map(|i| (format!("key{:02}", i), i.to_string()))
+            .collect()
+    }
+
+    fn typed_properties(count: usize) -> EventProperties {
+        (0..count).fold(EventProperties::new(), |properties, i| {
+            properties.long(format!("key{:02}", i), i as i64)
+        })
+    }
+
+    #[test]
+    fn limits_are_the_ones_documented_by_app_center() {
+        assert_eq!(MAX_EVENT_NAME_LENGTH, 256);
+        assert_eq!(MAX_PROPERTIES, 20);
+        assert_eq!(MAX_PROPERTY_LENGTH, 125);
+    }
+
+    #[test]
+    fn name_at_the_limit_is_kept() {
+        let name = "n".repeat(256);
+        assert_eq!(STRICT.name(&name), Ok(name.clone()));
+        assert_eq!(TRUNCATE.name(&name), Ok(name));
+    }
+
+    #[test]
+    fn name_over_the_limit() {
+        let name = "n".repeat(257);
+        assert_eq!(
+            STRICT.name(&name),
+            Err(EventValidationError::NameTooLong {
+                length: 257,
+                max: 256
+            })
+        );
+        assert_eq!(TRUNCATE.name(&name), Ok("n".repeat(256)));
+    }
+
+    #[test]
+    fn properties_at_the_limit_are_kept() {
+        assert_eq!(STRICT.properties(properties(20)), Ok(properties(20)));
+        assert_eq!(TRUNCATE.properties(properties(20)), Ok(properties(20)));
+        assert_eq!(
+            STRICT.typed_properties(typed_properties(20)).unwrap().len(),
+            20
+        );
+        assert_eq!(
+            TRUNCATE
+                .typed_properties(typed_properties(20))
+                .unwrap()
+                .len(),
+            20
+        );
+    }
+
+    #[test]
+    fn properties_over_the_limit() {
+        let error = EventValidationError::TooManyProperties { count: 21, max: 20 };
+        assert_eq!(STRICT.properties(properties(21)), Err(error.clone()));
+        assert_eq!(STRICT.typed_properties(typed_properties(21)), Err(error));
+
+        // The first properties in key order are kept
+        assert_eq!(TRUNCATE.properties(properties(21)), Ok(properties(20)));
+        let kept = TRUNCATE.typed_properties(typed_properties(21)).unwrap();
+        assert_eq!(kept.len(), 20);
+        assert_eq!(kept.last().map(TypedProperty::name), Some("key19"));
+    }
+
+    #[test]
+    fn key_and_value_at_the_limit_are_kept() {
+        let limit = BTreeMap::from([("k".repeat(125), "v".repeat(125))]);
+        assert_eq!(STRICT.properties(limit.clone()), Ok(limit.clone()));
+        assert_eq!(TRUNCATE.properties(limit.clone()), Ok(limit));
+    }
+
+    #[test]
+    fn key_over_the_limit() {
+        let long_key = BTreeMap::from([("k".repeat(126), "value".to_string())]);
+        assert_eq!(
+            STRICT.properties(long_key.clone()),
+            Err(EventValidationError::KeyTooLong {
+                key: "k".repeat(126),
+                max: 125
+            })
+        );
+        assert_eq!(
+            TRUNCATE.properties(long_key),
+            Ok(BTreeMap::from([("k".repeat(125), "value".to_string())]))
+        );
+
+        let typed = EventProperties::new().bool("k".repeat(126), true);
+        assert!(matches!(
+            STRICT.typed_properties(typed.clone()),
+            Err(EventValidationError::KeyTooLong { .. })
+        ));
+        assert_eq!(
+            TRUNCATE.typed_properties(typed),
+            Ok(vec![TypedProperty::Boolean {
+                name: "k".repeat(125),
+                value: true
+            }])
+        );
+    }
+
+    #[test]
+    fn value_over_the_limit() {
+        let long_value = BTreeMap::from([("key".to_string(), "v".repeat(126))]);
+        assert_eq!(
+            STRICT.properties(long_value.clone()),
+            Err(EventValidationError::ValueTooLong {
+                key: "key".to_string(),
+                max: 125
+            })
+        );
+        assert_eq!(
+            TRUNCATE.properties(long_value),
+            Ok(BTreeMap::from([("key".to_string(), "v".repeat(125))]))
+        );
+
+        let typed = EventProperties::new().string("key", "v".repeat(126));
+        assert!(matches!(
+            STRICT.typed_properties(typed.clone()),
+            Err(EventValidationError::ValueTooLong { .. })
+        ));
+        assert_eq!(
+            TRUNCATE.typed_properties(typed),
+            Ok(vec![TypedProperty::String {
+                name: "key".to_string(),
+                value: "v".repeat(125)
+            }])
+        );
+    }
+
+    #[test]
+    fn limits_count_characters_not_bytes() {
+        // 3 bytes per character, well over the limits in bytes but within them in characters
+        let name = "€".repeat(256);
+        assert_eq!(STRICT.name(&name), Ok(name.clone()));
+        let limit = BTreeMap::from([("€".repeat(125), "€".repeat(125))]);
+        assert_eq!(STRICT.properties(limit.clone()), Ok(limit));
+
+        // Truncation keeps whole characters
+        assert_eq!(TRUNCATE.name(&"€".repeat(257)), Ok(name));
+        let long = BTreeMap::from([("é".repeat(126), "€".repeat(126))]);
+        let truncated = TRUNCATE.properties(long).unwrap();
+        let (key, value) = truncated.iter().next().unwrap();
+        assert_eq!(key, &"é".repeat(125));
+        assert_eq!(value, &"€".repeat(125));
+        assert_eq!(value.len(), 375);
+    }
+
+    // The serialized `typedProperties` entry of the only property of `properties`
+    fn snapshot(properties: EventProperties) -> String {
+        let typed = STRICT.typed_properties(properties).unwrap();
+        assert_eq!(typed.len(), 1);
+        serde_json::to_string(&typed[0]).unwrap()
+    }
+
+    #[test]
+    fn string_property_snapshot() {
+        assert_eq!(
+            snapshot(EventProperties::new().string("format", "pdf")),
+            r#"{"type":"string","name":"format","value":"pdf"}"#
+        );
+    }
+
+    #[test]
+    fn long_property_snapshot() {
+        assert_eq!(
+            snapshot(EventProperties::new().long("pages", -12)),
+            r#"{"type":"long","name":"pages","value":-12}"#
+        );
+        assert_eq!(
+            snapshot(EventProperties::new().long("bytes", i64::MAX)),
+            r#"{"type":"long","name":"bytes","value":9223372036854775807}"#
+        );
+    }
+
+    #[test]
+    fn double_property_snapshot() {
+        assert_eq!(
+            snapshot(EventProperties::new().double("ratio", 0.25)),
+            r#"{"type":"double","name":"ratio","value":0.25}"#
+        );
+        // Stays a number, not an integer
+        assert_eq!(
+            snapshot(EventProperties::new().double("scale", 2.0)),
+            r#"{"type":"double","name":"scale","value":2.0}"#
+        );
+    }
+
+    #[test]
+    fn boolean_property_snapshot() {
+        assert_eq!(
+            snapshot(EventProperties::new().bool("compressed", true)),
+            r#"{"type":"boolean","name":"compressed","value":true}"#
+        );
+    }
+
+    #[test]
+    fn date_time_property_snapshot() {
+        use chrono::TimeZone;
+
+        let started = chrono::Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        assert_eq!(
+            snapshot(EventProperties::new().date_time("started", started)),
+            r#"{"type":"dateTime","name":"started","value":"2024-01-02T03:04:05Z"}"#
+        );
+    }
+}
@@ -0,0 +1,830 @@
+use super::scrub::{ScrubField, ScrubTarget};
+use super::Utils;
+#[cfg(feature = "enabled")]
+use backtrace::Backtrace;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::panic::PanicHookInfo;
+use std::path::Path;
+
+///
+/// Controls when the symbols of the backtrace of a handled error are resolved. The panic hook
+/// always captures only the addresses, the symbols are resolved on the reporting thread.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResolutionMode {
+    /// Resolve the symbols as soon as the backtrace is captured
+    #[default]
+    Eager,
+    /// Capture only the addresses and resolve the symbols just before the report is
+    /// serialized
+    Lazy,
+}
+
+///
+/// The exception part of a crash report: the panic message and the backtrace
+///
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppCenterException {
+    r#type: String,
+    message: String,
+    frames: Frames,
+    // The cause of the error, which may have a cause itself
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    inner_exceptions: Vec<AppCenterException>,
+}
+
+// How the frames are captured and converted
+#[cfg_attr(not(feature = "enabled"), allow(dead_code))]
+#[derive(Clone, Default)]
+pub(crate) struct FrameOptions {
+    pub(crate) resolution_mode: ResolutionMode,
+    pub(crate) include_raw_symbols: bool,
+    // Frames whose method or file name matches one of these are left out
+    pub(crate) skip_patterns: Vec<String>,
+    // Stripped from the file names, longest first
+    pub(crate) path_prefixes: Vec<String>,
+}
+
+impl FrameOptions {
+    // The options of an instance built with the defaults of `AppCenterBuilder`, resolved
+    // eagerly for the custom panic hooks
+    fn builder_defaults() -> Self {
+        let mut path_prefixes = default_path_prefixes();
+        sort_path_prefixes(&mut path_prefixes);
+        FrameOptions {
+            path_prefixes,
+            ..FrameOptions::default()
+        }
+    }
+
+    // Patterns with wildcards are globs, the others match as substrings
+    #[cfg_attr(not(feature = "enabled"), allow(dead_code))]
+    fn is_skipped(&self, frame: &ExceptionFrame) -> bool {
+        let matches = |s: &str| {
+            self.skip_patterns.iter().any(|p| {
+                if p.contains(['*', '?']) {
+                    Utils::glob_match(p, s)
+                } else {
+                    s.contains(p.as_str())
+                }
+            })
+        };
+
+        frame.method_name.as_deref().is_some_and(matches)
+            || frame.file_name.as_deref().is_some_and(matches)
+    }
+
+    // The path relative to the first matching prefix, unchanged when none matches
+    fn strip_path(&self, path: &str) -> String {
+        strip_path_prefix(path, &self.path_prefixes).unwrap_or_else(|| path.to_string())
+    }
+}
+
+// The cargo home, the rustup toolchain and the directory holding the sources of the build
+// machine, and the cargo home of this machine
+pub(crate) fn default_path_prefixes() -> Vec<String> {
+    let mut prefixes: Vec<String> = [
+        env!("APP_CENTER_BUILD_CARGO_HOME"),
+        env!("APP_CENTER_BUILD_TOOLCHAIN_DIR"),
+        env!("APP_CENTER_BUILD_WORKSPACE_PARENT"),
+    ]
+    .iter()
+    .filter(|p| !p.is_empty())
+    .map(|p| p.to_string())
+    .collect();
+
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .or_else(|| std::env::var_os("HOME").map(|h| Path::new(&h).join(".cargo").into()))
+        .or_else(|| std::env::var_os("USERPROFILE").map(|h| Path::new(&h).join(".cargo").into()));
+    if let Some(cargo_home) = cargo_home.and_then(|h| h.into_string().ok()) {
+        prefixes.push(cargo_home);
+    }
+
+    prefixes
+}
+
+// Longest first so the most specific prefix is stripped, without duplicates
+pub(crate) fn sort_path_prefixes(prefixes: &mut Vec<String>) {
+    prefixes.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    prefixes.dedup();
+}
+
+// Strip the first matching prefix, the rest is joined with `/` so it is the same on every
+// machine, and the registry index is left out: `registry/serde-1.0.0/src/de.rs`.
+// Windows paths compare case-insensitively, and verbatim `\\?\` paths match their plain form.
+// Prefixes only match whole components, `/home/al` is not stripped from `/home/alice`.
+fn strip_path_prefix(path: &str, prefixes: &[String]) -> Option<String> {
+    let path = normalize_separators(path);
+
+    prefixes.iter().find_map(|prefix| {
+        let prefix = normalize_separators(prefix);
+        let prefix = prefix.trim_end_matches('/');
+        if prefix.is_empty() {
+            return None;
+        }
+
+        let head = path.get(..prefix.len())?;
+        // The prefix must end on a separator of the path
+        let rest = path[prefix.len()..].strip_prefix('/')?;
+        let matches = if is_windows_path(prefix) {
+            head.eq_ignore_ascii_case(prefix)
+        } else {
+            head == prefix
+        };
+        if !matches {
+            return None;
+        }
+
+        Some(match rest.strip_prefix("registry/src/") {
+            Some(index) => match index.split_once('/') {
+                Some((_, crate_path)) => format!("registry/{}", crate_path),
+                None => rest.to_string(),
+            },
+            None => rest.to_string(),
+        })
+    })
+}
+
+fn normalize_separators(path: &str) -> String {
+    let path = if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", unc)
+    } else {
+        path.strip_prefix(r"\\?\").unwrap_or(path).to_string()
+    };
+    path.replace('\\', "/")
+}
+
+// A drive letter or a UNC path, once the separators are normalized
+fn is_windows_path(path: &str) -> bool {
+    path.starts_with("//") || path.as_bytes().get(1) == Some(&b':')
+}
+
+// Panics that are not reported, matched by message substring or by the module of the
+// top application frames
+#[derive(Clone, Default)]
+pub(crate) struct IgnoreList {
+    pub(crate) messages: Vec<String>,
+    pub(crate) modules: Vec<String>,
+}
+
+impl IgnoreList {
+    pub(crate) fn matches(&self, exception: &AppCenterException) -> bool {
+        if self
+            .messages
+            .iter()
+            .any(|m| exception.message.contains(m.as_str()))
+        {
+            return true;
+        }
+
+        exception
+            .app_frames()
+            .filter_map(|f| f.method_name())
+            .any(|name| self.modules.iter().any(|m| name.starts_with(m.as_str())))
+    }
+}
+
+// Frames are either resolved already, or waiting for resolution
+pub(crate) enum Frames {
+    Resolved(Vec<ExceptionFrame>),
+    #[cfg(feature = "enabled")]
+    Unresolved(Backtrace, FrameOptions),
+}
+
+///
+/// A single frame of the backtrace
+///
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub struct ExceptionFrame {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    method_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line_number: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    raw_symbol: Option<String>,
+    // Set for every symbol of a frame but the last one, when functions were inlined. The
+    // innermost inlined function comes first, the function the frame belongs to last.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    is_inline: bool,
+}
+
+// Only the fields that are stable between runs are hashed, the address depends on where
+// the binary was loaded
+impl std::hash::Hash for ExceptionFrame {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.method_name.hash(state);
+        self.file_name.hash(state);
+        self.line_number.hash(state);
+    }
+}
+
+impl ExceptionFrame {
+    /// A frame of a backtrace obtained elsewhere, for example from a C library, see
+    /// `ReportBuilder::frames`
+    pub fn new(
+        method_name: Option<String>,
+        file_name: Option<String>,
+        line_number: Option<u32>,
+    ) -> Self {
+        ExceptionFrame {
+            method_name,
+            line_number,
+            file_name,
+            address: None,
+            raw_symbol: None,
+            is_inline: false,
+        }
+    }
+
+    /// A hash of the method name, file name and line number of the frame, which are the
+    /// same across runs of the same binary
+    pub fn stable_hash(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The demangled name of the function
+    pub fn method_name(&self) -> Option<&str> {
+        self.method_name.as_deref()
+    }
+
+    /// The source file of the function
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// The line in the source file
+    pub fn line_number(&self) -> Option<u32> {
+        self.line_number
+    }
+
+    /// True if the function was inlined into its caller, the function of the next frame
+    pub fn is_inline(&self) -> bool {
+        self.is_inline
+    }
+
+    /// The mangled name of the function, only present when raw symbols are included
+    pub fn raw_symbol(&self) -> Option<&str> {
+        self.raw_symbol.as_deref()
+    }
+
+    /// Capture and resolve the backtrace of the current thread, for custom panic hooks, with
+    /// the default path prefixes stripped. Always empty without the `enabled` feature.
+    pub fn collect_backtrace() -> Vec<ExceptionFrame> {
+        #[cfg(feature = "enabled")]
+        return ExceptionFrame::from_backtrace(
+            &Backtrace::new(),
+            &FrameOptions::builder_defaults(),
+        );
+        #[cfg(not(feature = "enabled"))]
+        Vec::new()
+    }
+
+    // Frames of the panic machinery and of this crate, that are the same for every panic
+    fn is_runtime_frame(&self) -> bool {
+        const RUNTIME_PREFIXES: &[&str] = &[
+            "backtrace::",
+            "std::panicking",
+            "std::panic::",
+            "core::panicking",
+            "rust_begin_unwind",
+            "app_center::",
+            "<alloc::boxed::Box<F",
+        ];
+
+        self.method_name
+            .as_deref()
+            .is_none_or(|n| RUNTIME_PREFIXES.iter().any(|p| n.starts_with(p)))
+    }
+
+    #[cfg(feature = "enabled")]
+    fn from_backtrace(backtrace: &Backtrace, options: &FrameOptions) -> Vec<ExceptionFrame> {
+        let mut frames = Vec::new();
+
+        // A frame holds several symbols when functions were inlined into it
+        for frame in backtrace.frames().iter() {
+            let symbols = frame.symbols();
+            for (i, symbol) in symbols.iter().enumerate() {
+                let frame = ExceptionFrame {
+                    method_name: symbol.name().map(|n| format!("{}", n)),
+
+                    line_number: symbol.lineno(),
+
+                    file_name: symbol
+                        .filename()
+                        .and_then(|n| n.to_str())
+                        .map(|s| s.to_string()),
+
+                    address: None,
+
+                    raw_symbol: symbol
+                        .name()
+                        .filter(|_| options.include_raw_symbols)
+                        .map(|n| String::from_utf8_lossy(n.as_bytes()).to_string()),
+
+                    is_inline: i + 1 < symbols.len(),
+                };
+
+                if !options.is_skipped(&frame) {
+                    frames.push(ExceptionFrame {
+                        file_name: frame.file_name.as_deref().map(|n| options.strip_path(n)),
+                        ..frame
+                    });
+                }
+            }
+        }
+
+        frames
+    }
+}
+
+impl Frames {
+    // Nothing is captured without the `enabled` feature
+    #[cfg(not(feature = "enabled"))]
+    fn capture(_options: &FrameOptions) -> Self {
+        Frames::Resolved(Vec::new())
+    }
+
+    #[cfg(feature = "enabled")]
+    fn capture(options: &FrameOptions) -> Self {
+        match options.resolution_mode {
+            ResolutionMode::Eager => {
+                Frames::Resolved(ExceptionFrame::from_backtrace(&Backtrace::new(), options))
+            }
+            ResolutionMode::Lazy => {
+                Frames::Unresolved(Backtrace::new_unresolved(), options.clone())
+            }
+        }
+    }
+
+    fn resolve(&mut self) {
+        #[cfg(feature = "enabled")]
+        if let Frames::Unresolved(backtrace, options) = self {
+            backtrace.resolve();
+            *self = Frames::Resolved(ExceptionFrame::from_backtrace(backtrace, options));
+        }
+    }
+}
+
+impl Serialize for Frames {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Frames::Resolved(frames) => frames.serialize(s),
+            #[cfg(feature = "enabled")]
+            Frames::Unresolved(backtrace, options) => {
+                // Should have been resolved before serialization, but don't send garbage if it wasn't
+                let mut backtrace = backtrace.clone();
+                backtrace.resolve();
+                ExceptionFrame::from_backtrace(&backtrace, options).serialize(s)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Frames {
+    fn schema_name() -> String {
+        Vec::<ExceptionFrame>::schema_name()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        Vec::<ExceptionFrame>::json_schema(gen)
+    }
+}
+
+// Serialized frames are always resolved
+impl<'de> Deserialize<'de> for Frames {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        Vec::<ExceptionFrame>::deserialize(d).map(Frames::Resolved)
+    }
+}
+
+impl std::fmt::Debug for AppCenterException {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppCenterException")
+            .field("type", &self.r#type)
+            .field("message", &self.message)
+            .field("frames", &self.frames().len())
+            .field("inner_exceptions", &self.inner_exceptions)
+            .finish()
+    }
+}
+
+// Unresolved frames compare as empty, like `frames` returns them
+impl PartialEq for AppCenterException {
+    fn eq(&self, other: &Self) -> bool {
+        self.r#type == other.r#type
+            && self.message == other.message
+            && self.frames() == other.frames()
+            && self.inner_exceptions == other.inner_exceptions
+    }
+}
+
+impl AppCenterException {
+    pub(crate) fn new(panic_info: &PanicHookInfo, options: &FrameOptions) -> Self {
+        // Same wording as the default panic hook, so reports can be matched with the console
+        let thread = std::thread::current();
+        let payload = panic_info.payload();
+        let payload = match payload.downcast_ref::<&str>() {
+            Some(payload) => payload,
+            None => match payload.downcast_ref::<String>() {
+                Some(payload) => payload.as_str(),
+                None => "Box<dyn Any>",
+            },
+        };
+
+        let mut message = format!(
+            "thread '{}' panicked at '{}'",
+            thread.name().unwrap_or("<unnamed>"),
+            payload
+        );
+        if let Some(location) = panic_info.location() {
+            let file = options.strip_path(location.file());
+            message.push_str(&format!(", {}:{}", file, location.line()));
+        }
+
+        AppCenterException {
+            r#type: "panic".to_string(),
+            message,
+            frames: Frames::capture(options),
+            inner_exceptions: Vec::new(),
+        }
+    }
+
+    // The exception for a handled error, with the type name of the error and the backtrace
+    // of the calling thread
+    pub(crate) fn from_error<E: std::error::Error + ?Sized>(
+        error: &E,
+        options: &FrameOptions,
+    ) -> Self {
+        AppCenterException {
+            r#type: std::any::type_name::<E>().to_string(),
+            message: error.to_string(),
+            frames: Frames::capture(options),
+            inner_exceptions: Vec::new(),
+        }
+    }
+
+    // The exception of a report built with `ReportBuilder`, with the backtrace of the calling
+    // thread unless the frames are provided
+    pub(crate) fn from_parts(
+        r#type: String,
+        message: String,
+        frames: Option<Vec<ExceptionFrame>>,
+        options: &FrameOptions,
+    ) -> Self {
+        AppCenterException {
+            r#type,
+            message,
+            frames: match frames {
+                Some(frames) => Frames::Resolved(frames),
+                None => Frames::capture(options),
+            },
+            inner_exceptions: Vec::new(),
+        }
+    }
+
+    // The exception for a chain of errors, outermost first, as nested inner exceptions with
+    // the backtrace of the calling thread on the outermost. Error types are erased in the
+    // chain, so the types are only known for common errors and are `fallback_type` otherwise,
+    // the outermost exception takes the type of the root cause.
+    #[cfg_attr(not(any(feature = "anyhow", feature = "eyre")), allow(dead_code))]
+    pub(crate) fn from_chain<'e>(
+        chain: impl DoubleEndedIterator<Item = &'e (dyn std::error::Error + 'static)>,
+        fallback_type: &str,
+        options: &FrameOptions,
+    ) -> Self {
+        let mut exception: Option<AppCenterException> = None;
+        let mut root_type = None;
+
+        for error in chain.rev() {
+            let r#type = error_type_name(error).unwrap_or(fallback_type).to_string();
+            root_type.get_or_insert_with(|| r#type.clone());
+
+            exception = Some(AppCenterException {
+                r#type,
+                message: error.to_string(),
+                frames: Frames::Resolved(Vec::new()),
+                inner_exceptions: exception.into_iter().collect(),
+            });
+        }
+
+        let mut exception = exception.unwrap_or_else(|| AppCenterException {
+            r#type: fallback_type.to_string(),
+            message: String::new(),
+            frames: Frames::Resolved(Vec::new()),
+            inner_exceptions: Vec::new(),
+        });
+        if let Some(root_type) = root_type {
+            exception.r#type = root_type;
+        }
+        exception.frames = Frames::capture(options);
+        exception
+    }
+
+    /// Build the exception for a panic from inside a custom panic hook, with the backtrace
+    /// of the current thread resolved, like the hook installed by `AppCenter::start` does
+    /// with the default options: the default path prefixes are stripped, no frame is skipped
+    pub fn from_panic_info(panic_info: &PanicHookInfo) -> Self {
+        AppCenterException::new(panic_info, &FrameOptions::builder_defaults())
+    }
+
+    /// Resolve the symbols of a lazily captured backtrace
+    pub(crate) fn resolve(&mut self) {
+        self.frames.resolve();
+    }
+
+    /// The type of the exception, `"panic"` for panics
+    pub fn exception_type(&self) -> &str {
+        &self.r#type
+    }
+
+    /// Replace the type of the exception
+    pub fn set_exception_type(&mut self, exception_type: String) {
+        self.r#type = exception_type;
+    }
+
+    /// Replace the panic message, for example to redact sensitive data
+    pub fn set_message(&mut self, message: String) {
+        self.message = message;
+    }
+
+    /// Keep only the frames for which `f` returns true, resolving the backtrace if needed
+    #[cfg_attr(not(feature = "enabled"), allow(irrefutable_let_patterns))]
+    pub fn retain_frames<F: FnMut(&ExceptionFrame) -> bool>(&mut self, f: F) {
+        self.frames.resolve();
+        if let Frames::Resolved(frames) = &mut self.frames {
+            frames.retain(f);
+        }
+    }
+
+    // Run the scrubber over the type, the message and the file names of the frames
+    #[cfg_attr(not(feature = "enabled"), allow(irrefutable_let_patterns))]
+    pub(crate) fn scrub(&mut self, scrubber: &dyn Fn(&mut ScrubTarget)) {
+        scrubber(&mut ScrubTarget::text(
+            ScrubField::ExceptionType,
+            &mut self.r#type,
+        ));
+        scrubber(&mut ScrubTarget::text(
+            ScrubField::ExceptionMessage,
+            &mut self.message,
+        ));
+
+        self.frames.resolve();
+        if let Frames::Resolved(frames) = &mut self.frames {
+            for file_name in frames.iter_mut().filter_map(|f| f.file_name.as_mut()) {
+                scrubber(&mut ScrubTarget::text(ScrubField::FrameFileName, file_name));
+            }
+        }
+
+        for inner in &mut self.inner_exceptions {
+            inner.scrub(scrubber);
+        }
+    }
+
+    /// The panic message, formatted as `thread '<name>' panicked at '<payload>', <file>:<line>`
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The cause of the error, for handled errors reported with their chain of causes
+    pub fn inner_exceptions(&self) -> &[AppCenterException] {
+        &self.inner_exceptions
+    }
+
+    /// The frames of the backtrace, empty while the backtrace is not resolved
+    pub fn frames(&self) -> &[ExceptionFrame] {
+        match &self.frames {
+            Frames::Resolved(frames) => frames,
+            #[cfg(feature = "enabled")]
+            Frames::Unresolved(..) => &[],
+        }
+    }
+
+    // The first 3 frames outside of the panic machinery
+    fn app_frames(&self) -> impl Iterator<Item = &ExceptionFrame> {
+        self.frames()
+            .iter()
+            .filter(|f| !f.is_runtime_frame())
+            .take(3)
+    }
+
+    /// The default grouping key, a hash of the names of the first 3 frames outside of the
+    /// panic machinery
+    pub fn default_grouping_key(&self) -> String {
+        // FNV-1a, so the key is stable across platforms and compiler versions
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let names = self.app_frames().filter_map(|f| f.method_name());
+
+        for name in names {
+            for b in name.bytes().chain(std::iter::once(0)) {
+                hash ^= u64::from(b);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+
+        format!("{:016x}", hash)
+    }
+}
+
+// The type name of the errors of the standard library and of the dependencies that are
+// commonly wrapped in an error chain
+#[cfg_attr(not(any(feature = "anyhow", feature = "eyre")), allow(dead_code))]
+fn error_type_name(error: &(dyn std::error::Error + 'static)) -> Option<&'static str> {
+    fn name_of<E: std::error::Error + 'static>(
+        error: &(dyn std::error::Error + 'static),
+    ) -> Option<&'static str> {
+        error
+            .downcast_ref::<E>()
+            .map(|_| std::any::type_name::<E>())
+    }
+
+    name_of::<std::io::Error>(error)
+        .or_else(|| name_of::<std::fmt::Error>(error))
+        .or_else(|| name_of::<std::num::ParseIntError>(error))
+        .or_else(|| name_of::<std::num::ParseFloatError>(error))
+        .or_else(|| name_of::<std::num::TryFromIntError>(error))
+        .or_else(|| name_of::<std::str::ParseBoolError>(error))
+        .or_else(|| name_of::<std::str::Utf8Error>(error))
+        .or_else(|| name_of::<std::string::FromUtf8Error>(error))
+        .or_else(|| name_of::<std::net::AddrParseError>(error))
+        .or_else(|| name_of::<std::env::VarError>(error))
+        .or_else(|| name_of::<serde_json::Error>(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Inlined even without optimizations
+    #[cfg(feature = "enabled")]
+    #[inline(always)]
+    fn inlined_capture() -> Backtrace {
+        Backtrace::new()
+    }
+
+    #[cfg(feature = "enabled")]
+    #[inline(never)]
+    fn capture_caller() -> Backtrace {
+        inlined_capture()
+    }
+
+    #[test]
+    #[cfg(feature = "enabled")]
+    #[cfg_attr(
+        not(debug_assertions),
+        ignore = "inlined frames are only named with debug info"
+    )]
+    fn inlined_function_is_marked_inline() {
+        let frames = ExceptionFrame::from_backtrace(&capture_caller(), &FrameOptions::default());
+        let position = |name: &str| {
+            frames
+                .iter()
+                .position(|f| f.method_name().is_some_and(|n| n.contains(name)))
+                .unwrap_or_else(|| panic!("no frame for {}", name))
+        };
+
+        let inlined = position("inlined_capture");
+        let caller = position("capture_caller");
+        assert!(frames[inlined].is_inline());
+        assert!(!frames[caller].is_inline());
+        assert!(inlined < caller);
+    }
+
+    fn strip(path: &str, prefix: &str) -> Option<String> {
+        strip_path_prefix(path, &[prefix.to_string()])
+    }
+
+    #[test]
+    fn unix_prefix_is_stripped() {
+        let stripped = strip("/home/alice/project/src/main.rs", "/home/alice");
+        assert_eq!(stripped.as_deref(), Some("project/src/main.rs"));
+        let stripped = strip("/home/alice/project/src/main.rs", "/home/alice/");
+        assert_eq!(stripped.as_deref(), Some("project/src/main.rs"));
+        assert_eq!(strip("/Home/alice/main.rs", "/home/alice"), None);
+    }
+
+    #[test]
+    fn prefix_matches_whole_components() {
+        assert_eq!(strip("/home/alice/main.rs", "/home/al"), None);
+        assert_eq!(strip(r"C:\Users\alice\main.rs", r"C:\Users\al"), None);
+        assert_eq!(strip(r"\\server\shares\main.rs", r"\\server\share"), None);
+        // The prefix alone is not a file name
+        assert_eq!(strip("/home/alice", "/home/alice"), None);
+        // An empty or root prefix would strip everything
+        assert_eq!(strip("/home/alice/main.rs", "/"), None);
+        assert_eq!(strip("/home/alice/main.rs", ""), None);
+    }
+
+    #[test]
+    fn windows_drive_prefix_is_stripped() {
+        let stripped = strip(r"C:\Users\alice\project\src\main.rs", r"c:\users\Alice");
+        assert_eq!(stripped.as_deref(), Some("project/src/main.rs"));
+        let stripped = strip("C:/Users/alice/project/main.rs", r"C:\Users\alice\");
+        assert_eq!(stripped.as_deref(), Some("project/main.rs"));
+    }
+
+    #[test]
+    fn verbatim_prefix_matches_plain_path() {
+        let stripped = strip(r"C:\Users\alice\main.rs", r"\\?\C:\Users\alice");
+        assert_eq!(stripped.as_deref(), Some("main.rs"));
+        let stripped = strip(r"\\?\C:\Users\alice\main.rs", r"C:\Users\alice");
+        assert_eq!(stripped.as_deref(), Some("main.rs"));
+    }
+
+    #[test]
+    fn unc_prefix_is_stripped() {
+        let stripped = strip(r"\\server\share\project\main.rs", r"\\SERVER\share");
+        assert_eq!(stripped.as_deref(), Some("project/main.rs"));
+        let stripped = strip(r"\\?\UNC\server\share\main.rs", r"\\server\share");
+        assert_eq!(stripped.as_deref(), Some("main.rs"));
+        let stripped = strip(r"\\server\share\main.rs", r"\\?\UNC\server\share");
+        assert_eq!(stripped.as_deref(), Some("main.rs"));
+    }
+
+    #[test]
+    fn registry_index_is_left_out() {
+        let stripped = strip(
+            "/home/alice/.cargo/registry/src/index.crates.io-6f17d22bba15001f/serde-1.0.0/src/de.rs",
+            "/home/alice/.cargo",
+        );
+        assert_eq!(stripped.as_deref(), Some("registry/serde-1.0.0/src/de.rs"));
+    }
+
+    // A panic in `vendored::parser`, called by the application
+    fn vendored_panic(message: &str) -> AppCenterException {
+        let frame = |name: &str| ExceptionFrame::new(Some(name.to_string()), None, None);
+        let frames = vec![
+            frame("std::panicking::begin_panic_handler"),
+            frame("core::panicking::panic_fmt"),
+            frame("vendored::parser::parse"),
+            frame("my_app::config::load"),
+            frame("my_app::main"),
+            frame("std::rt::lang_start"),
+        ];
+        AppCenterException::from_parts(
+            "panic".to_string(),
+            message.to_string(),
+            Some(frames),
+            &FrameOptions::default(),
+        )
+    }
+
+    #[test]
+    fn ignored_message_is_matched_by_substring() {
+        let ignore_list = IgnoreList {
+            messages: vec!["unexpected EOF".to_string()],
+            modules: Vec::new(),
+        };
+        assert!(ignore_list.matches(&vendored_panic("parse error: unexpected EOF at 12")));
+        assert!(!ignore_list.matches(&vendored_panic("parse error: invalid token at 12")));
+        assert!(!IgnoreList::default().matches(&vendored_panic("unexpected EOF")));
+    }
+
+    #[test]
+    fn ignored_module_is_matched_against_the_top_application_frames() {
+        let mut ignore_list = IgnoreList {
+            messages: Vec::new(),
+            modules: vec!["vendored::".to_string()],
+        };
+        assert!(ignore_list.matches(&vendored_panic("invalid token")));
+
+        // The panic machinery is not an application frame
+        ignore_list.modules = vec!["core::panicking".to_string()];
+        assert!(!ignore_list.matches(&vendored_panic("invalid token")));
+
+        // Only the first 3 application frames are considered
+        ignore_list.modules = vec!["my_app::main".to_string()];
+        assert!(ignore_list.matches(&vendored_panic("invalid token")));
+        ignore_list.modules = vec!["std::rt".to_string()];
+        assert!(!ignore_list.matches(&vendored_panic("invalid token")));
+    }
+
+    #[test]
+    fn ignore_list_changes_take_effect_on_the_next_match() {
+        let mut ignore_list = IgnoreList::default();
+        let exception = vendored_panic("unexpected EOF");
+        assert!(!ignore_list.matches(&exception));
+
+        ignore_list.messages.push("EOF".to_string());
+        assert!(ignore_list.matches(&exception));
+
+        ignore_list.messages.clear();
+        ignore_list.modules.push("vendored::parser".to_string());
+        assert!(ignore_list.matches(&exception));
+
+        ignore_list.modules.clear();
+        assert!(!ignore_list.matches(&exception));
+    }
+}
@@ -0,0 +1,69 @@
+use super::{AppCenter, AppCenterInner};
+use eyre::EyreHandler;
+use std::error::Error;
+use std::sync::Weak;
+
+type BaseHook = Box<dyn Fn(&(dyn Error + 'static)) -> Box<dyn EyreHandler> + Send + Sync>;
+
+///
+/// An `eyre` hook that reports every `eyre::Report` to AppCenter as a handled error when it
+/// is created, usually by a `?`, so errors are reported where they happen even if they are
+/// handled further up. The formatting of the reports is left to a base hook, `eyre`'s default
+/// one unless another is provided, such as `eyre_hook.into_eyre_hook()` with the hooks of
+/// `color_eyre::config::HookBuilder::into_hooks`.
+///
+/// ```no_run
+/// use app_center::AppCenterEyreHook;
+///
+/// fn load_config() -> eyre::Result<Vec<u8>> {
+///     // A missing file is reported here, with the backtrace of the `?`
+///     Ok(std::fs::read("config.toml")?)
+/// }
+///
+/// let app_center = app_center::start!("00000000-0000-0000-0000-000000000000");
+/// AppCenterEyreHook::new(&app_center).install()?;
+/// let config = load_config().unwrap_or_default();
+/// # Ok::<(), eyre::InstallError>(())
+/// ```
+///
+/// The report is sent from the thread creating the `eyre::Report`, like `report_eyre`. The
+/// hook doesn't keep the instance alive, and only formats once the last handle is dropped.
+/// Requires the `eyre` feature.
+///
+pub struct AppCenterEyreHook {
+    app_center: Weak<AppCenterInner>,
+    base: BaseHook,
+}
+
+impl AppCenterEyreHook {
+    /// A hook reporting to `app_center`, formatting like `eyre` does by default
+    pub fn new(app_center: &AppCenter) -> Self {
+        AppCenterEyreHook {
+            app_center: std::sync::Arc::downgrade(&app_center.inner),
+            base: Box::new(eyre::DefaultHandler::default_with),
+        }
+    }
+
+    /// Create the handlers of the reports with `hook`, after the report is sent
+    pub fn base_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&(dyn Error + 'static)) -> Box<dyn EyreHandler> + Send + Sync + 'static,
+    {
+        self.base = Box::new(hook);
+        self
+    }
+
+    ///
+    /// Install the hook with `eyre::set_hook`, which fails if a hook was installed already,
+    /// including by `color_eyre::install`
+    ///
+    pub fn install(self) -> Result<(), eyre::InstallError> {
+        eyre::set_hook(Box::new(move |error| {
+            if let Some(app_center) = self.app_center.upgrade() {
+                let chain = std::iter::successors(Some(error), |&e| e.source());
+                let _ = app_center.report_chain(chain.collect::<Vec<_>>(), "eyre::Report");
+            }
+            (self.base)(error)
+        }))
+    }
+}
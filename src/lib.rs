@@ -1,277 +1,3235 @@
+//! Crash reporting for Rust applications to Microsoft AppCenter.
+//!
+//! Every public type is available at the crate root. The most commonly needed ones are
+//! also in the `prelude`:
+//!
+//! ```no_run
+//! use app_center::prelude::*;
+//!
+//! fn main() -> Result<(), ConfigError> {
+//!     let app_center: AppCenter = AppCenter::builder("00000000-0000-0000-0000-000000000000")
+//!         .app_version("1.0.0")
+//!         .build()?;
+//!
+//!     app_center.set_report_callback(|logs: &mut AppCenterLogs| logs.set_user_id(None));
+//!     app_center.add_breadcrumb("startup", "configured", None);
+//!     let _low_disk: Breadcrumb =
+//!         Breadcrumb::new("export", "disk almost full").with_level(BreadcrumbLevel::Warning);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! # `panic = "unwind"` and `panic = "abort"`
+//!
+//! Panic hooks run with both strategies, before the stack is unwound or the process is
+//! aborted, so crashes are reported the same way. The difference is what happens after the
+//! hook returns: with `unwind` the panicking thread unwinds and the process only exits if
+//! the panic reaches `main`, with `abort` the process terminates immediately. The hook only
+//! captures the backtrace and hands the report to a reporting thread, then runs the original
+//! hook and waits for the report to be sent, or queued on disk when the send fails, before it
+//! returns. Nothing is lost to the abort.
+//!
+//! Aborts that don't come from a panic, such as `std::process::abort` or an allocation
+//! failure, don't run the panic hook and are not reported.
+//!
+//! # Compiling reporting out
+//!
+//! Without the default `enabled` feature the API is the same, with the same types, but the
+//! instance is inert: no panic hook is installed, no backtrace is captured, the system is
+//! not queried and nothing is stored or sent. `AppCenter::is_enabled` is always false. Only
+//! the crates used by the public types remain, such as `serde`, `uuid` and `chrono`. The
+//! `send`, `encryption`, `schema` and `testing` features require `enabled`.
+//!
+//! # Minimum supported Rust version
+//!
+//! Rust 1.82, for `Option::is_none_or`, and because the `backtrace` crate requires it.
+//! The panic hook takes a `PanicHookInfo`, stable since 1.81, and `OnceLock` and
+//! `IsTerminal` are used since 1.70. Raising the minimum is a minor version change.
+
+#[cfg(app_center_unsupported_rustc)]
+compile_error!("app-center requires Rust 1.82 or newer");
+
+#[macro_use]
+mod logging;
+mod breadcrumbs;
+mod build_info;
+mod builder;
+mod capabilities;
+mod catch;
+mod clock;
 mod device;
+mod events;
+mod exception;
+#[cfg(feature = "eyre")]
+mod eyre_hook;
+mod logger;
+mod persist;
+pub mod prelude;
+mod report_builder;
+#[cfg(feature = "enabled")]
+mod reporter;
+mod scope;
+mod scrub;
+mod secret;
+mod settings;
+mod system;
+#[cfg(feature = "testing")]
+mod testing;
+#[cfg(feature = "tracing")]
+mod trace_layer;
+mod transport;
 mod utils;
+mod validate;
 
-use backtrace::Backtrace;
-use device::Device;
-use serde::Serialize;
-use std::panic::{self, PanicInfo};
-use std::sync::{Arc, Mutex};
+#[cfg(feature = "macros")]
+pub use app_center_macros::catch;
+pub use breadcrumbs::{Breadcrumb, BreadcrumbLevel};
+pub use builder::{AppCenterBuilder, ConfigError};
+pub use capabilities::CapabilitySet;
+pub use device::{Device, DeviceBuilder};
+use persist::{Envelope, LoadError, ReportStore};
+use serde::{Deserialize, Serialize};
+use settings::SettingsStore;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::panic;
+#[cfg(feature = "enabled")]
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Duration, Instant};
 pub(crate) use utils::Utils;
 
-///
-/// Install the custom panic hook that will attempt to upload panic stacktraces to
-/// appcenter using the provided app secret. `CARGO_PKG_VERSION` will be used as the application version.
-/// After the report is sent, the original panic hook is executed.
-///
-#[macro_export]
-macro_rules! start {
-    ($app_secret:expr) => {
-        app_center::AppCenter::start($app_secret, env!("CARGO_PKG_VERSION"))
-    };
+use breadcrumbs::BreadcrumbBuffer;
+use build_info::BuildMetadata;
+#[cfg(feature = "testing")]
+pub use clock::{Clock, FixedClock, IdSource, SequentialIds};
+pub use events::{EventProperties, EventValidation, EventValidationError};
+use events::{EventQueue, TypedProperty};
+pub use exception::{AppCenterException, ExceptionFrame, ResolutionMode};
+use exception::{FrameOptions, IgnoreList};
+#[cfg(feature = "eyre")]
+pub use eyre_hook::AppCenterEyreHook;
+pub use logger::{AppCenterLogger, LogCapacity};
+pub use logging::{LogLevel, LogSink, LOG_TARGET};
+pub use persist::{
+    FlushError, PendingSummary, PurgeSummary, QueuedReport, ReportError, SendError, VerifyError,
+};
+pub use report_builder::ReportBuilder;
+pub use scope::ContextGuard;
+pub use scrub::{ScrubField, ScrubTarget};
+pub use secret::AppSecret;
+#[cfg(feature = "testing")]
+pub use testing::{CapturedRequest, CapturingTransport};
+#[cfg(feature = "tracing")]
+pub use trace_layer::AppCenterLayer;
+pub use transport::{HttpRequest, HttpResponse, HttpSender};
+pub use validate::{validate_payload, Constraint, ValidationIssue};
+
+// Used by the code generated by `#[app_center::catch]` and by the benchmarks, not a stable API
+#[doc(hidden)]
+pub mod __private {
+    pub use super::catch::{catch, catch_async, catch_async_fatal, catch_fatal};
+
+    // What the panic hook captures on the panicking thread, with either resolution mode
+    #[cfg(feature = "enabled")]
+    pub fn capture_panic(
+        panic_info: &std::panic::PanicHookInfo,
+        resolution_mode: super::ResolutionMode,
+    ) -> super::AppCenterException {
+        let options = super::FrameOptions {
+            resolution_mode,
+            ..Default::default()
+        };
+        super::AppCenterException::new(panic_info, &options)
+    }
 }
 
+const DEFAULT_ENDPOINT: &str = "https://in.appcenter.ms/logs?Api-Version=1.0.0";
+
+// Limits on custom properties documented by AppCenter
+const MAX_PROPERTIES: usize = 20;
+const MAX_PROPERTY_LENGTH: usize = 125;
+const MAX_EVENT_NAME_LENGTH: usize = 256;
+// The part of an unsuccessful response that is logged and kept in the error
+const MAX_ERROR_BODY_LENGTH: usize = 512;
+// Attachment file names are kept below the usual file system limit
+const MAX_FILE_NAME_LENGTH: usize = 255;
+
+// Messages of the test crash and test report, which also carry the `test_report` property
+const TEST_CRASH_MESSAGE: &str = "AppCenter test crash";
+const TEST_REPORT_MESSAGE: &str = "AppCenter test report";
+const TEST_REPORT_PROPERTY: &str = "test_report";
+
+// Events are uploaded in batches, when this many are queued or when the oldest one has
+// waited for the interval
+const EVENT_BATCH_SIZE: usize = 50;
+const EVENT_FLUSH_INTERVAL: Duration = Duration::from_secs(3);
+// Past this many queued events, for example while offline, the oldest batch is moved to disk
+const EVENT_QUEUE_LIMIT: usize = 10 * EVENT_BATCH_SIZE;
+// The event thread checks in this often while idle, and is considered dead after the timeout
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(60);
+// How long the panic hook waits for a crash report on top of the send timeout, for the
+// symbols and the attachments
+#[cfg(feature = "enabled")]
+const REPORT_BUILD_TIMEOUT: Duration = Duration::from_secs(10);
+
+type ReportCallback = Arc<dyn Fn(&mut AppCenterLogs) + Send + Sync>;
+type ReportFilter = Arc<dyn Fn(&AppCenterLogs) -> bool + Send + Sync>;
+type Scrubber = Arc<dyn Fn(&mut ScrubTarget) + Send + Sync>;
+type GroupingKeyFn = Arc<dyn Fn(&AppCenterException) -> String + Send + Sync>;
+#[cfg(feature = "enabled")]
+type PanicHook = Box<dyn Fn(&PanicHookInfo) + Send + Sync>;
+
+///
+/// Handle to the crash reporter. Clones share the same configuration, callbacks and state;
+/// cloning does not install another panic hook, the hook is installed once when started.
+///
+/// Dropping the last handle stops reporting: the panic hook stays in the chain, since hooks
+/// can't be removed individually, but it only forwards to the previous hook from then on.
+///
 #[allow(dead_code)]
+#[derive(Clone)]
 pub struct AppCenter {
     inner: Arc<AppCenterInner>,
 }
 
+///
+/// Returned by `AppCenter::add_report_callback`, dropping the handle keeps the callback installed
+///
+pub struct ReportCallbackHandle {
+    id: u64,
+    inner: Weak<AppCenterInner>,
+}
+
+impl ReportCallbackHandle {
+    ///
+    /// Remove the callback, it won't be executed for subsequent reports
+    ///
+    pub fn unregister(self) {
+        if let Some(inner) = self.inner.upgrade() {
+            inner
+                .on_report
+                .lock()
+                .unwrap()
+                .retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+// The first instance started in the process, see `AppCenter::shared`
+static SHARED: OnceLock<Weak<AppCenterInner>> = OnceLock::new();
+
+///
+/// Associate the reports of the shared instance with a specific user ID,
+/// does nothing if no instance was started yet. See `AppCenter::shared`.
+///
+pub fn set_user_id<S: Into<String>>(id: Option<S>) {
+    if let Some(app_center) = AppCenter::shared() {
+        app_center.set_user_id(id);
+    }
+}
+
 impl AppCenter {
     ///
     /// Install an optonal callback to be executed just before the report is sent
     /// usually this is the place to add any atachements to the report using
-    /// `add_binary_attachement` or `add_text_attachement`
+    /// `add_binary_attachement` or `add_text_attachement`.
+    /// The callback stays installed and is called for every report. For panics it runs on the
+    /// reporting thread while the panicking thread waits, so thread locals of the panicking
+    /// thread are not visible and its locks must not be taken.
+    /// Replaces all the callbacks registered so far, see `add_report_callback`.
     ///
     pub fn set_report_callback<T>(&self, callback: T)
     where
         T: Fn(&mut AppCenterLogs) + Send + Sync + 'static,
     {
-        *self.inner.on_report.lock().unwrap() = Some(Box::new(callback));
+        self.inner.on_report.lock().unwrap().clear();
+        self.add_report_callback(callback);
     }
 
     ///
-    /// Associate the report with a specific user ID
+    /// Register an additional callback to be executed just before the report is sent.
+    /// Callbacks run in registration order, the returned handle unregisters the callback.
     ///
-    pub fn set_user_id<S: Into<String>>(&self, id: Option<S>) {
-        *self.inner.user_id.lock().unwrap() = id.map(|s| s.into());
+    pub fn add_report_callback<T>(&self, callback: T) -> ReportCallbackHandle
+    where
+        T: Fn(&mut AppCenterLogs) + Send + Sync + 'static,
+    {
+        let id = self.inner.next_callback_id.fetch_add(1, Ordering::Relaxed);
+        self.inner
+            .on_report
+            .lock()
+            .unwrap()
+            .push((id, Arc::new(callback)));
+
+        ReportCallbackHandle {
+            id,
+            inner: Arc::downgrade(&self.inner),
+        }
     }
 
     ///
-    /// Install the custom panic hook that will attempt to upload panic stacktraces to
-    /// appcenter using the provided app secret and application version.
-    /// After the report is sent, the original panic hook is executed.
+    /// The app secret the reports are sent with, see `app_secret_masked` for logging
     ///
-    pub fn start<S: Into<String>>(app_secret: S, app_version: &'static str) -> Self {
-        let inner = Arc::new(AppCenterInner {
-            app_secret: app_secret.into(),
-            app_version,
-            app_build: None,
-            app_launch_timestamp: chrono::Utc::now(),
-            user_id: Mutex::new(None),
-            on_report: Mutex::new(None),
-        });
+    pub fn app_secret(&self) -> &str {
+        &self.inner.app_secret
+    }
 
-        inner.set_panic_hook();
+    ///
+    /// The app secret with all but the first 8 characters replaced with `*`, safe to log
+    ///
+    pub fn app_secret_masked(&self) -> String {
+        secret::mask(&self.inner.app_secret)
+    }
 
-        AppCenter { inner }
+    ///
+    /// The application version reported with every crash
+    ///
+    pub fn app_version(&self) -> &'static str {
+        self.inner.app_version
     }
-}
 
-// The implementation is pretty straigtforward and follows the documentation in https://docs.microsoft.com/en-us/appcenter/diagnostics/upload-crashes
-struct AppCenterInner {
-    app_secret: String,
-    app_version: &'static str,
-    app_build: Option<String>,
-    app_launch_timestamp: chrono::DateTime<chrono::Utc>,
-    user_id: Mutex<Option<String>>,
-    on_report: Mutex<Option<Box<dyn Fn(&mut AppCenterLogs) + Send + Sync>>>,
-}
+    ///
+    /// The application build number reported with every crash
+    ///
+    pub fn app_build(&self) -> Option<String> {
+        self.inner.app_build.lock().unwrap().clone()
+    }
 
-#[derive(Serialize)]
-pub struct AppCenterLogs<'a> {
-    logs: Vec<AppCenterLog<'a>>,
-}
+    ///
+    /// Set the application build number reported with every crash, such as a CI build number
+    ///
+    pub fn set_app_build<S: Into<String>>(&self, build: S) {
+        *self.inner.app_build.lock().unwrap() = Some(build.into());
+    }
 
-#[derive(Serialize)]
-#[serde(tag = "type")]
-#[serde(rename_all = "camelCase")]
-enum AppCenterLog<'a> {
-    #[serde(rename_all = "camelCase")]
-    ManagedError {
-        id: uuid::Uuid,
-        user_id: Option<String>,
-        app_launch_timestamp: chrono::DateTime<chrono::Utc>,
-        timestamp: chrono::DateTime<chrono::Utc>,
-        fatal: bool,
-        process_id: u32,
-        process_name: String,
-        device: Device,
-        exception: AppCenterException,
-    },
-    #[serde(rename_all = "camelCase")]
-    ErrorAttachment {
-        id: uuid::Uuid,
-        error_id: uuid::Uuid,
-        device: Device,
-        content_type: &'static str,
-        #[serde(serialize_with = "as_base64")]
-        data: Vec<u8>,
-        #[serde(skip_serializing_if = "Option::is_none")]
-        file_name: Option<&'a str>,
-    },
-}
+    ///
+    /// The id identifying this installation of the application, persisted across launches
+    ///
+    pub fn install_id(&self) -> uuid::Uuid {
+        self.inner.install_id()
+    }
 
-fn as_base64<S>(val: &Vec<u8>, s: S) -> Result<S::Ok, S::Error>
-where
-    S: serde::Serializer,
-{
-    s.serialize_str(&base64::encode(val))
-}
+    ///
+    /// The number of times the application was started on this installation, including the
+    /// current launch. Counted when the hook is installed.
+    ///
+    pub fn launch_count(&self) -> u32 {
+        self.inner.launch_count
+    }
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-struct AppCenterException {
-    r#type: &'static str,
-    message: String,
-    frames: Vec<ExceptionFrame>,
-}
+    ///
+    /// Enable or disable crash reporting and analytics at runtime. While disabled the panic hook
+    /// skips building, sending and storing the report and goes straight to the original hook.
+    /// See `set_crashes_enabled` and `set_analytics_enabled` to disable only one of them.
+    ///
+    pub fn set_enabled(&self, enabled: bool) {
+        self.inner.enabled.store(enabled, Ordering::Relaxed);
+        if self.inner.persist_enabled {
+            self.inner.settings.update(|s| s.enabled = Some(enabled));
+        }
+    }
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-struct ExceptionFrame {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    method_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    line_number: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    file_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    address: Option<String>,
-}
+    ///
+    /// Enable or disable crash reporting only, analytics events and sessions are still sent.
+    /// Persisted like `set_enabled` with `AppCenterBuilder::persist_enabled`.
+    ///
+    pub fn set_crashes_enabled(&self, enabled: bool) {
+        self.inner.crashes_enabled.store(enabled, Ordering::Relaxed);
+        if self.inner.persist_enabled {
+            self.inner
+                .settings
+                .update(|s| s.crashes_enabled = Some(enabled));
+        }
+    }
+
+    ///
+    /// Returns true if crashes and handled errors are reported
+    ///
+    pub fn is_crashes_enabled(&self) -> bool {
+        self.inner.is_crashes_enabled()
+    }
+
+    ///
+    /// Enable or disable analytics only: events and sessions. Events still queued for upload
+    /// are dropped. Crash reporting is not affected. Persisted like `set_enabled` with
+    /// `AppCenterBuilder::persist_enabled`.
+    ///
+    pub fn set_analytics_enabled(&self, enabled: bool) {
+        self.inner
+            .analytics_enabled
+            .store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.inner.events.take_all();
+        }
+        if self.inner.persist_enabled {
+            self.inner
+                .settings
+                .update(|s| s.analytics_enabled = Some(enabled));
+        }
+    }
 
-impl ExceptionFrame {
-    fn collect_backtrace() -> Vec<ExceptionFrame> {
-        let mut frames = Vec::new();
+    ///
+    /// Returns true if events and sessions are sent
+    ///
+    pub fn is_analytics_enabled(&self) -> bool {
+        self.inner.is_analytics_enabled()
+    }
 
-        // First step is to collect the backtrace
-        let current_backtrace = Backtrace::new();
+    ///
+    /// Returns true if the thread uploading the events is running and checked in within the
+    /// last minute. It is started with the first tracked event, and restarted by the next
+    /// one if it died.
+    ///
+    pub fn is_analytics_alive(&self) -> bool {
+        self.inner.is_analytics_alive()
+    }
 
-        // We skip the frames until we hit the one that means something
-        for frame in current_backtrace.frames().into_iter() {
-            for symbol in frame.symbols() {
-                frames.push(ExceptionFrame {
-                    method_name: symbol.name().map(|n| format!("{}", n)),
+    ///
+    /// Returns true if crash reporting is enabled. Reporting is always disabled when the app
+    /// secret is empty or the all-zero GUID, or when the `APPCENTER_DISABLE=1` environment
+    /// variable is set.
+    ///
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_enabled()
+    }
 
-                    line_number: symbol.lineno(),
+    ///
+    /// Set how much the crate logs about its own operation. The messages go to the `log` crate
+    /// with the `app_center` target, so they can also be filtered by the logger. The level is
+    /// shared by all instances in the process. Defaults to `LogLevel::Debug`.
+    ///
+    pub fn set_log_level(&self, level: LogLevel) {
+        logging::set_level(level);
+    }
 
-                    file_name: symbol
-                        .filename()
-                        .and_then(|n| n.to_str())
-                        .map(|s| s.to_string()),
+    ///
+    /// The reporting mode in effect for the current build, `ReportingMode::ReleaseOnly`
+    /// resolves to `Always` or `Never` depending on `debug_assertions`
+    ///
+    pub fn reporting_mode(&self) -> ReportingMode {
+        self.inner.reporting_mode
+    }
 
-                    address: None,
-                });
-            }
-        }
+    ///
+    /// Install a filter deciding whether a report is sent. The filter runs after the report
+    /// callbacks, when it returns false the report is neither sent nor stored, and the
+    /// original panic hook runs right away.
+    ///
+    pub fn set_report_filter<F>(&self, filter: F)
+    where
+        F: Fn(&AppCenterLogs) -> bool + Send + Sync + 'static,
+    {
+        *self.inner.report_filter.lock().unwrap() = Some(Arc::new(filter));
+    }
 
-        frames
+    ///
+    /// Install a scrubber that runs over every outgoing log, after the report callbacks and
+    /// the filter, to remove personal data before anything leaves the machine. It is called
+    /// once per field: the exception type and message, the file names of the frames, the
+    /// user ID, event properties and attachments, see `ScrubField`. Events are scrubbed when
+    /// they are tracked. A report or event for which the scrubber panics is dropped.
+    /// Replaces the previous scrubber.
+    ///
+    pub fn set_scrubber<F>(&self, scrubber: F)
+    where
+        F: Fn(&mut ScrubTarget) + Send + Sync + 'static,
+    {
+        *self.inner.scrubber.lock().unwrap() = Some(Arc::new(scrubber));
     }
-}
 
-impl AppCenterException {
-    fn new(panic_info: &PanicInfo) -> Self {
-        let mut message = String::new();
-        if let Some(payload) = panic_info.payload().downcast_ref::<&str>() {
-            message.push_str(payload);
-        }
+    ///
+    /// Install a function computing the key used to group crashes, stored in the report as
+    /// `groupingKey`. By default the key is `AppCenterException::default_grouping_key`, which
+    /// is also used when the function panics.
+    ///
+    pub fn set_grouping_key_fn<F>(&self, f: F)
+    where
+        F: Fn(&AppCenterException) -> String + Send + Sync + 'static,
+    {
+        *self.inner.grouping_key_fn.lock().unwrap() = Some(Arc::new(f));
+    }
 
-        if let Some(location) = panic_info.location() {
-            message.push_str(&format!(" at {}:{}", location.file(), location.line()));
+    ///
+    /// Associate the report with a specific user ID, sent as selected with `set_user_id_mode`
+    ///
+    pub fn set_user_id<S: Into<String>>(&self, id: Option<S>) {
+        let id = id.map(|s| s.into());
+        if self.inner.persist_user_id.load(Ordering::Relaxed) {
+            self.inner.settings.update(|s| s.user_id = id.clone());
         }
+        *self.inner.user_id.lock().unwrap() = id;
+    }
 
-        AppCenterException {
-            r#type: "panic",
-            message,
-            frames: ExceptionFrame::collect_backtrace(),
+    ///
+    /// Attach a custom property to every report, such as the deployment channel or a tenant id.
+    /// The properties are attached to crashes as `properties.json`. Keys and values are truncated
+    /// to 125 characters and at most 20 properties are kept, as documented by AppCenter.
+    ///
+    pub fn set_property<K: Into<String>, V: Into<String>>(&self, key: K, value: V) {
+        let key = truncate(key.into(), MAX_PROPERTY_LENGTH);
+        let value = truncate(value.into(), MAX_PROPERTY_LENGTH);
+
+        let mut properties = self.inner.properties.lock().unwrap();
+        if properties.len() >= MAX_PROPERTIES && !properties.contains_key(&key) {
+            app_log!(Warn, "Too many custom properties, ignoring {:?}", key);
+            return;
         }
+        properties.insert(key, value);
     }
-}
 
-impl<'a> AppCenterLogs<'a> {
-    fn add_attachement_inner(
-        &mut self,
-        data: Vec<u8>,
-        file_name: Option<&'a str>,
-        content_type: &'static str,
-    ) {
-        // First attachement is always the ManagedError kind
-        let (device, error_id) = match &self.logs[0] {
-            AppCenterLog::ManagedError { device, id, .. } => (device.clone(), id.clone()),
-            _ => unreachable!(),
-        };
+    ///
+    /// Remove a custom property set with `set_property`
+    ///
+    pub fn remove_property(&self, key: &str) {
+        self.inner.properties.lock().unwrap().remove(key);
+    }
 
-        self.logs.push(AppCenterLog::ErrorAttachment {
-            id: uuid::Uuid::new_v4(),
-            device: device.clone(),
-            error_id,
-            content_type,
-            data,
-            file_name,
-        });
+    ///
+    /// Set a tag describing the current context of the application, such as the open screen.
+    /// Tags are added to the properties of the reports and take precedence over them.
+    ///
+    pub fn set_tag<K: Into<String>, V: Into<String>>(&self, key: K, value: V) {
+        self.inner
+            .tags
+            .lock()
+            .unwrap()
+            .insert(key.into(), value.into());
     }
 
-    pub fn add_binary_attachement(&mut self, data: Vec<u8>, file_name: Option<&'a str>) {
-        self.add_attachement_inner(data, file_name, "application/octet_stream");
+    ///
+    /// Remove a tag set with `set_tag`
+    ///
+    pub fn remove_tag(&self, key: &str) {
+        self.inner.tags.lock().unwrap().remove(key);
     }
 
-    pub fn add_text_attachement(&'a mut self, data: &str, file_name: Option<&'a str>) {
-        self.add_attachement_inner(data.as_bytes().to_vec(), file_name, "text/plain");
+    ///
+    /// Start a scope of tags that only apply to the reports of the current thread, until the
+    /// returned guard is dropped. Tags of the scope take precedence over the global tags.
+    ///
+    /// ```no_run
+    /// let app_center = app_center::start!("00000000-0000-0000-0000-000000000000");
+    /// let scope = app_center.push_scope();
+    /// scope.set_tag("screen", "editor");
+    /// ```
+    ///
+    pub fn push_scope(&self) -> ContextGuard {
+        ContextGuard::push()
     }
-}
 
-impl AppCenterInner {
-    fn new_payload(&self, panic_info: &PanicInfo) -> AppCenterLogs {
-        let user_id = { (*self.user_id.lock().unwrap()).clone() };
+    ///
+    /// Persist the user ID across launches, so crashes that happen before the application
+    /// calls `set_user_id` are still associated with the last known user. Enabling restores
+    /// the persisted user ID unless one was set already, disabling removes it from disk.
+    ///
+    pub fn set_persist_user_id(&self, persist: bool) {
+        self.inner.persist_user_id.store(persist, Ordering::Relaxed);
 
-        AppCenterLogs {
-            logs: vec![AppCenterLog::ManagedError {
-                id: uuid::Uuid::new_v4(),
-                user_id,
-                app_launch_timestamp: self.app_launch_timestamp,
-                timestamp: chrono::Utc::now(),
-                fatal: true,
-                process_id: Utils::get_pid(),
-                process_name: "".to_string(),
-                device: Device::current_device(self.app_version, &self.app_build),
-                exception: AppCenterException::new(panic_info),
-            }],
+        let mut user_id = self.inner.user_id.lock().unwrap();
+        if !persist {
+            self.inner.settings.update(|s| s.user_id = None);
+        } else if user_id.is_none() {
+            *user_id = self.inner.settings.load().user_id;
+        } else {
+            self.inner.settings.update(|s| s.user_id = user_id.clone());
         }
     }
 
-    fn set_panic_hook(self: &Arc<Self>) {
-        let app_center = Arc::clone(self);
+    ///
+    /// Don't report panics whose message contains any of the provided strings. The previous
+    /// panic hook still runs for ignored panics.
+    ///
+    pub fn set_ignored_messages(&self, messages: Vec<String>) {
+        self.inner.ignore_list.lock().unwrap().messages = messages;
+    }
 
-        let old_hook = panic::take_hook();
+    ///
+    /// Don't report panics where any of the top 3 application frames is in a module starting
+    /// with one of the provided prefixes, such as `"vendored_crate::"`
+    ///
+    pub fn set_ignored_modules(&self, modules: Vec<String>) {
+        self.inner.ignore_list.lock().unwrap().modules = modules;
+    }
 
-        panic::set_hook(Box::new(move |panic_info| {
-            let mut payload = app_center.new_payload(panic_info);
+    ///
+    /// Select when the backtrace symbols of handled errors are resolved. `ResolutionMode::Lazy`
+    /// only captures the addresses and defers the expensive symbol resolution until right
+    /// before the report is sent. Panics are always resolved on the reporting thread, outside
+    /// of the panic hook.
+    ///
+    pub fn set_resolution_mode(&self, mode: ResolutionMode) {
+        *self.inner.resolution_mode.lock().unwrap() = mode;
+    }
 
-            let report_callback = { app_center.on_report.lock().unwrap().take() };
+    ///
+    /// Select how the user ID is sent, see `UserIdMode`. The user ID set with `set_user_id`
+    /// is kept as is, the mode applies to the reports created from now on.
+    ///
+    pub fn set_user_id_mode(&self, mode: UserIdMode) {
+        *self.inner.user_id_mode.lock().unwrap() = mode;
+    }
 
-            if let Some(report_callback) = report_callback {
-                report_callback(&mut payload)
-            }
+    ///
+    /// Select the Base64 alphabet used to encode the attachments, defaults to
+    /// `Base64Variant::Standard`
+    ///
+    pub fn set_base64_variant(&self, variant: Base64Variant) {
+        *self.inner.base64_variant.lock().unwrap() = variant;
+    }
 
-            if let Ok(client) = reqwest::blocking::Client::builder()
-                .connect_timeout(std::time::Duration::from_secs(4))
-                .build()
-            {
-                let send_payload = client
-                    .post("https://in.appcenter.ms/logs?Api-Version=1.0.0")
-                    .header("Content-Type", "application/json")
-                    .header("app-secret", &app_center.app_secret)
-                    .header("install-id", "00000000-0000-0000-0000-000000000001")
-                    .body(serde_json::to_vec(&payload).unwrap())
-                    .send();
-
-                match send_payload {
-                    Ok(resp) => log::info!("Crash report sent: {:?}", resp.text()),
-                    // TODO: We failed to send the crash report, save it to disk to be sent later
-                    Err(err) => log::error!("Failed to send crash report {:?}", err),
-                }
-            }
+    ///
+    /// Set the directory where crash reports that failed to upload are stored.
+    /// Stored reports are sent by `send_pending_reports`, usually on the next launch.
+    ///
+    /// The directory holds one file per report, named after the report id: `<id>.json`, or
+    /// `<id>.enc` when encryption is enabled. A report file holds two lines: a JSON header such as
+    /// `{"formatVersion":1,"crateVersion":"0.1.0","createdAt":"...","endpoint":"...","installId":"..."}`
+    /// followed by the JSON payload as it would have been uploaded. Files with a newer
+    /// `formatVersion` are left for a newer version of the crate to send, until they expire.
+    /// A sidecar `<id>.meta.json` file records the upload attempts made so far as
+    /// `{"attempts": 2, "lastAttempt": "2021-04-30T12:00:00Z"}`.
+    ///
+    pub fn set_crash_dir<P: Into<PathBuf>>(&self, dir: Option<P>) {
+        *self.inner.crash_dir.lock().unwrap() = dir.map(|d| d.into());
+    }
 
-            // Execute the original panic handler
-            old_hook(panic_info)
-        }));
+    ///
+    /// Encrypt the crash reports stored on disk with ChaCha20-Poly1305 using the provided
+    /// 32 byte key. The same key must be set on the next launch for the reports to be sent,
+    /// reports that fail to decrypt are deleted.
+    ///
+    #[cfg(feature = "encryption")]
+    pub fn set_encryption_key(&self, key: Option<[u8; 32]>) {
+        *self.inner.encryption_key.lock().unwrap() = key;
+    }
+
+    ///
+    /// Report a non-fatal error as a `handledError`, with the backtrace of the calling thread.
+    /// The report goes through the same callbacks and filters as crash reports, and is sent
+    /// from the calling thread before returning. Returns the id of the report, which is
+    /// stored for a later attempt when the upload fails, or why it was not reported.
+    ///
+    pub fn report_error<E: std::error::Error + ?Sized>(
+        &self,
+        error: &E,
+    ) -> Result<uuid::Uuid, ReportError> {
+        if !self.inner.is_crashes_enabled() {
+            return Err(ReportError::Disabled);
+        }
+
+        let exception = AppCenterException::from_error(error, &self.inner.frame_options());
+        self.inner
+            .report(self.inner.new_payload(exception, false), Instant::now())
+    }
+
+    ///
+    /// Report an `anyhow::Error` as a `handledError` like `report_error`, with its chain of
+    /// causes as nested inner exceptions. The type of the report is the type of the root
+    /// cause when it is a common error of the standard library, `anyhow::Error` otherwise.
+    /// The backtrace is the one of the calling thread, not the one captured by `anyhow`.
+    /// Requires the `anyhow` feature.
+    ///
+    /// ```no_run
+    /// use anyhow::Context;
+    ///
+    /// let app_center = app_center::start!("00000000-0000-0000-0000-000000000000");
+    /// if let Err(err) = std::fs::read("config.toml").context("loading the configuration") {
+    ///     let _ = app_center.report_anyhow(&err);
+    /// }
+    /// ```
+    ///
+    #[cfg(feature = "anyhow")]
+    pub fn report_anyhow(&self, error: &anyhow::Error) -> Result<uuid::Uuid, ReportError> {
+        self.inner
+            .report_chain(error.chain().collect(), "anyhow::Error")
+    }
+
+    ///
+    /// Report an `eyre::Report` as a `handledError` with its chain of causes, like
+    /// `report_anyhow`. To report errors where they are created, see `AppCenterEyreHook`.
+    /// Requires the `eyre` feature.
+    ///
+    #[cfg(feature = "eyre")]
+    pub fn report_eyre(&self, error: &eyre::Report) -> Result<uuid::Uuid, ReportError> {
+        self.inner
+            .report_chain(error.chain().collect(), "eyre::Report")
+    }
+
+    ///
+    /// Build the report `report_error` would send for `error`, without sending, storing or
+    /// printing it and without any network activity. The report callbacks, the grouping key
+    /// and the scrubber are applied, the filter and sampling are not. When the scrubber
+    /// panics nothing is returned, `ReportError::Discarded`, rather than a report that may
+    /// still hold what it should have removed. Meant for testing callbacks, scrubbers and
+    /// attachments without panicking:
+    ///
+    /// ```no_run
+    /// let app_center = app_center::AppCenter::builder("00000000-0000-0000-0000-000000000000")
+    ///     .dry_run(true)
+    ///     .start();
+    /// app_center.set_report_callback(|logs| logs.set_user_id(None));
+    ///
+    /// let error = std::io::Error::new(std::io::ErrorKind::Other, "disk full");
+    /// let report = app_center.build_report(&error).unwrap();
+    /// assert_eq!(report.user_id(), None);
+    /// println!("{}", report.to_json_pretty().unwrap());
+    /// ```
+    ///
+    pub fn build_report<E: std::error::Error + ?Sized>(
+        &self,
+        error: &E,
+    ) -> Result<AppCenterLogs<'_>, ReportError> {
+        let exception = AppCenterException::from_error(error, &self.inner.frame_options());
+        self.inner
+            .build_report(self.inner.new_payload(exception, false))
+    }
+
+    ///
+    /// Build a report from parts, such as an exception type and a message, instead of a panic
+    /// or an error value, see `ReportBuilder`
+    ///
+    pub fn report_builder(&self) -> ReportBuilder<'_> {
+        ReportBuilder::new(self)
+    }
+
+    ///
+    /// The id of the last report, fatal or not, that made it past the callbacks, the filter
+    /// and sampling and was sent, queued, or printed in dry-run mode. This is the `id` of the
+    /// error in AppCenter, for example to reference it in the logs of the application after
+    /// `report_error`.
+    ///
+    pub fn last_error_id(&self) -> Option<uuid::Uuid> {
+        *self.inner.last_error_id.lock().unwrap()
+    }
+
+    ///
+    /// Panic with a recognizable message, to verify that crashes make it from the panic hook
+    /// to the AppCenter portal, like `Crashes.generateTestCrash` of the official SDKs. The
+    /// report has the `test_report` property set to `true`.
+    ///
+    /// Does nothing in release builds, unless allowed with
+    /// `AppCenterBuilder::test_crash_in_release`, and when crashes would not be reported
+    /// because reporting is disabled or compiled out.
+    ///
+    #[track_caller]
+    pub fn generate_test_crash(&self) {
+        if cfg!(not(debug_assertions)) && !self.inner.test_crash_in_release {
+            app_log!(Debug, "Test crash ignored in a release build");
+            return;
+        }
+        if !self.inner.is_crashes_enabled() {
+            app_log!(Warn, "Test crash ignored, crash reporting is disabled");
+            return;
+        }
+
+        let scope = self.push_scope();
+        scope.set_tag(TEST_REPORT_PROPERTY, "true");
+        panic!("{}", TEST_CRASH_MESSAGE);
+    }
+
+    ///
+    /// Send a synthetic handled error, with the `test_report` property set to `true`, and
+    /// return its id once AppCenter accepted it. The report goes through the callbacks and
+    /// the scrubber, the filter and sampling are skipped. Like `verify_connection`, this
+    /// makes a real HTTP request from the calling thread, even in dry-run mode, and nothing
+    /// is stored when it fails, so smoke tests can check the whole pipeline without crashing.
+    ///
+    pub fn send_test_report(&self) -> Result<uuid::Uuid, VerifyError> {
+        if !self.inner.is_crashes_enabled() {
+            return Err(VerifyError::Disabled);
+        }
+
+        let scope = self.push_scope();
+        scope.set_tag(TEST_REPORT_PROPERTY, "true");
+        let error = std::io::Error::other(TEST_REPORT_MESSAGE);
+        let exception = AppCenterException::from_error(&error, &self.inner.frame_options());
+        let payload = self.inner.new_payload(exception, false);
+        drop(scope);
+
+        let payload = self
+            .inner
+            .build_report(payload)
+            .map_err(|_| VerifyError::Discarded)?;
+        let report = payload
+            .to_json_bytes()
+            .map_err(|err| VerifyError::Network(SendError::from(Box::from(err.to_string()))))?;
+        self.inner.send_verified(&report)?;
+
+        Ok(payload.error_id())
+    }
+
+    ///
+    /// Record a step on the path of the user to a crash, such as `"navigation"`, `"opened editor"`.
+    /// The most recent breadcrumbs are attached to every report as `breadcrumbs.json`, grouped by
+    /// the session they were added in,
+    /// see `AppCenterBuilder::max_breadcrumbs`. Safe to call from any thread or async task.
+    ///
+    pub fn add_breadcrumb(
+        &self,
+        category: &str,
+        message: &str,
+        data: Option<BTreeMap<String, String>>,
+    ) {
+        let session_id = *self.inner.session_id.lock().unwrap();
+        self.inner
+            .breadcrumbs
+            .push(self.inner.clock.now(), session_id, category, message, data);
+    }
+
+    ///
+    /// Record a breadcrumb from an async task, like `add_breadcrumb`, stamped with the time
+    /// and the session when it is added.
+    ///
+    /// Without the `tokio` feature the future completes on its first poll: the buffer is
+    /// behind a `std::sync::Mutex` that is only held for the push and never across an await
+    /// point, so this is the same as `add_breadcrumb`, which is fine for most applications.
+    /// With the `tokio` feature the buffer is behind a `tokio::sync::Mutex` and the future
+    /// waits for it, so tasks adding breadcrumbs concurrently on a busy runtime yield to the
+    /// scheduler instead of blocking their worker thread.
+    ///
+    /// ```no_run
+    /// # async fn export(app_center: &app_center::AppCenter) {
+    /// let breadcrumb = app_center::Breadcrumb::new("export", "started export");
+    /// app_center.add_breadcrumb_async(breadcrumb).await;
+    /// # }
+    /// ```
+    ///
+    pub async fn add_breadcrumb_async(&self, mut breadcrumb: Breadcrumb) {
+        let session_id = *self.inner.session_id.lock().unwrap();
+        breadcrumb.stamp(self.inner.clock.now(), session_id);
+        self.inner.breadcrumbs.push_async(breadcrumb).await;
+    }
+
+    ///
+    /// Track a product analytics event, such as `"export_completed"`, in the same AppCenter app
+    /// as the crashes. Events are uploaded from a background thread in batches, see `shutdown`
+    /// for the ones still queued when the process exits. Events exceeding the limits of
+    /// AppCenter are truncated or rejected, see `AppCenterBuilder::event_validation`.
+    ///
+    pub fn track_event(
+        &self,
+        name: &str,
+        properties: Option<BTreeMap<String, String>>,
+    ) -> Result<(), EventValidationError> {
+        self.inner.track_event(name, properties, None)
+    }
+
+    ///
+    /// Track the screen the user navigated to, for GUI applications. A `page` log is uploaded
+    /// like events, and the page is recorded as a `navigation` breadcrumb and as the current
+    /// page, added to the properties of every later report as `page`. The name is validated
+    /// like event names.
+    ///
+    pub fn track_page(&self, name: &str) -> Result<(), EventValidationError> {
+        self.inner.track_page(name)
+    }
+
+    ///
+    /// Track a product analytics event with typed properties, so numbers, booleans and dates
+    /// keep their type in the portal. See `track_event`.
+    ///
+    pub fn track_event_with_properties(
+        &self,
+        name: &str,
+        properties: EventProperties,
+    ) -> Result<(), EventValidationError> {
+        self.inner.track_event(name, None, Some(properties))
+    }
+
+    ///
+    /// Check that AppCenter is reachable and accepts the app secret, and the mirror secrets,
+    /// by sending a `startSession` log for the current session and checking the response.
+    /// This makes a real HTTP request from the calling thread, even in dry-run mode, so it is
+    /// meant for diagnostics during development or at startup.
+    ///
+    pub fn verify_connection(&self) -> Result<(), VerifyError> {
+        if !self.inner.is_enabled() {
+            return Err(VerifyError::Disabled);
+        }
+        self.inner.verify_connection()
+    }
+
+    ///
+    /// Upload the analytics events that are still queued from the calling thread, before the
+    /// process exits. Events that fail to upload are stored in the crash directory and sent by
+    /// `send_pending_reports` on the next launch. The instance stays usable. When the last
+    /// handle is dropped without calling this, the queued events are stored without an
+    /// upload attempt.
+    ///
+    pub fn shutdown(&self) {
+        if self.inner.is_analytics_enabled() {
+            self.inner.flush_events();
+        }
+    }
+
+    ///
+    /// Start a new session: reports are attributed to a new session id from now on, and a
+    /// `startSession` log is sent for it from a background thread. For applications whose
+    /// sessions don't match the lifetime of the process, such as a user signing in.
+    ///
+    pub fn renew_session(&self) {
+        *self.inner.session_id.lock().unwrap() = self.inner.ids.next_id();
+        self.inner.start_session();
+    }
+
+    ///
+    /// Reset the launch timestamp reported with every crash to the current time, for
+    /// long running processes that restart their work without restarting the process
+    ///
+    pub fn reset_launch_timestamp(&self) {
+        *self.inner.app_launch_timestamp.lock().unwrap() = self.inner.clock.now();
+    }
+
+    ///
+    /// Set the two letter ISO country code of the device, such as `"DE"`, used by AppCenter for
+    /// the geography breakdowns. When not set, the country is detected from the system settings.
+    /// Codes that are not two letters are ignored, the case is normalized.
+    ///
+    pub fn set_country_code(&self, country_code: Option<String>) {
+        let country_code = match country_code {
+            Some(code) => match Utils::normalize_country_code(&code) {
+                Some(code) => Some(code),
+                None => {
+                    app_log!(Warn, "Ignoring invalid country code {:?}", code);
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        *self.inner.country_code.lock().unwrap() = country_code;
+    }
+
+    ///
+    /// Set the number of upload attempts after which a stored crash report is dropped.
+    /// Between launches the attempts are spaced with an exponential backoff. Defaults to 10.
+    ///
+    pub fn set_max_send_attempts(&self, attempts: u32) {
+        self.inner
+            .max_send_attempts
+            .store(attempts, Ordering::Relaxed);
+    }
+
+    ///
+    /// Attempt to upload the crash reports stored in the crash directory by previous runs.
+    /// Successfully sent reports are removed from disk, reports that are still backing off
+    /// after a previous failure are skipped.
+    ///
+    pub fn send_pending_reports(&self) -> PendingSummary {
+        if !self.inner.is_crashes_enabled() {
+            return PendingSummary::default();
+        }
+        self.inner.send_pending_reports()
+    }
+
+    ///
+    /// Upload all the crash reports stored in the crash directory now, regardless of their
+    /// backoff. Returns the number of reports sent, stops at the first failed upload.
+    ///
+    pub fn flush_queued_reports(&self) -> Result<usize, FlushError> {
+        self.inner.flush_queued_reports()
+    }
+
+    ///
+    /// List the crash reports stored in the crash directory
+    ///
+    pub fn list_queued_reports(&self) -> Vec<QueuedReport> {
+        self.inner
+            .report_store()
+            .and_then(|store| store.list().ok())
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Delete everything stored about this installation, for example on a data deletion
+    /// request: the reports and events in the crash directory, and the settings including
+    /// the install id and the persisted user ID. The state in memory is reset as well: a new
+    /// install id and session, and no user ID, properties, tags, breadcrumbs or queued events.
+    /// The new install id is not stored. Call `set_enabled(false)` afterwards to stop
+    /// reporting, which is stored again when `AppCenterBuilder::persist_enabled` is set.
+    ///
+    /// Files that could not be deleted are listed in the summary, an error is returned when
+    /// the crash directory can't be read.
+    ///
+    pub fn purge_local_data(&self) -> std::io::Result<PurgeSummary> {
+        self.inner.purge_local_data()
+    }
+
+    ///
+    /// Delete a crash report from the crash directory without sending it
+    ///
+    pub fn delete_queued_report(&self, id: uuid::Uuid) -> std::io::Result<()> {
+        let store = self.inner.report_store().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "no crash directory")
+        })?;
+
+        match store.find(&id)? {
+            Some(path) => {
+                store.remove(&path);
+                Ok(())
+            }
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "no such report",
+            )),
+        }
+    }
+
+    ///
+    /// Install the custom panic hook that will attempt to upload panic stacktraces to
+    /// appcenter using the provided app secret and application version.
+    /// After the report is sent, the original panic hook is executed.
+    ///
+    /// When the app secret is empty or the all-zero GUID, or when the environment variable
+    /// `APPCENTER_DISABLE=1` is set, the hook is installed but nothing is ever sent.
+    /// The same applies to a secret that is not a UUID, which is logged as an error;
+    /// use `AppSecret` or `AppCenterBuilder::build` to handle invalid secrets.
+    ///
+    /// Without the default `enabled` feature no hook is installed and nothing is stored or
+    /// sent, the API stays the same so the feature can be toggled without code changes.
+    /// Without the default `send` feature the hook is installed, but reports are kept in the
+    /// crash directory, or written to stderr, unless an `HttpSender` is configured.
+    ///
+    pub fn start<S: Into<String>>(app_secret: S, app_version: &'static str) -> Self {
+        AppCenter::builder(app_secret)
+            .app_version(app_version)
+            .start()
+    }
+
+    ///
+    /// The first instance started in the process, so code deep in the application can add
+    /// context without threading the handle around. Instances started after the first one
+    /// are not reachable through `shared`. Returns `None` once every handle to the first
+    /// instance was dropped.
+    ///
+    pub fn shared() -> Option<AppCenter> {
+        let inner = SHARED.get()?.upgrade()?;
+        Some(AppCenter { inner })
+    }
+
+    ///
+    /// The optional parts of the crate this build was compiled with, for diagnostics in
+    /// applications where the features are decided deep in the dependency graph
+    ///
+    /// ```no_run
+    /// println!("AppCenter capabilities: {}", app_center::AppCenter::capabilities());
+    /// ```
+    ///
+    pub fn capabilities() -> CapabilitySet {
+        CapabilitySet::compiled()
+    }
+
+    ///
+    /// Configure the crash reporter for the provided app secret, the panic hook is
+    /// installed by `AppCenterBuilder::build`
+    ///
+    pub fn builder<S: Into<String>>(app_secret: S) -> AppCenterBuilder {
+        AppCenterBuilder::new(app_secret.into())
+    }
+}
+
+// The implementation is pretty straigtforward and follows the documentation in https://docs.microsoft.com/en-us/appcenter/diagnostics/upload-crashes
+struct AppCenterInner {
+    app_secret: String,
+    // Additional apps every report is sent to
+    mirror_app_secrets: Vec<String>,
+    // Replaced by `purge_local_data`
+    install_id: Mutex<uuid::Uuid>,
+    settings: SettingsStore,
+    persist_user_id: AtomicBool,
+    enabled: AtomicBool,
+    // Each category can be disabled on its own, `enabled` disables both
+    crashes_enabled: AtomicBool,
+    analytics_enabled: AtomicBool,
+    persist_enabled: bool,
+    // Set when the secret is a placeholder or reporting is disabled by the environment,
+    // reporting can't be enabled at runtime in this mode
+    noop: bool,
+    app_version: &'static str,
+    app_build: Mutex<Option<String>>,
+    build_metadata: BuildMetadata,
+    // Replaces the detected device when set
+    device: Option<Device>,
+    // Set by the application, takes precedence over the detected one
+    country_code: Mutex<Option<String>>,
+    detected_country_code: Option<String>,
+    app_launch_timestamp: Mutex<chrono::DateTime<chrono::Utc>>,
+    launch_count: u32,
+    process_name: String,
+    // Referenced by every report, rotated with `renew_session`
+    session_id: Mutex<uuid::Uuid>,
+    last_error_id: Mutex<Option<uuid::Uuid>>,
+    // Real ids and time, unless replaced for tests
+    ids: Arc<dyn clock::IdSource>,
+    clock: Arc<dyn clock::Clock>,
+    events: Arc<EventQueue<AppCenterLog<'static>>>,
+    // The background sender is started with the first tracked event, and restarted by the
+    // next one when it died
+    event_sender: Mutex<Option<std::thread::JoinHandle<()>>>,
+    // When the background sender last checked in, in milliseconds since the Unix epoch
+    last_heartbeat_epoch_ms: Arc<AtomicU64>,
+    endpoint: String,
+    dry_run: bool,
+    dry_run_pretty: Option<bool>,
+    test_crash_in_release: bool,
+    #[cfg_attr(not(feature = "color-backtrace"), allow(dead_code))]
+    pretty_panic_output: bool,
+    timeout: Duration,
+    // `None` without the `send` feature, unless the user provided a sender
+    http_sender: Option<Box<dyn HttpSender>>,
+    env_snapshot: Option<BTreeMap<String, String>>,
+    include_cwd: bool,
+    attach_system_state: bool,
+    user_id: Mutex<Option<String>>,
+    user_id_mode: Mutex<UserIdMode>,
+    properties: Mutex<BTreeMap<String, String>>,
+    tags: Mutex<BTreeMap<String, String>>,
+    current_page: Mutex<Option<String>>,
+    event_validation: EventValidation,
+    breadcrumbs: BreadcrumbBuffer,
+    on_report: Mutex<Vec<(u64, ReportCallback)>>,
+    next_callback_id: AtomicU64,
+    report_filter: Mutex<Option<ReportFilter>>,
+    scrubber: Mutex<Option<Scrubber>>,
+    grouping_key_fn: Mutex<Option<GroupingKeyFn>>,
+    ignore_list: Mutex<IgnoreList>,
+    resolution_mode: Mutex<ResolutionMode>,
+    include_raw_symbols: bool,
+    skip_frame_patterns: Vec<String>,
+    path_prefixes: Vec<String>,
+    sample_rate: f32,
+    // Always resolved to `Always` or `Never`
+    reporting_mode: ReportingMode,
+    base64_variant: Mutex<Base64Variant>,
+    crash_dir: Mutex<Option<PathBuf>>,
+    max_send_attempts: AtomicU32,
+    #[cfg(feature = "encryption")]
+    encryption_key: Mutex<Option<[u8; 32]>>,
+    // Started with the panic hook, builds and sends the crash reports
+    #[cfg(feature = "enabled")]
+    reporter: OnceLock<reporter::Reporter>,
+}
+
+// What the panic hook takes for a report, the report is built from it on the reporting thread
+struct ReportSnapshot {
+    exception: AppCenterException,
+    fatal: bool,
+    id: uuid::Uuid,
+    timestamp: chrono::DateTime<chrono::Utc>,
+    user_id: Option<String>,
+    app_launch_timestamp: chrono::DateTime<chrono::Utc>,
+    session_id: uuid::Uuid,
+    current_page: Option<String>,
+    scope_tags: BTreeMap<String, String>,
+}
+
+///
+/// A crash report as sent to AppCenter. Deserializing reads back a serialized report, for
+/// example one written in dry-run mode; the attachments keep the Base64 alphabet they were
+/// encoded with.
+///
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Serialize)]
+pub struct AppCenterLogs<'a> {
+    #[serde(borrow)]
+    logs: Vec<AppCenterLog<'a>>,
+    #[serde(skip)]
+    base64_variant: Base64Variant,
+    #[serde(skip)]
+    force_send: bool,
+    #[serde(skip)]
+    current_page: Option<String>,
+    // The ids of the instance the report was built by, for the attachments added later
+    #[serde(skip)]
+    #[cfg_attr(feature = "schema", schemars(skip))]
+    ids: Option<Arc<dyn clock::IdSource>>,
+}
+
+// The accessors rely on the error being the first log, other reports are rejected
+impl<'de: 'a, 'a> Deserialize<'de> for AppCenterLogs<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Logs<'a> {
+            #[serde(borrow)]
+            logs: Vec<AppCenterLog<'a>>,
+        }
+
+        let Logs { logs } = Logs::deserialize(d)?;
+        match logs.first() {
+            Some(AppCenterLog::ManagedError { .. }) | Some(AppCenterLog::HandledError { .. }) => {
+                Ok(AppCenterLogs {
+                    logs,
+                    base64_variant: Base64Variant::default(),
+                    force_send: false,
+                    current_page: None,
+                    ids: None,
+                })
+            }
+            _ => Err(serde::de::Error::custom(
+                "the first log of a report must be a managedError or a handledError",
+            )),
+        }
+    }
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "camelCase")]
+enum AppCenterLog<'a> {
+    #[serde(rename_all = "camelCase")]
+    ManagedError {
+        id: uuid::Uuid,
+        user_id: Option<String>,
+        app_launch_timestamp: chrono::DateTime<chrono::Utc>,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        fatal: bool,
+        process_id: u32,
+        process_name: String,
+        device: Arc<Device>,
+        exception: AppCenterException,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        grouping_key: Option<String>,
+        is_first_launch: bool,
+        #[serde(rename = "sid")]
+        session_id: uuid::Uuid,
+        // Time spent in the panic hook before the report was serialized, diagnostic only
+        #[serde(skip_serializing_if = "Option::is_none")]
+        hook_execution_ms: Option<u64>,
+    },
+    // A non-fatal error, reported by the application
+    #[serde(rename_all = "camelCase")]
+    HandledError {
+        id: uuid::Uuid,
+        user_id: Option<String>,
+        app_launch_timestamp: chrono::DateTime<chrono::Utc>,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        process_id: u32,
+        process_name: String,
+        device: Arc<Device>,
+        exception: AppCenterException,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        grouping_key: Option<String>,
+        #[serde(rename = "sid")]
+        session_id: uuid::Uuid,
+        // Shown with the error in the portal, crashes only carry them in `properties.json`
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        properties: BTreeMap<String, String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ErrorAttachment {
+        id: uuid::Uuid,
+        error_id: uuid::Uuid,
+        device: Arc<Device>,
+        content_type: Cow<'static, str>,
+        data: Base64Data,
+        #[serde(default, borrow, skip_serializing_if = "Option::is_none")]
+        file_name: Option<Cow<'a, str>>,
+    },
+    #[serde(rename_all = "camelCase")]
+    StartSession {
+        id: uuid::Uuid,
+        #[serde(rename = "sid")]
+        session_id: uuid::Uuid,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        device: Device,
+    },
+    #[serde(rename_all = "camelCase")]
+    Event {
+        id: uuid::Uuid,
+        name: String,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        #[serde(rename = "sid")]
+        session_id: uuid::Uuid,
+        device: Device,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        properties: Option<BTreeMap<String, String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        typed_properties: Option<Vec<TypedProperty>>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Page {
+        id: uuid::Uuid,
+        name: String,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        #[serde(rename = "sid")]
+        session_id: uuid::Uuid,
+        device: Device,
+    },
+}
+
+///
+/// Which builds send crash reports
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportingMode {
+    /// Every build sends reports
+    #[default]
+    Always,
+    /// Only builds without `debug_assertions` send reports, the panic hook still runs
+    /// in debug builds but the reports are dropped instead of sent
+    ReleaseOnly,
+    /// No build sends reports, the panic hook still runs
+    Never,
+}
+
+impl ReportingMode {
+    // Resolve `ReleaseOnly` for the current build
+    fn effective(self) -> ReportingMode {
+        match self {
+            ReportingMode::ReleaseOnly if cfg!(debug_assertions) => ReportingMode::Never,
+            ReportingMode::ReleaseOnly => ReportingMode::Always,
+            mode => mode,
+        }
+    }
+}
+
+///
+/// How the user ID is sent, to correlate the crashes of a user without uploading an email
+/// address or an account name
+///
+#[derive(Clone, Default)]
+pub enum UserIdMode {
+    /// The user ID is sent as is
+    #[default]
+    Raw,
+    /// The lowercase hex SHA-256 digest of the user ID is sent, the same on every platform
+    Sha256,
+    /// The result of the function is sent. When the function panics the SHA-256 digest is
+    /// sent instead, never the raw user ID.
+    Custom(Arc<dyn Fn(&str) -> String + Send + Sync>),
+}
+
+impl UserIdMode {
+    fn apply(&self, user_id: &str) -> String {
+        match self {
+            UserIdMode::Raw => user_id.to_string(),
+            UserIdMode::Sha256 => Utils::sha256_hex(user_id.as_bytes()),
+            UserIdMode::Custom(f) => {
+                match panic::catch_unwind(panic::AssertUnwindSafe(|| f(user_id))) {
+                    Ok(id) => id,
+                    Err(_) => {
+                        app_log!(Error, "User ID function panicked, sending the digest");
+                        UserIdMode::Sha256.apply(user_id)
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for UserIdMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UserIdMode::Raw => f.write_str("Raw"),
+            UserIdMode::Sha256 => f.write_str("Sha256"),
+            UserIdMode::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+///
+/// The Base64 alphabet used to encode the attachments
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Base64Variant {
+    /// Standard alphabet with `+` and `/`, padded with `=`
+    #[default]
+    Standard,
+    /// URL safe alphabet with `-` and `_`, padded with `=`
+    UrlSafe,
+}
+
+impl Base64Variant {
+    fn config(self) -> base64::Config {
+        match self {
+            Base64Variant::Standard => base64::STANDARD,
+            Base64Variant::UrlSafe => base64::URL_SAFE,
+        }
+    }
+}
+
+// Attachment bytes, encoded with the selected variant on serialization
+struct Base64Data {
+    data: Vec<u8>,
+    variant: Base64Variant,
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Base64Data {
+    fn schema_name() -> String {
+        "Base64Data".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
+impl Serialize for Base64Data {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&base64::encode_config(&self.data, self.variant.config()))
+    }
+}
+
+// Either alphabet is accepted, and kept for serializing the data again
+impl<'de> Deserialize<'de> for Base64Data {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let encoded = Cow::<str>::deserialize(d)?;
+        let variant = if encoded.contains(['-', '_']) {
+            Base64Variant::UrlSafe
+        } else {
+            Base64Variant::Standard
+        };
+        let data = base64::decode_config(encoded.as_bytes(), variant.config())
+            .map_err(serde::de::Error::custom)?;
+        Ok(Base64Data { data, variant })
+    }
+}
+
+// The wall clock in milliseconds since the Unix epoch, for the heartbeat of the event thread
+fn epoch_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+// Whether the random bits fall within the sample, the top 24 bits are drawn, enough for an f32
+// in [0, 1)
+fn in_sample(rate: f32, random: u128) -> bool {
+    let draw = (random >> 104) as f32 / (1u32 << 24) as f32;
+    draw < rate
+}
+
+// Truncate to at most `max` characters
+fn truncate(mut s: String, max: usize) -> String {
+    if let Some((idx, _)) = s.char_indices().nth(max) {
+        s.truncate(idx);
+    }
+    s
+}
+
+impl std::fmt::Debug for AppCenter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppCenter")
+            .field("app_secret", &self.app_secret_masked())
+            .field("app_version", &self.app_version())
+            .field("enabled", &self.is_enabled())
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for AppCenterLogs<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let attachment_sizes: Vec<_> = self
+            .logs
+            .iter()
+            .filter_map(|log| match log {
+                AppCenterLog::ErrorAttachment { data, .. } => Some(data.data.len()),
+                _ => None,
+            })
+            .collect();
+
+        f.debug_struct("AppCenterLogs")
+            .field("error_id", &self.error_id())
+            .field("logs", &self.logs.len())
+            .field("attachment_sizes", &attachment_sizes)
+            .finish()
+    }
+}
+
+impl AppCenterLog<'_> {
+    fn id(&self) -> uuid::Uuid {
+        match self {
+            AppCenterLog::ManagedError { id, .. }
+            | AppCenterLog::HandledError { id, .. }
+            | AppCenterLog::ErrorAttachment { id, .. }
+            | AppCenterLog::StartSession { id, .. }
+            | AppCenterLog::Event { id, .. }
+            | AppCenterLog::Page { id, .. } => *id,
+        }
+    }
+}
+
+impl<'a> AppCenterLogs<'a> {
+    ///
+    /// The JSON schema of the reports as serialized by this crate, for validation tooling.
+    /// It follows the AppCenter ingestion schema for the fields this crate sends, with a few
+    /// extensions: `hookExecutionMs` and `groupingKey` of error logs, and `windowsVersion`,
+    /// `windowsBuild` and `cpuBits` of the device. The attachment `data` is a Base64 string.
+    ///
+    #[cfg(feature = "schema")]
+    pub fn json_schema() -> schemars::schema::RootSchema {
+        schemars::schema_for!(AppCenterLogs)
+    }
+
+    ///
+    /// The page set by the last call to `AppCenter::track_page` before the report
+    ///
+    pub fn current_page(&self) -> Option<&str> {
+        self.current_page.as_deref()
+    }
+
+    ///
+    /// Send this report even when it falls outside of the sample rate, for example
+    /// from a report callback that recognizes an interesting panic
+    ///
+    pub fn set_force_send(&mut self, force: bool) {
+        self.force_send = force;
+    }
+
+    ///
+    /// Serialize the report as it is sent to AppCenter
+    ///
+    pub fn to_json_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(self)
+    }
+
+    ///
+    /// Serialize the report as indented JSON, for debugging
+    ///
+    pub fn to_json_pretty(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    ///
+    /// The id of the error, attachments refer to it as `errorId`
+    ///
+    pub fn error_id(&self) -> uuid::Uuid {
+        self.logs[0].id()
+    }
+
+    ///
+    /// When the error happened
+    ///
+    pub fn timestamp(&self) -> chrono::DateTime<chrono::Utc> {
+        match &self.logs[0] {
+            AppCenterLog::ManagedError { timestamp, .. }
+            | AppCenterLog::HandledError { timestamp, .. } => *timestamp,
+            _ => unreachable!(),
+        }
+    }
+
+    ///
+    /// The user ID the error is associated with
+    ///
+    pub fn user_id(&self) -> Option<&str> {
+        match &self.logs[0] {
+            AppCenterLog::ManagedError { user_id, .. }
+            | AppCenterLog::HandledError { user_id, .. } => user_id.as_deref(),
+            _ => unreachable!(),
+        }
+    }
+
+    ///
+    /// The device the error happened on
+    ///
+    pub fn device(&self) -> &Device {
+        match &self.logs[0] {
+            AppCenterLog::ManagedError { device, .. }
+            | AppCenterLog::HandledError { device, .. } => device,
+            _ => unreachable!(),
+        }
+    }
+
+    ///
+    /// The type of the exception, `"panic"` for panics
+    ///
+    pub fn exception_type(&self) -> &str {
+        self.exception().exception_type()
+    }
+
+    ///
+    /// The panic message, formatted as `thread '<name>' panicked at '<payload>', <file>:<line>`.
+    ///
+    /// ```no_run
+    /// let app_center = app_center::start!("00000000-0000-0000-0000-000000000000");
+    /// app_center.set_report_callback(|logs| {
+    ///     if logs.message().contains("database") {
+    ///         let note = format!("report {}", logs.error_id());
+    ///         let _ = logs.add_binary_attachement(note.into_bytes(), Some("db.txt"));
+    ///     }
+    /// });
+    /// ```
+    ///
+    pub fn message(&self) -> &str {
+        self.exception().message()
+    }
+
+    ///
+    /// The frames of the backtrace
+    ///
+    pub fn frames(&self) -> &[ExceptionFrame] {
+        self.exception().frames()
+    }
+
+    ///
+    /// Replace the panic message, for example to redact paths or user data
+    ///
+    pub fn set_message(&mut self, message: String) {
+        self.exception_mut().set_message(message);
+    }
+
+    ///
+    /// Replace the type of the exception
+    ///
+    pub fn set_exception_type(&mut self, exception_type: String) {
+        self.exception_mut().set_exception_type(exception_type);
+    }
+
+    ///
+    /// Replace the user ID the error is associated with
+    ///
+    pub fn set_user_id(&mut self, id: Option<String>) {
+        match &mut self.logs[0] {
+            AppCenterLog::ManagedError { user_id, .. }
+            | AppCenterLog::HandledError { user_id, .. } => *user_id = id,
+            _ => unreachable!(),
+        }
+    }
+
+    ///
+    /// Keep only the frames of the backtrace for which `f` returns true
+    ///
+    pub fn retain_frames<F: FnMut(&ExceptionFrame) -> bool>(&mut self, f: F) {
+        self.exception_mut().retain_frames(f);
+    }
+
+    fn exception_mut(&mut self) -> &mut AppCenterException {
+        match &mut self.logs[0] {
+            AppCenterLog::ManagedError { exception, .. }
+            | AppCenterLog::HandledError { exception, .. } => exception,
+            _ => unreachable!(),
+        }
+    }
+
+    fn exception(&self) -> &AppCenterException {
+        match &self.logs[0] {
+            AppCenterLog::ManagedError { exception, .. }
+            | AppCenterLog::HandledError { exception, .. } => exception,
+            _ => unreachable!(),
+        }
+    }
+
+    fn set_hook_execution_time(&mut self, hook_entry: Instant) {
+        if let AppCenterLog::ManagedError {
+            hook_execution_ms, ..
+        } = &mut self.logs[0]
+        {
+            *hook_execution_ms = Some(hook_entry.elapsed().as_millis() as u64);
+        }
+    }
+
+    fn add_attachement_inner(
+        &mut self,
+        data: Vec<u8>,
+        file_name: Option<&'a str>,
+        content_type: &'static str,
+    ) {
+        // First attachement is always the error
+        let (device, error_id) = match &self.logs[0] {
+            AppCenterLog::ManagedError { device, id, .. }
+            | AppCenterLog::HandledError { device, id, .. } => (Arc::clone(device), *id),
+            _ => unreachable!(),
+        };
+
+        let id = match &self.ids {
+            Some(ids) => ids.next_id(),
+            None => uuid::Uuid::new_v4(),
+        };
+        self.logs.push(AppCenterLog::ErrorAttachment {
+            id,
+            device,
+            error_id,
+            content_type: Cow::Borrowed(content_type),
+            data: Base64Data {
+                data,
+                variant: self.base64_variant,
+            },
+            file_name: file_name.map(Cow::Borrowed),
+        });
+    }
+
+    // Resolve a lazily captured backtrace, before the report is serialized
+    fn resolve_frames(&mut self) {
+        self.exception_mut().resolve();
+    }
+
+    ///
+    /// Attach binary data to the report. The file name must be a plain name of at most 255
+    /// characters, without path separators or null bytes, and not `.` or `..`.
+    ///
+    pub fn add_binary_attachement(
+        &mut self,
+        data: Vec<u8>,
+        file_name: Option<&'a str>,
+    ) -> Result<(), AttachmentError> {
+        validate_file_name(file_name)?;
+        self.add_attachement_inner(data, file_name, "application/octet_stream");
+        Ok(())
+    }
+
+    ///
+    /// Attach text to the report, with the same file name rules as `add_binary_attachement`
+    ///
+    pub fn add_text_attachement(
+        &'a mut self,
+        data: &str,
+        file_name: Option<&'a str>,
+    ) -> Result<(), AttachmentError> {
+        validate_file_name(file_name)?;
+        self.add_attachement_inner(data.as_bytes().to_vec(), file_name, "text/plain");
+        Ok(())
+    }
+}
+
+fn validate_file_name(file_name: Option<&str>) -> Result<(), AttachmentError> {
+    match file_name {
+        Some(name)
+            if name.is_empty()
+                || name == "."
+                || name == ".."
+                || name.contains(['/', '\\', '\0'])
+                || name.chars().count() > MAX_FILE_NAME_LENGTH =>
+        {
+            Err(AttachmentError::InvalidFileName(name.to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+///
+/// The reason an attachment was not added to a report
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttachmentError {
+    /// The file name is empty, `.` or `..`, longer than 255 characters, or contains a path
+    /// separator or a null byte
+    InvalidFileName(String),
+}
+
+impl std::fmt::Display for AttachmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttachmentError::InvalidFileName(name) => {
+                write!(f, "invalid attachment file name {:?}", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AttachmentError {}
+
+// Queued events are kept for the next launch, there is no time to upload them here
+impl Drop for AppCenterInner {
+    fn drop(&mut self) {
+        self.events.close();
+        if self.is_analytics_enabled() {
+            self.save_logs(self.events.take_all());
+        }
+    }
+}
+
+impl AppCenterInner {
+    fn is_enabled(&self) -> bool {
+        !self.noop && self.enabled.load(Ordering::Relaxed)
+    }
+
+    fn is_crashes_enabled(&self) -> bool {
+        self.is_enabled() && self.crashes_enabled.load(Ordering::Relaxed)
+    }
+
+    fn is_analytics_enabled(&self) -> bool {
+        self.is_enabled() && self.analytics_enabled.load(Ordering::Relaxed)
+    }
+
+    fn is_analytics_alive(&self) -> bool {
+        let running = self
+            .event_sender
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished());
+        let since_heartbeat =
+            epoch_ms().saturating_sub(self.last_heartbeat_epoch_ms.load(Ordering::Relaxed));
+        running && since_heartbeat < HEARTBEAT_TIMEOUT.as_millis() as u64
+    }
+
+    fn frame_options(&self) -> FrameOptions {
+        FrameOptions {
+            resolution_mode: *self.resolution_mode.lock().unwrap(),
+            include_raw_symbols: self.include_raw_symbols,
+            skip_patterns: self.skip_frame_patterns.clone(),
+            path_prefixes: self.path_prefixes.clone(),
+        }
+    }
+
+    // A crash report for a panic when `fatal`, or for a handled error
+    fn new_payload(&self, exception: AppCenterException, fatal: bool) -> AppCenterLogs<'static> {
+        self.payload_from(self.snapshot(exception, fatal))
+    }
+
+    // The fields of a report that change over time or are kept per thread, cheap enough to
+    // take from the panic hook
+    fn snapshot(&self, exception: AppCenterException, fatal: bool) -> ReportSnapshot {
+        ReportSnapshot {
+            exception,
+            fatal,
+            id: self.ids.next_id(),
+            timestamp: self.clock.now(),
+            user_id: { (*self.user_id.lock().unwrap()).clone() },
+            app_launch_timestamp: *self.app_launch_timestamp.lock().unwrap(),
+            session_id: *self.session_id.lock().unwrap(),
+            current_page: { self.current_page.lock().unwrap().clone() },
+            scope_tags: scope::scope_tags(),
+        }
+    }
+
+    // The rest of the report: the device, the process and the attachments
+    fn payload_from(&self, snapshot: ReportSnapshot) -> AppCenterLogs<'static> {
+        let ReportSnapshot {
+            exception,
+            fatal,
+            id,
+            timestamp,
+            user_id,
+            app_launch_timestamp,
+            session_id,
+            current_page,
+            scope_tags,
+        } = snapshot;
+        let user_id = user_id.map(|id| self.apply_user_id_mode(&id));
+        // Shared with the attachments
+        let device = Arc::new(self.current_device());
+
+        let mut properties = { self.properties.lock().unwrap().clone() };
+        properties.extend(self.tags.lock().unwrap().clone());
+        properties.extend(scope_tags);
+        if let Some(page) = &current_page {
+            properties.insert("page".to_string(), page.clone());
+        }
+        properties.insert("launch_count".to_string(), self.launch_count.to_string());
+        if self.include_cwd {
+            if let Some(cwd) = Utils::get_cwd() {
+                properties.insert("cwd".to_string(), cwd);
+            }
+        }
+
+        let log = if fatal {
+            AppCenterLog::ManagedError {
+                id,
+                user_id,
+                app_launch_timestamp,
+                timestamp,
+                fatal: true,
+                process_id: Utils::get_pid(),
+                process_name: self.process_name.clone(),
+                device,
+                exception,
+                grouping_key: None,
+                is_first_launch: self.launch_count <= 1,
+                session_id,
+                hook_execution_ms: None,
+            }
+        } else {
+            AppCenterLog::HandledError {
+                id,
+                user_id,
+                app_launch_timestamp,
+                timestamp,
+                process_id: Utils::get_pid(),
+                process_name: self.process_name.clone(),
+                device,
+                exception,
+                grouping_key: None,
+                session_id,
+                // Within the limits of AppCenter, all of them are in `properties.json`
+                properties: properties
+                    .iter()
+                    .take(MAX_PROPERTIES)
+                    .map(|(key, value)| {
+                        (
+                            truncate(key.clone(), MAX_PROPERTY_LENGTH),
+                            truncate(value.clone(), MAX_PROPERTY_LENGTH),
+                        )
+                    })
+                    .collect(),
+            }
+        };
+
+        let mut payload = AppCenterLogs {
+            logs: vec![log],
+            base64_variant: *self.base64_variant.lock().unwrap(),
+            force_send: false,
+            current_page,
+            ids: Some(Arc::clone(&self.ids)),
+        };
+
+        if let Ok(data) = serde_json::to_vec(&properties) {
+            payload.add_attachement_inner(data, Some("properties.json"), "application/json");
+        }
+
+        if let Some(env_snapshot) = &self.env_snapshot {
+            if let Ok(data) = serde_json::to_vec(env_snapshot) {
+                payload.add_attachement_inner(data, Some("environment.json"), "application/json");
+            }
+        }
+
+        if self.attach_system_state {
+            // The volume the reports are stored on, or the one of the executable
+            let disk = { self.crash_dir.lock().unwrap().clone() }.or_else(|| {
+                std::env::current_exe()
+                    .ok()
+                    .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+            });
+            let state =
+                system::SystemState::collect(app_launch_timestamp, timestamp, disk.as_deref());
+            if let Ok(data) = serde_json::to_vec(&state) {
+                payload.add_attachement_inner(data, Some("system.json"), "application/json");
+            }
+        }
+
+        if let Ok(data) = serde_json::to_vec(&build_info::BuildInfo::current(&self.build_metadata))
+        {
+            payload.add_attachement_inner(data, Some("build_info.json"), "application/json");
+        }
+
+        if let Some(data) = self.breadcrumbs.to_json() {
+            payload.add_attachement_inner(data, Some("breadcrumbs.json"), "application/json");
+        }
+
+        if let Some(logs) = logger::captured_logs() {
+            payload.add_attachement_inner(logs.into_bytes(), Some("log.txt"), "text/plain");
+        }
+
+        payload
+    }
+
+    fn send_report(
+        &self,
+        report: &[u8],
+        endpoint: &str,
+        app_secret: &str,
+        install_id: &uuid::Uuid,
+        timeout: Duration,
+    ) -> Result<(), SendError> {
+        let sender = self
+            .http_sender
+            .as_ref()
+            .ok_or_else(|| SendError::from(Box::from("sending is compiled out")))?;
+
+        let request = HttpRequest::new(endpoint, app_secret, install_id, report, timeout);
+        let resp = sender.send(&request)?;
+
+        if !resp.is_success() {
+            let mut body = resp.body().to_string();
+            if let Some(idx) = (0..=MAX_ERROR_BODY_LENGTH)
+                .rev()
+                .find(|&i| body.is_char_boundary(i))
+            {
+                body.truncate(idx);
+            }
+
+            if (400..500).contains(&resp.status()) {
+                let hint = match resp.status() {
+                    400 => ", the payload is malformed",
+                    401 => ", the app secret is invalid",
+                    403 => ", the app refused the request",
+                    _ => "",
+                };
+                app_log!(
+                    Error,
+                    "AppCenter rejected the report with HTTP {}{}: {}",
+                    resp.status(),
+                    hint,
+                    body
+                );
+            }
+            return Err(SendError::from_status(resp.status(), body));
+        }
+
+        app_log!(Info, "Crash report sent: {:?}", resp);
+        Ok(())
+    }
+
+    fn verify_connection(&self) -> Result<(), VerifyError> {
+        let payload = AppCenterLogs {
+            logs: vec![AppCenterLog::StartSession {
+                id: self.ids.next_id(),
+                session_id: *self.session_id.lock().unwrap(),
+                timestamp: self.clock.now(),
+                device: self.current_device(),
+            }],
+            base64_variant: Base64Variant::default(),
+            force_send: false,
+            current_page: None,
+            ids: None,
+        };
+        let report = payload
+            .to_json_bytes()
+            .map_err(|err| VerifyError::Network(SendError::from(Box::from(err.to_string()))))?;
+
+        self.send_verified(&report)
+    }
+
+    // Send to every app from the calling thread, classifying the response
+    fn send_verified(&self, report: &[u8]) -> Result<(), VerifyError> {
+        let sender = self.http_sender.as_ref().ok_or(VerifyError::Disabled)?;
+
+        for app_secret in self.app_secrets() {
+            let request = HttpRequest::new(
+                &self.endpoint,
+                app_secret,
+                &self.install_id(),
+                report,
+                self.timeout,
+            );
+            let resp = sender.send(&request).map_err(VerifyError::Network)?;
+
+            match resp.status() {
+                401 => return Err(VerifyError::Unauthorized),
+                403 => return Err(VerifyError::Forbidden),
+                _ if resp.is_success() => {}
+                status => return Err(VerifyError::Status(status)),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Send the report the way it would have been sent at the time of the crash
+    fn send_stored_report(&self, report: &persist::StoredReport) -> Result<(), SendError> {
+        let endpoint = report
+            .envelope
+            .endpoint
+            .as_deref()
+            .unwrap_or(&self.endpoint);
+        let app_secret = self.stored_app_secret(&report.envelope);
+        let install_id = report.envelope.install_id.unwrap_or(self.install_id());
+        self.send_report(
+            &report.payload,
+            endpoint,
+            app_secret,
+            &install_id,
+            self.timeout,
+        )
+    }
+
+    // The app a stored report was saved for, the primary app when the envelope doesn't say
+    // or the app is no longer configured
+    fn stored_app_secret(&self, envelope: &Envelope) -> &str {
+        self.app_secrets()
+            .find(|app_secret| envelope.is_for(app_secret))
+            .unwrap_or(&self.app_secret)
+    }
+
+    // The primary app secret followed by the mirrors, every report is sent to each of them
+    fn app_secrets(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.app_secret.as_str())
+            .chain(self.mirror_app_secrets.iter().map(|s| s.as_str()))
+    }
+
+    // Nothing is stored without the `enabled` feature
+    fn report_store(&self) -> Option<ReportStore> {
+        if cfg!(not(feature = "enabled")) {
+            return None;
+        }
+        let dir = self.crash_dir.lock().unwrap().clone()?;
+        let store = ReportStore::new(dir);
+        #[cfg(feature = "encryption")]
+        let store = store.with_key(*self.encryption_key.lock().unwrap());
+        Some(store)
+    }
+
+    // Keep the report around so it can be sent on the next launch
+    fn save_report(&self, id: &uuid::Uuid, app_secret: &str, report: &[u8]) {
+        if let Some(store) = self.report_store() {
+            let envelope = Envelope::new(&self.endpoint, app_secret, self.install_id());
+            match store.save(id, &envelope, report) {
+                Ok(path) => app_log!(Info, "Crash report saved to {:?}", path),
+                Err(err) => app_log!(Error, "Failed to save crash report {:?}", err),
+            }
+        }
+    }
+
+    fn send_pending_reports(&self) -> PendingSummary {
+        let mut summary = PendingSummary::default();
+
+        // Without a sender every attempt would fail and eventually drop the report
+        if self.http_sender.is_none() {
+            return summary;
+        }
+
+        let store = match self.report_store() {
+            Some(store) => store,
+            None => return summary,
+        };
+
+        let max_attempts = self.max_send_attempts.load(Ordering::Relaxed);
+        let now = chrono::Utc::now();
+
+        let pending = match store.pending() {
+            Ok(pending) => pending,
+            Err(err) => {
+                app_log!(Error, "Failed to read the crash directory {:?}", err);
+                return summary;
+            }
+        };
+
+        let mut by_target: BTreeMap<String, Vec<(PathBuf, persist::StoredReport)>> =
+            BTreeMap::new();
+        let sent = self.settings.load().sent_report_ids;
+
+        for path in pending {
+            if ReportStore::report_id(&path).is_some_and(|id| sent.contains(&id)) {
+                app_log!(Info, "Removing crash report {:?}, already sent", path);
+                store.remove(&path);
+                summary.duplicates += 1;
+                continue;
+            }
+
+            let meta = store.meta(&path);
+
+            if meta.attempts >= max_attempts {
+                app_log!(
+                    Warn,
+                    "Dropping crash report {:?} after {} attempts",
+                    path,
+                    meta.attempts
+                );
+                store.remove(&path);
+                summary.expired += 1;
+                continue;
+            }
+
+            if !meta.is_due(now) {
+                summary.deferred += 1;
+                continue;
+            }
+
+            let report = match store.load(&path) {
+                Ok(report) => report,
+                Err(LoadError::MissingKey) | Err(LoadError::Unsupported) => continue,
+                Err(LoadError::Corrupt) => {
+                    app_log!(Warn, "Discarding unreadable crash report {:?}", path);
+                    store.remove(&path);
+                    summary.discarded += 1;
+                    continue;
+                }
+                Err(LoadError::Expired) => {
+                    app_log!(Warn, "Dropping expired crash report {:?}", path);
+                    store.remove(&path);
+                    summary.expired += 1;
+                    continue;
+                }
+            };
+
+            store.record_attempt(&path, &meta);
+
+            let target = self.stored_app_secret(&report.envelope).to_string();
+            by_target.entry(target).or_default().push((path, report));
+        }
+
+        // Each app is sent to from its own thread, so a slow app doesn't hold back the others
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = by_target
+                .into_values()
+                .map(|reports| {
+                    let store = &store;
+                    scope.spawn(move || {
+                        let mut results = Vec::new();
+                        for (path, report) in reports {
+                            let result = self.send_stored_report(&report);
+                            if result.is_ok() {
+                                self.remove_sent_report(store, &path);
+                            }
+                            results.push(result);
+                        }
+                        results
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().unwrap_or_default())
+                .collect()
+        });
+
+        for result in results {
+            match result {
+                Ok(()) => summary.sent += 1,
+                Err(err) => {
+                    app_log!(Error, "Failed to send pending crash report {:?}", err);
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        summary
+    }
+
+    fn purge_local_data(&self) -> std::io::Result<PurgeSummary> {
+        let mut summary = PurgeSummary::default();
+
+        self.events.take_all();
+        *self.install_id.lock().unwrap() = uuid::Uuid::new_v4();
+        *self.session_id.lock().unwrap() = self.ids.next_id();
+        *self.user_id.lock().unwrap() = None;
+        *self.last_error_id.lock().unwrap() = None;
+        *self.current_page.lock().unwrap() = None;
+        self.properties.lock().unwrap().clear();
+        self.tags.lock().unwrap().clear();
+        self.breadcrumbs.clear();
+
+        self.settings.purge(&mut summary);
+        if let Some(store) = self.report_store() {
+            store.purge(&mut summary)?;
+        }
+
+        app_log!(
+            Info,
+            "Local data purged, {} files deleted",
+            summary.deleted.len()
+        );
+        Ok(summary)
+    }
+
+    fn install_id(&self) -> uuid::Uuid {
+        *self.install_id.lock().unwrap()
+    }
+
+    // The id is remembered before the file is removed, so a copy left behind by a crash in
+    // between is recognized instead of being sent twice
+    fn remove_sent_report(&self, store: &ReportStore, path: &std::path::Path) {
+        if let Some(id) = ReportStore::report_id(path) {
+            self.settings.update(|s| s.record_sent_report(id));
+        }
+        store.remove(path);
+    }
+
+    fn flush_queued_reports(&self) -> Result<usize, FlushError> {
+        if !self.is_crashes_enabled() || self.http_sender.is_none() {
+            return Err(FlushError::Disabled);
+        }
+
+        let store = self.report_store().ok_or(FlushError::NoCrashDir)?;
+        let mut flushed = 0;
+        let sent = self.settings.load().sent_report_ids;
+
+        for path in store.pending()? {
+            if ReportStore::report_id(&path).is_some_and(|id| sent.contains(&id)) {
+                store.remove(&path);
+                continue;
+            }
+
+            let report = match store.load(&path) {
+                Ok(report) => report,
+                Err(LoadError::MissingKey) | Err(LoadError::Unsupported) => continue,
+                Err(LoadError::Corrupt) => {
+                    app_log!(Warn, "Discarding unreadable crash report {:?}", path);
+                    store.remove(&path);
+                    continue;
+                }
+                Err(LoadError::Expired) => {
+                    app_log!(Warn, "Dropping expired crash report {:?}", path);
+                    store.remove(&path);
+                    continue;
+                }
+            };
+
+            store.record_attempt(&path, &store.meta(&path));
+
+            if let Err(error) = self.send_stored_report(&report) {
+                return Err(FlushError::Send { flushed, error });
+            }
+
+            self.remove_sent_report(&store, &path);
+            flushed += 1;
+        }
+
+        Ok(flushed)
+    }
+
+    fn run_report_callbacks(&self, payload: &mut AppCenterLogs) {
+        let callbacks = { self.on_report.lock().unwrap().clone() };
+
+        for (_, callback) in callbacks {
+            // Keep a misbehaving callback from taking the others down. Note that a panic
+            // inside the panic hook itself still aborts the process.
+            let run = panic::catch_unwind(panic::AssertUnwindSafe(|| callback(payload)));
+            if run.is_err() {
+                app_log!(Error, "Report callback panicked");
+            }
+        }
+    }
+
+    // Returns false when the scrubber panicked, the logs must not be sent then
+    fn scrub(&self, logs: &mut [AppCenterLog]) -> bool {
+        let scrubber = { self.scrubber.lock().unwrap().clone() };
+        let scrubber = match scrubber {
+            Some(scrubber) => scrubber,
+            None => return true,
+        };
+
+        let run = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            scrub::scrub_logs(logs, &*scrubber)
+        }));
+        if run.is_err() {
+            app_log!(Error, "Scrubber panicked, the report is dropped");
+        }
+        run.is_ok()
+    }
+
+    fn apply_grouping_key(&self, payload: &mut AppCenterLogs) {
+        let key_fn = { self.grouping_key_fn.lock().unwrap().clone() };
+
+        if let AppCenterLog::ManagedError {
+            exception,
+            grouping_key,
+            ..
+        }
+        | AppCenterLog::HandledError {
+            exception,
+            grouping_key,
+            ..
+        } = &mut payload.logs[0]
+        {
+            let key = key_fn.and_then(|key_fn| {
+                let key = panic::catch_unwind(panic::AssertUnwindSafe(|| key_fn(exception)));
+                if key.is_err() {
+                    app_log!(
+                        Error,
+                        "Grouping key function panicked, using the default key"
+                    );
+                }
+                key.ok()
+            });
+            *grouping_key = Some(key.unwrap_or_else(|| exception.default_grouping_key()));
+        }
+    }
+
+    // The mode is called outside of its lock, it may be a user function
+    fn apply_user_id_mode(&self, user_id: &str) -> String {
+        let mode = { self.user_id_mode.lock().unwrap().clone() };
+        mode.apply(user_id)
+    }
+
+    fn current_device(&self) -> Device {
+        let country_code = { self.country_code.lock().unwrap().clone() };
+
+        if let Some(device) = &self.device {
+            let mut device = device.clone();
+            if country_code.is_some() {
+                device.set_country_code(country_code);
+            }
+            return device;
+        }
+
+        let app_build = { (*self.app_build.lock().unwrap()).clone() };
+        let country_code = country_code.or_else(|| self.detected_country_code.clone());
+        Device::current_device(self.app_version, &app_build, country_code)
+    }
+
+    // Notify AppCenter about the new session from a background thread, so `start` is not
+    // blocked on the network
+    fn start_session(self: &Arc<Self>) {
+        if !self.is_analytics_enabled() || self.reporting_mode == ReportingMode::Never {
+            return;
+        }
+
+        let app_center = Arc::clone(self);
+        let session_id = *self.session_id.lock().unwrap();
+
+        std::thread::spawn(move || {
+            let log = AppCenterLog::StartSession {
+                id: app_center.ids.next_id(),
+                session_id,
+                timestamp: app_center.clock.now(),
+                device: app_center.current_device(),
+            };
+            if app_center.is_analytics_enabled() {
+                app_center.send_logs(vec![log], "start session", false);
+            }
+        });
+    }
+
+    fn track_event(
+        self: &Arc<Self>,
+        name: &str,
+        properties: Option<BTreeMap<String, String>>,
+        typed_properties: Option<EventProperties>,
+    ) -> Result<(), EventValidationError> {
+        let name = self.event_validation.name(name)?;
+        let properties = properties
+            .map(|p| self.event_validation.properties(p))
+            .transpose()?;
+        let typed_properties = typed_properties
+            .map(|p| self.event_validation.typed_properties(p))
+            .transpose()?;
+
+        if !self.is_analytics_enabled() || self.reporting_mode == ReportingMode::Never {
+            return Ok(());
+        }
+
+        let log = AppCenterLog::Event {
+            id: self.ids.next_id(),
+            name,
+            timestamp: self.clock.now(),
+            session_id: *self.session_id.lock().unwrap(),
+            device: self.current_device(),
+            properties,
+            typed_properties,
+        };
+        self.enqueue_event(log);
+        Ok(())
+    }
+
+    fn track_page(self: &Arc<Self>, name: &str) -> Result<(), EventValidationError> {
+        let name = self.event_validation.name(name)?;
+
+        // The page is context for crashes, even when analytics is disabled
+        *self.current_page.lock().unwrap() = Some(name.clone());
+        let session_id = *self.session_id.lock().unwrap();
+        self.breadcrumbs
+            .push(self.clock.now(), session_id, "navigation", &name, None);
+
+        if !self.is_analytics_enabled() || self.reporting_mode == ReportingMode::Never {
+            return Ok(());
+        }
+
+        let log = AppCenterLog::Page {
+            id: self.ids.next_id(),
+            name,
+            timestamp: self.clock.now(),
+            session_id: *self.session_id.lock().unwrap(),
+            device: self.current_device(),
+        };
+        self.enqueue_event(log);
+        Ok(())
+    }
+
+    // Hand an analytics log to the background sender
+    fn enqueue_event(self: &Arc<Self>, log: AppCenterLog<'static>) {
+        {
+            let mut sender = self.event_sender.lock().unwrap();
+            if sender.as_ref().is_none_or(|thread| thread.is_finished()) {
+                if sender.is_some() {
+                    // Queued events are kept, the new thread picks them up
+                    app_log!(Warn, "The analytics thread died, restarting it");
+                    self.events.clear_poison();
+                }
+                let events = Arc::clone(&self.events);
+                let weak = Arc::downgrade(self);
+                let heartbeat = Arc::clone(&self.last_heartbeat_epoch_ms);
+                *sender = Some(std::thread::spawn(move || {
+                    Self::send_events(weak, events, heartbeat)
+                }));
+            }
+        }
+
+        let mut log = log;
+        if !self.scrub(std::slice::from_mut(&mut log)) {
+            return;
+        }
+
+        if self.events.push(log) > EVENT_QUEUE_LIMIT {
+            self.save_logs(self.events.take(EVENT_BATCH_SIZE));
+        }
+    }
+
+    // Upload the queued events in batches, until the instance is dropped
+    fn send_events(
+        weak: Weak<Self>,
+        events: Arc<EventQueue<AppCenterLog<'static>>>,
+        heartbeat: Arc<AtomicU64>,
+    ) {
+        loop {
+            heartbeat.store(epoch_ms(), Ordering::Relaxed);
+            let batch =
+                match events.next_batch(EVENT_BATCH_SIZE, EVENT_FLUSH_INTERVAL, HEARTBEAT_INTERVAL)
+                {
+                    Some(batch) => batch,
+                    None => return,
+                };
+            if batch.is_empty() {
+                continue;
+            }
+
+            // Events queued before analytics was disabled are dropped
+            match weak.upgrade() {
+                Some(app_center) if app_center.is_analytics_enabled() => {
+                    app_center.send_logs(batch, "events", true)
+                }
+                Some(_) => {}
+                None => return,
+            }
+        }
+    }
+
+    // Upload the queued events from the calling thread
+    fn flush_events(&self) {
+        let mut events = self.events.take_all();
+        while !events.is_empty() {
+            let rest = events.split_off(events.len().min(EVENT_BATCH_SIZE));
+            self.send_logs(events, "events", true);
+            events = rest;
+        }
+    }
+
+    // Send logs that are not crash reports, such as sessions and events. When `persist` is
+    // set, logs that fail to send are kept for `send_pending_reports` like crash reports.
+    fn send_logs(&self, logs: Vec<AppCenterLog>, what: &str, persist: bool) {
+        let payload = AppCenterLogs {
+            logs,
+            base64_variant: Base64Variant::default(),
+            force_send: false,
+            current_page: None,
+            ids: None,
+        };
+
+        if self.dry_run {
+            self.print_report(&payload);
+        } else if self.http_sender.is_none() {
+            if persist {
+                self.save_logs(payload.logs);
+            }
+        } else if let Ok(report) = payload.to_json_bytes() {
+            for (i, app_secret) in self.app_secrets().enumerate() {
+                let sent = self.send_report(
+                    &report,
+                    &self.endpoint,
+                    app_secret,
+                    &self.install_id(),
+                    self.timeout,
+                );
+                if let Err(err) = sent {
+                    app_log!(Warn, "Failed to send {} {:?}", what, err);
+                    if persist {
+                        // Stored under the id of the first log, for duplicate suppression
+                        let id = match i {
+                            0 => payload.logs[0].id(),
+                            _ => uuid::Uuid::new_v4(),
+                        };
+                        self.save_report(&id, app_secret, &report);
+                    }
+                }
+            }
+        }
+    }
+
+    // Keep logs that were not sent for the next launch, in batches of the upload size
+    fn save_logs(&self, mut logs: Vec<AppCenterLog>) {
+        while !logs.is_empty() {
+            let rest = logs.split_off(logs.len().min(EVENT_BATCH_SIZE));
+            let payload = AppCenterLogs {
+                logs,
+                base64_variant: Base64Variant::default(),
+                force_send: false,
+                current_page: None,
+                ids: None,
+            };
+
+            if let Ok(report) = payload.to_json_bytes() {
+                for (i, app_secret) in self.app_secrets().enumerate() {
+                    let id = match i {
+                        0 => payload.logs[0].id(),
+                        _ => uuid::Uuid::new_v4(),
+                    };
+                    self.save_report(&id, app_secret, &report);
+                }
+            }
+            logs = rest;
+        }
+    }
+
+    // In dry-run mode the report is written to stderr instead of being sent
+    fn print_report(&self, payload: &AppCenterLogs) {
+        use std::io::{IsTerminal, Write};
+
+        let stderr = std::io::stderr();
+        let pretty = self.dry_run_pretty.unwrap_or_else(|| stderr.is_terminal());
+
+        let report = if pretty {
+            payload.to_json_pretty().map(String::into_bytes)
+        } else {
+            payload.to_json_bytes()
+        };
+
+        if let Ok(report) = report {
+            let mut stderr = stderr.lock();
+            let _ = stderr.write_all(&report);
+            let _ = stderr.write_all(b"\n");
+        }
+    }
+
+    // The hook executed after the report is sent
+    #[cfg(feature = "enabled")]
+    fn fallback_hook(&self) -> PanicHook {
+        #[cfg(feature = "color-backtrace")]
+        {
+            if self.pretty_panic_output {
+                return color_backtrace::BacktracePrinter::new()
+                    .into_panic_handler(color_backtrace::default_output_stream());
+            }
+        }
+
+        panic::take_hook()
+    }
+
+    // Draw whether the current report is within the sample
+    fn is_sampled(&self) -> bool {
+        self.sample_rate >= 1.0 || in_sample(self.sample_rate, uuid::Uuid::new_v4().as_u128())
+    }
+
+    // Only the addresses of the backtrace and the snapshot are taken here, the symbols, the
+    // attachments and the sending are left to the reporting thread
+    #[cfg(feature = "enabled")]
+    fn report_panic(
+        self: &Arc<Self>,
+        panic_info: &PanicHookInfo,
+        hook_entry: Instant,
+    ) -> Option<reporter::PendingReport> {
+        let options = FrameOptions {
+            resolution_mode: ResolutionMode::Lazy,
+            ..self.frame_options()
+        };
+        let exception = AppCenterException::new(panic_info, &options);
+        // Inside a non-fatal `catch`, the panic is reported as a handled error once caught
+        let snapshot = self.snapshot(catch::stash(self, exception)?, true);
+
+        let app_center = Arc::clone(self);
+        let report = move || {
+            let _ = app_center.report(app_center.payload_from(snapshot), hook_entry);
+        };
+        match self.reporter.get() {
+            Some(reporter) => reporter.submit(report),
+            None => {
+                report();
+                None
+            }
+        }
+    }
+
+    // The report as it would be sent, before the filter and sampling
+    fn build_report<'a>(
+        &self,
+        mut payload: AppCenterLogs<'a>,
+    ) -> Result<AppCenterLogs<'a>, ReportError> {
+        self.run_report_callbacks(&mut payload);
+        payload.resolve_frames();
+        self.apply_grouping_key(&mut payload);
+        if !self.scrub(&mut payload.logs) {
+            return Err(ReportError::Discarded);
+        }
+        Ok(payload)
+    }
+
+    // Report a chain of errors, outermost first, as a handled error with nested exceptions
+    #[cfg_attr(not(any(feature = "anyhow", feature = "eyre")), allow(dead_code))]
+    fn report_chain(
+        &self,
+        chain: Vec<&(dyn std::error::Error + 'static)>,
+        fallback_type: &str,
+    ) -> Result<uuid::Uuid, ReportError> {
+        if !self.is_crashes_enabled() {
+            return Err(ReportError::Disabled);
+        }
+
+        let exception =
+            AppCenterException::from_chain(chain.into_iter(), fallback_type, &self.frame_options());
+        self.report(self.new_payload(exception, false), Instant::now())
+    }
+
+    // Run the report through the callbacks and filters, then send it or store it on failure
+    fn report(
+        &self,
+        mut payload: AppCenterLogs,
+        started: Instant,
+    ) -> Result<uuid::Uuid, ReportError> {
+        self.run_report_callbacks(&mut payload);
+
+        payload.resolve_frames();
+        self.apply_grouping_key(&mut payload);
+
+        if self
+            .ignore_list
+            .lock()
+            .unwrap()
+            .matches(payload.exception())
+        {
+            app_log!(Debug, "Crash report discarded by the ignore list");
+            return Err(ReportError::Discarded);
+        }
+
+        let filter = { self.report_filter.lock().unwrap().clone() };
+        if let Some(filter) = filter {
+            if !filter(&payload) {
+                app_log!(Debug, "Crash report discarded by the report filter");
+                return Err(ReportError::Discarded);
+            }
+        }
+
+        if payload.force_send {
+            app_log!(Debug, "Crash report forced past sampling");
+        } else if !self.is_sampled() {
+            app_log!(Debug, "Crash report skipped by sampling");
+            return Err(ReportError::Discarded);
+        }
+
+        if !self.scrub(&mut payload.logs) {
+            return Err(ReportError::Discarded);
+        }
+
+        // Sent anyway, AppCenter has the last word
+        for issue in validate_payload(&payload) {
+            app_log!(Error, "Crash report is likely to be rejected, {}", issue);
+        }
+
+        let error_id = payload.error_id();
+        if self.dry_run {
+            *self.last_error_id.lock().unwrap() = Some(error_id);
+            self.print_report(&payload);
+            return Ok(error_id);
+        }
+
+        if self.reporting_mode == ReportingMode::Never {
+            app_log!(
+                Debug,
+                "Crash report not sent, reporting is off for this build"
+            );
+            return Err(ReportError::Disabled);
+        }
+
+        *self.last_error_id.lock().unwrap() = Some(error_id);
+
+        payload.set_hook_execution_time(started);
+        let report = payload.to_json_bytes().unwrap();
+
+        // Without a way to send, the report is kept for later offline analysis
+        if self.http_sender.is_none() {
+            if self.report_store().is_some() {
+                for (i, app_secret) in self.app_secrets().enumerate() {
+                    let id = match i {
+                        0 => payload.logs[0].id(),
+                        _ => uuid::Uuid::new_v4(),
+                    };
+                    self.save_report(&id, app_secret, &report);
+                }
+            } else {
+                self.print_report(&payload);
+            }
+            return Ok(error_id);
+        }
+
+        // The apps are sent to one after the other, within a single timeout
+        let deadline = Instant::now() + self.timeout;
+        for (i, app_secret) in self.app_secrets().enumerate() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let sent = if remaining.is_zero() {
+                app_log!(Error, "No time left to send crash report");
+                false
+            } else {
+                self.send_report(
+                    &report,
+                    &self.endpoint,
+                    app_secret,
+                    &self.install_id(),
+                    remaining,
+                )
+                .map_err(|err| app_log!(Error, "Failed to send crash report {:?}", err))
+                .is_ok()
+            };
+
+            if !sent {
+                // Only the failed apps are retried, each from its own file. The stored report
+                // also accounts for the failed upload.
+                let id = if i == 0 {
+                    payload.logs[0].id()
+                } else {
+                    uuid::Uuid::new_v4()
+                };
+                payload.set_hook_execution_time(started);
+                let report = payload.to_json_bytes().unwrap();
+                self.save_report(&id, app_secret, &report);
+            }
+        }
+
+        Ok(error_id)
+    }
+
+    #[cfg(feature = "enabled")]
+    fn set_panic_hook(self: &Arc<Self>) {
+        // The hook must not keep the instance alive after the user dropped every handle
+        let weak = Arc::downgrade(self);
+
+        let old_hook = self.fallback_hook();
+
+        match reporter::Reporter::start() {
+            Ok(reporter) => {
+                let _ = self.reporter.set(reporter);
+            }
+            Err(err) => app_log!(
+                Error,
+                "Failed to start the reporting thread, crashes are reported from the panic hook {}",
+                err
+            ),
+        }
+
+        panic::set_hook(Box::new(move |panic_info| {
+            let hook_entry = Instant::now();
+
+            let app_center = match weak.upgrade() {
+                Some(app_center) if app_center.is_crashes_enabled() => app_center,
+                _ => return old_hook(panic_info),
+            };
+
+            let pending = app_center.report_panic(panic_info, hook_entry);
+
+            // Execute the original panic handler
+            old_hook(panic_info);
+
+            // The process may be gone once the hook returns
+            if let Some(pending) = pending {
+                pending.wait(app_center.timeout + REPORT_BUILD_TIMEOUT);
+            }
+        }));
+    }
+}
+
+///
+/// Install the custom panic hook that will attempt to upload panic stacktraces to
+/// appcenter using the provided app secret. `CARGO_PKG_VERSION` will be used as the application version,
+/// unless a version is provided. An optional build number can follow the version.
+/// After the report is sent, the original panic hook is executed.
+///
+/// ```no_run
+/// let app_center = app_center::start!("00000000-0000-0000-0000-000000000000");
+/// let app_center = app_center::start!("00000000-0000-0000-0000-000000000000", "1.2.0");
+/// let app_center = app_center::start!("00000000-0000-0000-0000-000000000000", "1.2.0", "4567");
+/// ```
+///
+#[macro_export]
+macro_rules! start {
+    ($app_secret:expr) => {
+        $crate::AppCenter::start($app_secret, env!("CARGO_PKG_VERSION"))
+    };
+    ($app_secret:expr, $app_version:expr) => {
+        $crate::AppCenter::start($app_secret, $app_version)
+    };
+    ($app_secret:expr, $app_version:expr, $app_build:expr) => {
+        $crate::AppCenter::builder($app_secret)
+            .app_version($app_version)
+            .app_build($app_build)
+            .start()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // Keeps the bodies of the requests
+    #[cfg(feature = "enabled")]
+    #[derive(Clone, Default)]
+    struct RecordingSender(Arc<Mutex<Vec<String>>>);
+
+    #[cfg(feature = "enabled")]
+    impl HttpSender for RecordingSender {
+        fn send(&self, request: &HttpRequest) -> Result<HttpResponse, SendError> {
+            let body = String::from_utf8_lossy(request.body()).to_string();
+            self.0.lock().unwrap().push(body);
+            Ok(HttpResponse::new(200, ""))
+        }
+    }
+
+    #[cfg(feature = "enabled")]
+    fn wait_until(condition: impl Fn() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            if Instant::now() > deadline {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        true
+    }
+
+    #[cfg(feature = "enabled")]
+    #[test]
+    fn dead_analytics_thread_is_detected_and_restarted() {
+        let sender = RecordingSender::default();
+        let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+            .app_version("1.0.0")
+            .auto_start_session(false)
+            .http_sender(sender.clone())
+            .build()
+            .unwrap();
+        // The panics of the poisoning are not reported
+        app_center.set_crashes_enabled(false);
+        assert!(!app_center.is_analytics_alive());
+
+        app_center.track_event("first", None).unwrap();
+        assert!(wait_until(|| app_center.is_analytics_alive()));
+
+        app_center.inner.events.poison();
+        assert!(wait_until(|| !app_center.is_analytics_alive()));
+
+        // Restarted on the same queue, the event queued before the thread died is sent too
+        app_center.track_event("second", None).unwrap();
+        assert!(wait_until(|| app_center.is_analytics_alive()));
+        assert!(wait_until(|| !sender.0.lock().unwrap().is_empty()));
+        let bodies = sender.0.lock().unwrap();
+        assert_eq!(bodies.len(), 1, "{:?}", bodies);
+        assert!(bodies[0].contains("\"first\"") && bodies[0].contains("\"second\""));
+    }
+
+    // Answers every request with the same status
+    #[cfg(feature = "enabled")]
+    struct StatusSender(u16, &'static str);
+
+    #[cfg(feature = "enabled")]
+    impl HttpSender for StatusSender {
+        fn send(&self, _: &HttpRequest) -> Result<HttpResponse, SendError> {
+            Ok(HttpResponse::new(self.0, self.1))
+        }
+    }
+
+    #[cfg(feature = "enabled")]
+    fn send_with(sender: impl HttpSender + 'static) -> Result<(), SendError> {
+        let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+            .app_version("1.0.0")
+            .auto_start_session(false)
+            .http_sender(sender)
+            .build()
+            .unwrap();
+        let inner = &app_center.inner;
+        inner.send_report(
+            b"{}",
+            &inner.endpoint,
+            "12345678-1234-1234-1234-123456789abc",
+            &inner.install_id(),
+            inner.timeout,
+        )
+    }
+
+    #[cfg(feature = "enabled")]
+    #[test]
+    fn unauthorized_response_is_an_http_status_error() {
+        let err = send_with(StatusSender(401, "Invalid app secret")).unwrap_err();
+        assert_eq!(err.http_status(), Some(401));
+        assert_eq!(err.response_body(), Some("Invalid app secret"));
+        assert_eq!(err.to_string(), "HTTP 401: Invalid app secret");
+
+        assert!(send_with(StatusSender(200, "")).is_ok());
+    }
+
+    #[cfg(feature = "enabled")]
+    #[test]
+    fn unreachable_server_has_no_http_status() {
+        struct Unreachable;
+        impl HttpSender for Unreachable {
+            fn send(&self, _: &HttpRequest) -> Result<HttpResponse, SendError> {
+                Err(SendError::from(Box::from("connection refused")))
+            }
+        }
+
+        let err = send_with(Unreachable).unwrap_err();
+        assert_eq!(err.http_status(), None);
+        assert_eq!(err.response_body(), None);
+    }
+
+    #[cfg(feature = "enabled")]
+    #[test]
+    fn error_body_is_truncated() {
+        let body: &'static str = Box::leak("x".repeat(MAX_ERROR_BODY_LENGTH * 2).into_boxed_str());
+        let err = send_with(StatusSender(400, body)).unwrap_err();
+        assert_eq!(err.http_status(), Some(400));
+        assert_eq!(err.response_body().unwrap().len(), MAX_ERROR_BODY_LENGTH);
+    }
+
+    // A xorshift generator with a fixed seed, the same draws on every run
+    fn fixed_rng() -> impl Iterator<Item = u128> {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        std::iter::repeat_with(move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state as u128) << 64
+        })
+    }
+
+    #[test]
+    fn attachment_file_name_must_be_a_plain_name() {
+        for name in [
+            "subdir/file.txt",
+            "subdir\\file.txt",
+            "..",
+            ".",
+            "../file.txt",
+            "/etc/passwd",
+            "C:\\Windows\\win.ini",
+            "",
+            "file\0.txt",
+        ] {
+            assert_eq!(
+                validate_file_name(Some(name)),
+                Err(AttachmentError::InvalidFileName(name.to_string())),
+                "{:?}",
+                name
+            );
+        }
+
+        for name in ["file.txt", "..file", ".hidden", "file..txt"] {
+            assert_eq!(validate_file_name(Some(name)), Ok(()), "{:?}", name);
+        }
+        assert_eq!(validate_file_name(None), Ok(()));
+        let longest = "x".repeat(MAX_FILE_NAME_LENGTH);
+        assert_eq!(validate_file_name(Some(&longest)), Ok(()));
+        assert!(validate_file_name(Some(&format!("{}x", longest))).is_err());
+    }
+
+    #[test]
+    fn sha256_user_id_is_the_hex_digest() {
+        // The test vector of FIPS 180-2
+        assert_eq!(
+            UserIdMode::Sha256.apply("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(UserIdMode::Raw.apply("abc"), "abc");
+
+        let panicking = UserIdMode::Custom(Arc::new(|_| std::panic::resume_unwind(Box::new(()))));
+        assert_eq!(panicking.apply("abc"), UserIdMode::Sha256.apply("abc"));
+    }
+
+    fn sampled(rate: f32) -> usize {
+        fixed_rng()
+            .take(10_000)
+            .filter(|&random| in_sample(rate, random))
+            .count()
+    }
+
+    #[test]
+    fn sample_rate_zero_sends_nothing() {
+        assert_eq!(sampled(0.0), 0);
+        assert!(!in_sample(0.0, 0));
+    }
+
+    #[test]
+    fn sample_rate_one_sends_everything() {
+        assert_eq!(sampled(1.0), 10_000);
+        assert!(in_sample(1.0, u128::MAX));
+    }
+
+    #[test]
+    fn sample_rate_between_sends_a_fraction() {
+        let sent = sampled(0.25);
+        assert!((2_300..2_700).contains(&sent), "{}", sent);
+
+        // Draws below the rate are in the sample, the ones at or above it are not
+        assert!(in_sample(0.5, (1 << 127) - 1));
+        assert!(!in_sample(0.5, 1 << 127));
+    }
+
+    fn device() -> Device {
+        DeviceBuilder::new()
+            .model("test")
+            .os_name("Linux")
+            .os_version("6.1")
+            .locale("en_US")
+            .app_version("1.0.0")
+            .app_build("42")
+            .build()
+    }
+
+    fn exception() -> AppCenterException {
+        AppCenterException::from_parts(
+            "DiskFull".to_string(),
+            "disk full".to_string(),
+            Some(vec![ExceptionFrame::new(
+                Some("app::main".to_string()),
+                Some("src/main.rs".to_string()),
+                Some(3),
+            )]),
+            &FrameOptions::default(),
+        )
+    }
+
+    fn timestamp() -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap()
+    }
+
+    fn id(n: u128) -> uuid::Uuid {
+        uuid::Uuid::from_u128(n)
+    }
+
+    fn managed_error() -> AppCenterLog<'static> {
+        AppCenterLog::ManagedError {
+            id: id(1),
+            user_id: Some("user".to_string()),
+            app_launch_timestamp: timestamp(),
+            timestamp: timestamp(),
+            fatal: true,
+            process_id: 7,
+            process_name: "app".to_string(),
+            device: Arc::new(device()),
+            exception: exception(),
+            grouping_key: Some("key".to_string()),
+            is_first_launch: false,
+            session_id: id(2),
+            hook_execution_ms: Some(12),
+        }
+    }
+
+    fn handled_error() -> AppCenterLog<'static> {
+        AppCenterLog::HandledError {
+            id: id(1),
+            user_id: None,
+            app_launch_timestamp: timestamp(),
+            timestamp: timestamp(),
+            process_id: 7,
+            process_name: "app".to_string(),
+            device: Arc::new(device()),
+            exception: exception(),
+            grouping_key: None,
+            session_id: id(2),
+            properties: BTreeMap::from([("launch_count".to_string(), "3".to_string())]),
+        }
+    }
+
+    fn attachment(variant: Base64Variant) -> AppCenterLog<'static> {
+        AppCenterLog::ErrorAttachment {
+            id: id(3),
+            error_id: id(1),
+            device: Arc::new(device()),
+            content_type: Cow::Borrowed("application/octet_stream"),
+            data: Base64Data {
+                // Encodes to `+` and `/` with the standard alphabet
+                data: vec![0xfb, 0xff, 0xbf],
+                variant,
+            },
+            file_name: Some(Cow::Borrowed("state.bin")),
+        }
+    }
+
+    // Serialize, read back and serialize again, the JSON must not change
+    fn assert_round_trip(log: AppCenterLog) {
+        let json = serde_json::to_string(&log).unwrap();
+        let back: AppCenterLog = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&back).unwrap(), json);
+    }
+
+    #[test]
+    fn managed_error_round_trip() {
+        assert_round_trip(managed_error());
+    }
+
+    #[test]
+    fn handled_error_round_trip() {
+        assert_round_trip(handled_error());
+    }
+
+    #[test]
+    fn handled_error_carries_properties() {
+        let handled = serde_json::to_value(handled_error()).unwrap();
+        assert_eq!(handled["type"], "handledError");
+        assert_eq!(handled["properties"]["launch_count"], "3");
+
+        let managed = serde_json::to_value(managed_error()).unwrap();
+        assert!(managed.get("properties").is_none());
+    }
+
+    #[test]
+    fn error_attachment_round_trip() {
+        assert_round_trip(attachment(Base64Variant::Standard));
+        assert_round_trip(attachment(Base64Variant::UrlSafe));
+    }
+
+    #[test]
+    fn attachment_data_uses_the_alphabet_of_the_variant() {
+        let encoded = |data: &[u8], variant| {
+            let data = Base64Data {
+                data: data.to_vec(),
+                variant,
+            };
+            serde_json::to_value(data).unwrap()
+        };
+
+        assert_eq!(
+            encoded(&[0xfb, 0xff, 0xbf], Base64Variant::Standard),
+            "+/+/"
+        );
+        assert_eq!(encoded(&[0xfb, 0xff, 0xbf], Base64Variant::UrlSafe), "-_-_");
+        // Both are padded
+        assert_eq!(encoded(&[0xfb, 0xf0], Base64Variant::Standard), "+/A=");
+        assert_eq!(encoded(&[0xfb, 0xf0], Base64Variant::UrlSafe), "-_A=");
+        assert_eq!(encoded(&[0xfb], Base64Variant::UrlSafe), "-w==");
+    }
+
+    #[test]
+    fn start_session_round_trip() {
+        assert_round_trip(AppCenterLog::StartSession {
+            id: id(4),
+            session_id: id(2),
+            timestamp: timestamp(),
+            device: device(),
+        });
+    }
+
+    #[test]
+    fn event_round_trip() {
+        assert_round_trip(AppCenterLog::Event {
+            id: id(5),
+            name: "export_completed".to_string(),
+            timestamp: timestamp(),
+            session_id: id(2),
+            device: device(),
+            properties: Some(BTreeMap::from([("format".to_string(), "pdf".to_string())])),
+            typed_properties: Some(vec![TypedProperty::Long {
+                name: "pages".to_string(),
+                value: 12,
+            }]),
+        });
+    }
+
+    #[test]
+    fn page_round_trip() {
+        assert_round_trip(AppCenterLog::Page {
+            id: id(6),
+            name: "settings".to_string(),
+            timestamp: timestamp(),
+            session_id: id(2),
+            device: device(),
+        });
+    }
+
+    #[test]
+    fn report_round_trip() {
+        let logs = AppCenterLogs {
+            logs: vec![managed_error(), attachment(Base64Variant::Standard)],
+            base64_variant: Base64Variant::Standard,
+            force_send: false,
+            current_page: None,
+            ids: None,
+        };
+        let json = logs.to_json_bytes().unwrap();
+        let back: AppCenterLogs = serde_json::from_slice(&json).unwrap();
+        assert_eq!(back.to_json_bytes().unwrap(), json);
+        assert_eq!(back.error_id(), id(1));
+    }
+
+    #[test]
+    fn report_must_start_with_an_error() {
+        let empty = r#"{"logs":[]}"#;
+        assert!(serde_json::from_str::<AppCenterLogs>(empty).is_err());
+
+        let attachment = serde_json::to_string(&attachment(Base64Variant::Standard)).unwrap();
+        let attachment_first = format!(r#"{{"logs":[{}]}}"#, attachment);
+        assert!(serde_json::from_str::<AppCenterLogs>(&attachment_first).is_err());
     }
 }
@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+// The buffer of the installed `AppCenterLogger`, attached to every report as `log.txt`
+static LOG_BUFFER: OnceLock<LogBuffer> = OnceLock::new();
+
+///
+/// The amount of log output kept for the reports
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogCapacity {
+    /// Keep the last `n` lines
+    Lines(usize),
+    /// Keep the most recent lines totalling at most `n` bytes
+    Bytes(usize),
+}
+
+// Bounded ring of formatted log lines
+pub(crate) struct LogBuffer {
+    capacity: LogCapacity,
+    lines: Mutex<(VecDeque<String>, usize)>,
+}
+
+impl LogBuffer {
+    // The buffer attached to the reports, the capacity of the first caller wins
+    pub(crate) fn shared(capacity: LogCapacity) -> &'static LogBuffer {
+        LOG_BUFFER.get_or_init(|| LogBuffer {
+            capacity,
+            lines: Mutex::new((VecDeque::new(), 0)),
+        })
+    }
+
+    // Append a line formatted like the others in the buffer
+    pub(crate) fn push_record(
+        &self,
+        level: &dyn std::fmt::Display,
+        target: &str,
+        message: &dyn std::fmt::Display,
+    ) {
+        self.push(format!(
+            "{} {:<5} {}: {}\n",
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            level.to_string(),
+            target,
+            message
+        ));
+    }
+
+    fn push(&self, line: String) {
+        // A panic while logging must not stop the crash report from getting the logs
+        let mut guard = self.lines.lock().unwrap_or_else(PoisonError::into_inner);
+        let (lines, bytes) = &mut *guard;
+
+        *bytes += line.len();
+        lines.push_back(line);
+
+        loop {
+            let over = match self.capacity {
+                LogCapacity::Lines(n) => lines.len() > n,
+                LogCapacity::Bytes(n) => *bytes > n,
+            };
+            if !over {
+                break;
+            }
+            match lines.pop_front() {
+                Some(line) => *bytes -= line.len(),
+                None => break,
+            }
+        }
+    }
+
+    fn contents(&self) -> String {
+        let guard = self.lines.lock().unwrap_or_else(PoisonError::into_inner);
+        guard.0.iter().map(|l| l.as_str()).collect()
+    }
+}
+
+///
+/// A `log::Log` adapter that forwards every record to another logger and keeps the most
+/// recent lines in memory, so they are attached to every report as `log.txt`
+///
+pub struct AppCenterLogger {
+    inner: Box<dyn log::Log>,
+    buffer: &'static LogBuffer,
+}
+
+impl AppCenterLogger {
+    ///
+    /// Install the adapter as the global logger around `inner_logger`. Only the records
+    /// enabled by `inner_logger` are captured. The max level is set to `Trace`, filtering
+    /// is left to `inner_logger`.
+    ///
+    /// ```no_run
+    /// use app_center::{AppCenterLogger, LogCapacity};
+    ///
+    /// struct Stderr;
+    ///
+    /// impl log::Log for Stderr {
+    ///     fn enabled(&self, metadata: &log::Metadata) -> bool {
+    ///         metadata.level() <= log::Level::Info
+    ///     }
+    ///     fn log(&self, record: &log::Record) {
+    ///         eprintln!("{} {}", record.level(), record.args());
+    ///     }
+    ///     fn flush(&self) {}
+    /// }
+    ///
+    /// AppCenterLogger::init(Box::new(Stderr), LogCapacity::Lines(200)).unwrap();
+    /// ```
+    ///
+    pub fn init(
+        inner_logger: Box<dyn log::Log>,
+        capacity: LogCapacity,
+    ) -> Result<(), log::SetLoggerError> {
+        let buffer = LogBuffer::shared(capacity);
+
+        log::set_boxed_logger(Box::new(AppCenterLogger {
+            inner: inner_logger,
+            buffer,
+        }))?;
+        log::set_max_level(log::LevelFilter::Trace);
+        Ok(())
+    }
+}
+
+impl log::Log for AppCenterLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.inner.enabled(record.metadata()) {
+            return;
+        }
+
+        self.buffer
+            .push_record(&record.level(), record.target(), record.args());
+
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+// The captured log lines, if an `AppCenterLogger` is installed
+pub(crate) fn captured_logs() -> Option<String> {
+    LOG_BUFFER.get().map(|b| b.contents())
+}
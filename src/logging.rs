@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+
+///
+/// How much the crate logs about its own operation, independently of the `log` max level
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum LogLevel {
+    /// Nothing is logged
+    Off,
+    /// Only failures are logged, such as a report that failed to send
+    Error,
+    /// Everything is logged
+    #[default]
+    Debug,
+}
+
+/// Receives the messages of the crate instead of the `log` crate, see `AppCenterBuilder::log_sink`
+pub type LogSink = Arc<dyn Fn(log::Level, &str) + Send + Sync>;
+
+/// The target of every message logged by the crate
+pub const LOG_TARGET: &str = "app_center";
+
+// Logging is configured for the whole process, like the `log` crate itself
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Debug as u8);
+static SINK: Mutex<Option<LogSink>> = Mutex::new(None);
+
+pub(crate) fn set_level(level: LogLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub(crate) fn set_sink(sink: Option<LogSink>) {
+    *SINK.lock().unwrap() = sink;
+}
+
+pub(crate) fn log(level: log::Level, args: std::fmt::Arguments) {
+    let enabled = match LEVEL.load(Ordering::Relaxed) {
+        l if l == LogLevel::Off as u8 => false,
+        l if l == LogLevel::Error as u8 => level <= log::Level::Error,
+        _ => true,
+    };
+    if !enabled {
+        return;
+    }
+
+    // Don't hold the lock while the sink runs, it may log itself
+    let sink = { SINK.lock().unwrap().clone() };
+    match sink {
+        Some(sink) => sink(level, &args.to_string()),
+        None => log::log!(target: LOG_TARGET, level, "{}", args),
+    }
+}
+
+// Log through the level and sink configured for the crate
+macro_rules! app_log {
+    ($level:ident, $($arg:tt)+) => {
+        $crate::logging::log(log::Level::$level, format_args!($($arg)+))
+    };
+}
@@ -0,0 +1,812 @@
+use super::Utils;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "encryption")]
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+#[cfg(feature = "encryption")]
+use chacha20poly1305::ChaCha20Poly1305;
+
+const PLAIN_EXT: &str = "json";
+const META_EXT: &str = "meta.json";
+#[cfg(feature = "encryption")]
+const ENCRYPTED_EXT: &str = "enc";
+#[cfg(feature = "encryption")]
+const NONCE_LEN: usize = 12;
+
+// Version of the envelope written around stored reports, files written with a newer version
+// are left alone for a newer version of the crate to send, until they expire
+const FORMAT_VERSION: u32 = 1;
+const RETENTION_DAYS: i64 = 30;
+
+// Delay before the second upload attempt, doubled after every failed attempt
+const BACKOFF_BASE_SECS: i64 = 60;
+const BACKOFF_MAX_SECS: i64 = 24 * 60 * 60;
+
+/// Outcome of an attempt to upload the reports left on disk by previous runs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PendingSummary {
+    /// Reports that were uploaded and removed from disk
+    pub sent: usize,
+    /// Reports that failed to upload and were kept for a later attempt
+    pub failed: usize,
+    /// Reports that could not be read or decrypted and were deleted
+    pub discarded: usize,
+    /// Reports that were not attempted because they are still backing off
+    pub deferred: usize,
+    /// Reports that were deleted after exceeding the maximum number of attempts or the
+    /// retention period
+    pub expired: usize,
+    /// Reports that were deleted because they had already been sent
+    pub duplicates: usize,
+}
+
+/// Outcome of `AppCenter::purge_local_data`
+#[derive(Debug, Default)]
+pub struct PurgeSummary {
+    /// Files that were deleted: stored reports, their sidecars and the settings
+    pub deleted: Vec<PathBuf>,
+    /// Files that could not be deleted
+    pub errors: Vec<(PathBuf, io::Error)>,
+}
+
+impl PurgeSummary {
+    pub(crate) fn record(&mut self, path: PathBuf, result: io::Result<()>) {
+        match result {
+            Ok(()) => self.deleted.push(path),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => self.errors.push((path, err)),
+        }
+    }
+}
+
+/// A crash report waiting on disk to be sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedReport {
+    /// The id of the crash report
+    pub id: uuid::Uuid,
+    /// When the report was written to disk
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// The size of the report file
+    pub size_bytes: usize,
+    /// The number of upload attempts made on previous launches
+    pub attempts: u32,
+}
+
+/// The reason `flush_queued_reports` stopped
+#[derive(Debug)]
+pub enum FlushError {
+    /// Crash reporting is disabled, or sending is compiled out without an `HttpSender`
+    Disabled,
+    /// No crash directory is configured
+    NoCrashDir,
+    /// The crash directory could not be read
+    Io(io::Error),
+    /// A report failed to upload, `flushed` reports were sent before the failure
+    Send { flushed: usize, error: SendError },
+}
+
+impl std::fmt::Display for FlushError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FlushError::Disabled => write!(f, "crash reporting is disabled"),
+            FlushError::NoCrashDir => write!(f, "no crash directory configured"),
+            FlushError::Io(err) => write!(f, "failed to read the crash directory: {}", err),
+            FlushError::Send { flushed, error } => {
+                write!(
+                    f,
+                    "failed to send report after {} flushed: {}",
+                    flushed, error
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for FlushError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FlushError::Disabled | FlushError::NoCrashDir => None,
+            FlushError::Io(err) => Some(err),
+            FlushError::Send { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for FlushError {
+    fn from(err: io::Error) -> Self {
+        FlushError::Io(err)
+    }
+}
+
+///
+/// The reason a report failed to upload. An unsuccessful HTTP status is an error too, the
+/// common ones being 400 for a payload AppCenter could not parse, 401 for an invalid app
+/// secret and 403 for an app that refuses the request, see `http_status`.
+///
+#[derive(Debug)]
+pub struct SendError(Box<dyn std::error::Error + Send + Sync>);
+
+impl SendError {
+    pub(crate) fn from_status(code: u16, body: String) -> Self {
+        SendError(Box::new(HttpStatusError { code, body }))
+    }
+
+    /// The HTTP status AppCenter answered with, `None` when it could not be reached
+    pub fn http_status(&self) -> Option<u16> {
+        self.0.downcast_ref::<HttpStatusError>().map(|e| e.code)
+    }
+
+    /// The body of the unsuccessful response, truncated to 512 bytes
+    pub fn response_body(&self) -> Option<&str> {
+        self.0
+            .downcast_ref::<HttpStatusError>()
+            .map(|e| e.body.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct HttpStatusError {
+    code: u16,
+    body: String,
+}
+
+impl std::fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HTTP {}", self.code)?;
+        if !self.body.is_empty() {
+            write!(f, ": {}", self.body)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+impl std::fmt::Display for SendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for SendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.0)
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for SendError {
+    fn from(err: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        SendError(err)
+    }
+}
+
+#[cfg(feature = "send")]
+impl From<reqwest::Error> for SendError {
+    fn from(err: reqwest::Error) -> Self {
+        SendError(Box::new(err))
+    }
+}
+
+/// The reason `report_error` did not report the error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportError {
+    /// Crash reporting is disabled, compiled out, configured with a placeholder secret or
+    /// off for this build
+    Disabled,
+    /// The report was dropped by the ignore list, the report filter, sampling or the scrubber
+    Discarded,
+}
+
+impl std::fmt::Display for ReportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportError::Disabled => write!(f, "reporting is disabled"),
+            ReportError::Discarded => write!(f, "the report was discarded"),
+        }
+    }
+}
+
+impl std::error::Error for ReportError {}
+
+/// The reason `verify_connection` failed
+#[derive(Debug)]
+pub enum VerifyError {
+    /// Reporting is disabled, compiled out or configured with a placeholder secret
+    Disabled,
+    /// AppCenter rejected the app secret, HTTP 401
+    Unauthorized,
+    /// AppCenter refused the request, HTTP 403
+    Forbidden,
+    /// AppCenter answered with another unsuccessful HTTP status
+    Status(u16),
+    /// AppCenter could not be reached
+    Network(SendError),
+    /// The scrubber panicked and the test report was dropped
+    Discarded,
+}
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyError::Disabled => write!(f, "reporting is disabled"),
+            VerifyError::Unauthorized => write!(f, "the app secret was rejected (HTTP 401)"),
+            VerifyError::Forbidden => write!(f, "the request was refused (HTTP 403)"),
+            VerifyError::Status(status) => write!(f, "unexpected HTTP status {}", status),
+            VerifyError::Network(err) => write!(f, "AppCenter is unreachable: {}", err),
+            VerifyError::Discarded => write!(f, "the test report was discarded"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VerifyError::Network(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Upload attempts of a stored report, kept in a sidecar file next to the report
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReportMeta {
+    pub(crate) attempts: u32,
+    pub(crate) last_attempt: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl ReportMeta {
+    /// Returns true if enough time has passed since the last attempt
+    pub(crate) fn is_due(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        let last_attempt = match self.last_attempt {
+            Some(last_attempt) if self.attempts > 0 => last_attempt,
+            _ => return true,
+        };
+
+        let shift = (self.attempts - 1).min(16);
+        let delay = (BACKOFF_BASE_SECS << shift).min(BACKOFF_MAX_SECS);
+
+        now >= last_attempt + chrono::Duration::seconds(delay)
+    }
+}
+
+pub(crate) enum LoadError {
+    /// The file is encrypted but no key is currently configured, keep it around
+    MissingKey,
+    /// The file was written by a newer version of the crate, keep it around
+    Unsupported,
+    /// The file is unreadable or corrupt, it will never be sent
+    Corrupt,
+    /// The file is older than the retention period, whatever its version
+    Expired,
+}
+
+/// Header stored in front of every report, describing how the report should be sent.
+/// Only `format_version` is guaranteed to be present in all versions.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Envelope {
+    #[serde(default)]
+    pub(crate) format_version: u32,
+    #[serde(default)]
+    pub(crate) crate_version: Option<String>,
+    #[serde(default)]
+    pub(crate) created_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub(crate) endpoint: Option<String>,
+    #[serde(default)]
+    pub(crate) install_id: Option<uuid::Uuid>,
+    // The SHA-256 digest of the app secret, the secret itself is never written to disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) app_secret_sha256: Option<String>,
+    // Written in plain text by earlier versions, still read to route their reports
+    #[serde(default, skip_serializing)]
+    pub(crate) app_secret: Option<String>,
+}
+
+impl Envelope {
+    pub(crate) fn new(endpoint: &str, app_secret: &str, install_id: uuid::Uuid) -> Self {
+        Envelope {
+            format_version: FORMAT_VERSION,
+            crate_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            created_at: Some(chrono::Utc::now()),
+            endpoint: Some(endpoint.to_string()),
+            install_id: Some(install_id),
+            app_secret_sha256: Some(Utils::sha256_hex(app_secret.as_bytes())),
+            app_secret: None,
+        }
+    }
+
+    /// Returns true if the report was saved for the app `app_secret`
+    pub(crate) fn is_for(&self, app_secret: &str) -> bool {
+        match (&self.app_secret_sha256, &self.app_secret) {
+            (Some(digest), _) => *digest == Utils::sha256_hex(app_secret.as_bytes()),
+            (None, Some(legacy)) => legacy == app_secret,
+            (None, None) => false,
+        }
+    }
+
+    fn is_expired(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.created_at
+            .is_some_and(|t| t + chrono::Duration::days(RETENTION_DAYS) < now)
+    }
+}
+
+/// A report read back from disk
+pub(crate) struct StoredReport {
+    pub(crate) envelope: Envelope,
+    pub(crate) payload: Vec<u8>,
+}
+
+impl StoredReport {
+    fn encode(envelope: &Envelope, payload: &[u8]) -> io::Result<Vec<u8>> {
+        let mut data = serde_json::to_vec(envelope).map_err(io::Error::other)?;
+        data.push(b'\n');
+        data.extend_from_slice(payload);
+        Ok(data)
+    }
+
+    /// Parse a stored report, `modified` stands in for the creation time of files that
+    /// don't record it
+    fn decode(
+        data: Vec<u8>,
+        modified: Option<chrono::DateTime<chrono::Utc>>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Self, LoadError> {
+        let (mut envelope, payload) = match data.iter().position(|&b| b == b'\n') {
+            Some(split) => {
+                let envelope: Envelope =
+                    serde_json::from_slice(&data[..split]).map_err(|_| LoadError::Corrupt)?;
+                (envelope, data[split + 1..].to_vec())
+            }
+            // Files written before the envelope was introduced only hold the payload
+            None => (Envelope::default(), data),
+        };
+
+        if envelope.created_at.is_none() {
+            envelope.created_at = modified;
+        }
+        if envelope.is_expired(now) {
+            return Err(LoadError::Expired);
+        }
+        if envelope.format_version > FORMAT_VERSION {
+            return Err(LoadError::Unsupported);
+        }
+
+        Ok(StoredReport { envelope, payload })
+    }
+}
+
+/// A directory of crash reports that could not be sent at the time of the crash
+pub(crate) struct ReportStore {
+    dir: PathBuf,
+    #[cfg(feature = "encryption")]
+    key: Option<[u8; 32]>,
+}
+
+impl ReportStore {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        ReportStore {
+            dir,
+            #[cfg(feature = "encryption")]
+            key: None,
+        }
+    }
+
+    #[cfg(feature = "encryption")]
+    pub(crate) fn with_key(mut self, key: Option<[u8; 32]>) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// Write the serialized report to disk, encrypting it if a key is set
+    pub(crate) fn save(
+        &self,
+        id: &uuid::Uuid,
+        envelope: &Envelope,
+        payload: &[u8],
+    ) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let report = &StoredReport::encode(envelope, payload)?[..];
+
+        #[cfg(feature = "encryption")]
+        {
+            if let Some(key) = &self.key {
+                let cipher = ChaCha20Poly1305::new(key.into());
+                let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+                let sealed = cipher
+                    .encrypt(&nonce, report)
+                    .map_err(|_| io::Error::other("encryption failed"))?;
+
+                let mut data = nonce.to_vec();
+                data.extend_from_slice(&sealed);
+
+                let path = self.dir.join(format!("{}.{}", id, ENCRYPTED_EXT));
+                fs::write(&path, data)?;
+                return Ok(path);
+            }
+        }
+
+        let path = self.dir.join(format!("{}.{}", id, PLAIN_EXT));
+        fs::write(&path, report)?;
+        Ok(path)
+    }
+
+    /// List all the reports currently waiting on disk
+    pub(crate) fn pending(&self) -> io::Result<Vec<PathBuf>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut reports: Vec<_> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| Self::is_report(p))
+            .map(|p| (fs::metadata(&p).and_then(|m| m.modified()).ok(), p))
+            .collect();
+        // Oldest first, so events are sent in the order they were tracked
+        reports.sort();
+        Ok(reports.into_iter().map(|(_, p)| p).collect())
+    }
+
+    /// Describe all the reports currently waiting on disk
+    pub(crate) fn list(&self) -> io::Result<Vec<QueuedReport>> {
+        Ok(self
+            .pending()?
+            .into_iter()
+            .filter_map(|path| {
+                let id = Self::report_id(&path)?;
+                let metadata = fs::metadata(&path).ok()?;
+                let timestamp = metadata.modified().map(Into::into).ok()?;
+
+                Some(QueuedReport {
+                    id,
+                    timestamp,
+                    size_bytes: metadata.len() as usize,
+                    attempts: self.meta(&path).attempts,
+                })
+            })
+            .collect())
+    }
+
+    /// Find the file of the report with the given id
+    pub(crate) fn find(&self, id: &uuid::Uuid) -> io::Result<Option<PathBuf>> {
+        Ok(self
+            .pending()?
+            .into_iter()
+            .find(|p| Self::report_id(p).as_ref() == Some(id)))
+    }
+
+    /// Read a report back, decrypting it if required
+    pub(crate) fn load(&self, path: &Path) -> Result<StoredReport, LoadError> {
+        let modified = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .map(Into::into);
+        StoredReport::decode(self.read(path)?, modified, chrono::Utc::now())
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<u8>, LoadError> {
+        let data = fs::read(path).map_err(|_| LoadError::Corrupt)?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(PLAIN_EXT) => Ok(data),
+            #[cfg(feature = "encryption")]
+            Some(ENCRYPTED_EXT) => {
+                let key = self.key.as_ref().ok_or(LoadError::MissingKey)?;
+                if data.len() < NONCE_LEN {
+                    return Err(LoadError::Corrupt);
+                }
+
+                let (nonce, sealed) = data.split_at(NONCE_LEN);
+                ChaCha20Poly1305::new(key.into())
+                    .decrypt(nonce.into(), sealed)
+                    .map_err(|_| LoadError::Corrupt)
+            }
+            _ => Err(LoadError::MissingKey),
+        }
+    }
+
+    /// Read the attempts sidecar of a report, a missing or corrupt sidecar means no attempts
+    pub(crate) fn meta(&self, path: &Path) -> ReportMeta {
+        fs::read(Self::meta_path(path))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// Bump the attempts counter of a report before trying to send it, so concurrent
+    /// processes observe the attempt as well
+    pub(crate) fn record_attempt(&self, path: &Path, meta: &ReportMeta) {
+        let meta = ReportMeta {
+            attempts: meta.attempts + 1,
+            last_attempt: Some(chrono::Utc::now()),
+        };
+
+        let meta_path = Self::meta_path(path);
+        let written = serde_json::to_vec(&meta)
+            .map_err(io::Error::other)
+            .and_then(|data| write_atomic(&meta_path, &data));
+
+        if let Err(err) = written {
+            app_log!(
+                Warn,
+                "Failed to update crash report attempts {:?}: {:?}",
+                meta_path,
+                err
+            );
+        }
+    }
+
+    pub(crate) fn remove(&self, path: &Path) {
+        if let Err(err) = fs::remove_file(path) {
+            app_log!(Warn, "Failed to remove crash report {:?}: {:?}", path, err);
+        }
+        let _ = fs::remove_file(Self::meta_path(path));
+    }
+
+    /// Delete every file of the crash directory, including the sidecars and the reports
+    /// this version of the crate can't read
+    pub(crate) fn purge(&self, summary: &mut PurgeSummary) -> io::Result<()> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_file() {
+                let removed = fs::remove_file(&path);
+                summary.record(path, removed);
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn report_id(path: &Path) -> Option<uuid::Uuid> {
+        let stem = path.file_stem()?.to_str()?;
+        uuid::Uuid::parse_str(stem).ok()
+    }
+
+    fn meta_path(path: &Path) -> PathBuf {
+        path.with_extension(META_EXT)
+    }
+
+    fn is_report(path: &Path) -> bool {
+        let is_meta = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.ends_with(META_EXT));
+        if is_meta {
+            return false;
+        }
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(PLAIN_EXT) => true,
+            #[cfg(feature = "encryption")]
+            Some(ENCRYPTED_EXT) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Write to a temporary file first and rename it, so the file is never seen half written
+/// by a concurrent process
+pub(crate) fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let tmp_path = path.with_extension(format!("tmp{}", std::process::id()));
+    let written = fs::write(&tmp_path, data).and_then(|_| fs::rename(&tmp_path, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    written
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYLOAD: &[u8] = br#"{"logs":[]}"#;
+
+    // A fresh directory per test, tests run in parallel
+    fn store(name: &str) -> ReportStore {
+        let dir = std::env::temp_dir().join(format!(
+            "app-center-persist-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        ReportStore::new(dir)
+    }
+
+    fn envelope() -> Envelope {
+        Envelope::new(
+            "https://example.com",
+            "00000000-0000-0000-0000-000000000001",
+            uuid::Uuid::new_v4(),
+        )
+    }
+
+    fn assert_round_trip(store: &ReportStore, extension: &str) {
+        let id = uuid::Uuid::new_v4();
+        let envelope = envelope();
+        let path = store.save(&id, &envelope, PAYLOAD).unwrap();
+        assert_eq!(path.extension().and_then(|e| e.to_str()), Some(extension));
+        assert_eq!(store.pending().unwrap(), vec![path.clone()]);
+
+        let report = store.load(&path).unwrap_or_else(|_| panic!("{:?}", path));
+        assert_eq!(report.payload, PAYLOAD);
+        assert_eq!(report.envelope.format_version, FORMAT_VERSION);
+        assert_eq!(report.envelope.endpoint, envelope.endpoint);
+        assert_eq!(
+            report.envelope.app_secret_sha256,
+            envelope.app_secret_sha256
+        );
+        assert!(report
+            .envelope
+            .is_for("00000000-0000-0000-0000-000000000001"));
+        assert_eq!(report.envelope.install_id, envelope.install_id);
+        assert_eq!(report.envelope.created_at, envelope.created_at);
+
+        let _ = fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn plain_report_round_trip() {
+        assert_round_trip(&store("plain"), PLAIN_EXT);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypted_report_round_trip() {
+        assert_round_trip(&store("encrypted").with_key(Some([7; 32])), ENCRYPTED_EXT);
+    }
+
+    #[test]
+    fn app_secret_is_not_written_to_the_file() {
+        let store = store("secret");
+        let path = store
+            .save(&uuid::Uuid::new_v4(), &envelope(), PAYLOAD)
+            .unwrap();
+
+        let contents = String::from_utf8(fs::read(&path).unwrap()).unwrap();
+        assert!(!contents.contains("00000000-0000-0000-0000-000000000001"));
+        assert!(contents.contains(&Utils::sha256_hex(b"00000000-0000-0000-0000-000000000001")));
+
+        let _ = fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn legacy_app_secret_is_still_routed() {
+        let envelope: Envelope = serde_json::from_str(
+            r#"{"formatVersion":1,"appSecret":"00000000-0000-0000-0000-000000000001"}"#,
+        )
+        .unwrap();
+        assert!(envelope.is_for("00000000-0000-0000-0000-000000000001"));
+        assert!(!envelope.is_for("00000000-0000-0000-0000-000000000002"));
+        assert!(!serde_json::to_string(&envelope)
+            .unwrap()
+            .contains("appSecret"));
+    }
+
+    #[test]
+    fn pre_envelope_report_is_loaded() {
+        let store = store("pre-envelope");
+        fs::create_dir_all(&store.dir).unwrap();
+        let path = store
+            .dir
+            .join(format!("{}.{}", uuid::Uuid::new_v4(), PLAIN_EXT));
+        fs::write(&path, PAYLOAD).unwrap();
+
+        let report = store.load(&path).unwrap_or_else(|_| panic!("{:?}", path));
+        assert_eq!(report.payload, PAYLOAD);
+        assert_eq!(report.envelope.format_version, 0);
+        assert!(report.envelope.endpoint.is_none());
+        assert!(report.envelope.app_secret_sha256.is_none());
+        assert!(!report
+            .envelope
+            .is_for("00000000-0000-0000-0000-000000000001"));
+
+        let _ = fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_a_day() {
+        let last_attempt = chrono::Utc::now();
+        let delays: Vec<i64> = (1..=20)
+            .map(|attempts| {
+                let meta = ReportMeta {
+                    attempts,
+                    last_attempt: Some(last_attempt),
+                };
+                let delay = (1..=BACKOFF_MAX_SECS)
+                    .find(|&secs| meta.is_due(last_attempt + chrono::Duration::seconds(secs)))
+                    .unwrap();
+                assert!(!meta.is_due(last_attempt + chrono::Duration::seconds(delay - 1)));
+                delay
+            })
+            .collect();
+
+        assert_eq!(
+            delays[..11],
+            [60, 120, 240, 480, 960, 1920, 3840, 7680, 15360, 30720, 61440]
+        );
+        assert!(delays[11..].iter().all(|&delay| delay == 24 * 60 * 60));
+    }
+
+    #[test]
+    fn reports_without_attempts_are_due() {
+        let now = chrono::Utc::now();
+        assert!(ReportMeta::default().is_due(now));
+        let meta = ReportMeta {
+            attempts: 0,
+            last_attempt: Some(now),
+        };
+        assert!(meta.is_due(now));
+    }
+
+    #[test]
+    fn attempts_are_recorded_in_the_sidecar() {
+        let store = store("attempts");
+        let path = store
+            .save(&uuid::Uuid::new_v4(), &envelope(), PAYLOAD)
+            .unwrap();
+        assert_eq!(store.meta(&path).attempts, 0);
+
+        store.record_attempt(&path, &store.meta(&path));
+        store.record_attempt(&path, &store.meta(&path));
+        let meta = store.meta(&path);
+        assert_eq!(meta.attempts, 2);
+        assert!(meta.last_attempt.is_some());
+
+        // Only the report and its sidecar, no temporary file is left behind
+        let mut files: Vec<_> = fs::read_dir(&store.dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec![path.clone(), ReportStore::meta_path(&path)]);
+        assert_eq!(store.pending().unwrap(), vec![path]);
+
+        let _ = fs::remove_dir_all(&store.dir);
+    }
+
+    fn encoded(format_version: u32, created_at: Option<chrono::DateTime<chrono::Utc>>) -> Vec<u8> {
+        let envelope = Envelope {
+            format_version,
+            created_at,
+            ..envelope()
+        };
+        StoredReport::encode(&envelope, PAYLOAD).unwrap()
+    }
+
+    #[test]
+    fn reports_expire_whatever_their_version() {
+        let now = chrono::Utc::now();
+        let fresh = now - chrono::Duration::days(RETENTION_DAYS - 1);
+        let stale = now - chrono::Duration::days(RETENTION_DAYS + 1);
+
+        for version in [FORMAT_VERSION, FORMAT_VERSION + 1] {
+            let decoded = StoredReport::decode(encoded(version, Some(stale)), None, now);
+            assert!(matches!(decoded, Err(LoadError::Expired)), "{}", version);
+        }
+
+        let decoded = StoredReport::decode(encoded(FORMAT_VERSION, Some(fresh)), None, now);
+        assert!(decoded.is_ok());
+        let decoded = StoredReport::decode(encoded(FORMAT_VERSION + 1, Some(fresh)), None, now);
+        assert!(matches!(decoded, Err(LoadError::Unsupported)));
+
+        // Without a creation time, the modification time of the file is used
+        let decoded = StoredReport::decode(PAYLOAD.to_vec(), Some(stale), now);
+        assert!(matches!(decoded, Err(LoadError::Expired)));
+        let decoded = StoredReport::decode(encoded(FORMAT_VERSION, None), Some(stale), now);
+        assert!(matches!(decoded, Err(LoadError::Expired)));
+        let decoded = StoredReport::decode(PAYLOAD.to_vec(), Some(fresh), now);
+        assert!(decoded.is_ok());
+    }
+}
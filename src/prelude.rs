@@ -0,0 +1,5 @@
+//! The types needed by most applications, for a single glob import
+
+pub use crate::{
+    AppCenter, AppCenterBuilder, AppCenterLogs, Breadcrumb, BreadcrumbLevel, ConfigError,
+};
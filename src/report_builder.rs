@@ -0,0 +1,169 @@
+use super::ExceptionFrame;
+use super::{
+    validate_file_name, AppCenter, AppCenterException, AppCenterLogs, AttachmentError, ReportError,
+};
+use std::time::Instant;
+
+///
+/// Builds a report from parts instead of a panic or a `std::error::Error`, for example for an
+/// error a C library reported through `errno`. Created with `AppCenter::report_builder`. The
+/// device, the ids, the timestamps, the process and the properties are filled in like for
+/// the other reports.
+///
+/// ```no_run
+/// let app_center = app_center::start!("00000000-0000-0000-0000-000000000000");
+/// let errno = std::io::Error::last_os_error();
+///
+/// let _ = app_center
+///     .report_builder()
+///     .exception_type("libfoo::Error")
+///     .message(&format!("foo_open failed: {}", errno))
+///     .add_text_attachment("mode=rw", Some("foo.txt"))?
+///     .send();
+/// # Ok::<(), app_center::AttachmentError>(())
+/// ```
+///
+pub struct ReportBuilder<'a> {
+    app_center: &'a AppCenter,
+    exception_type: String,
+    message: String,
+    frames: Option<Vec<ExceptionFrame>>,
+    fatal: bool,
+    user_id: Option<Option<String>>,
+    attachments: Vec<(Vec<u8>, Option<&'a str>, &'static str)>,
+}
+
+impl<'a> ReportBuilder<'a> {
+    pub(crate) fn new(app_center: &'a AppCenter) -> Self {
+        ReportBuilder {
+            app_center,
+            exception_type: "error".to_string(),
+            message: String::new(),
+            frames: None,
+            fatal: false,
+            user_id: None,
+            attachments: Vec::new(),
+        }
+    }
+
+    ///
+    /// The type of the exception, `"error"` by default
+    ///
+    pub fn exception_type(mut self, exception_type: &str) -> Self {
+        self.exception_type = exception_type.to_string();
+        self
+    }
+
+    ///
+    /// The message of the exception, empty by default
+    ///
+    pub fn message(mut self, message: &str) -> Self {
+        self.message = message.to_string();
+        self
+    }
+
+    ///
+    /// The frames of the backtrace, see `ExceptionFrame::new`. By default the backtrace of the
+    /// thread calling `build` or `send` is captured.
+    ///
+    pub fn frames(mut self, frames: Vec<ExceptionFrame>) -> Self {
+        self.frames = Some(frames);
+        self
+    }
+
+    ///
+    /// Report a crash, `managedError` with `fatal` set, instead of a `handledError`
+    ///
+    pub fn fatal(mut self, fatal: bool) -> Self {
+        self.fatal = fatal;
+        self
+    }
+
+    ///
+    /// Associate the report with this user ID instead of the one set with
+    /// `AppCenter::set_user_id`, the `UserIdMode` still applies
+    ///
+    pub fn user_id(mut self, id: Option<String>) -> Self {
+        self.user_id = Some(id);
+        self
+    }
+
+    ///
+    /// Attach text to the report, with the file name rules of
+    /// `AppCenterLogs::add_binary_attachement`
+    ///
+    pub fn add_text_attachment(
+        self,
+        data: &str,
+        file_name: Option<&'a str>,
+    ) -> Result<Self, AttachmentError> {
+        self.add_attachment(data.as_bytes().to_vec(), file_name, "text/plain")
+    }
+
+    ///
+    /// Attach binary data to the report, see `add_text_attachment`
+    ///
+    pub fn add_binary_attachment(
+        self,
+        data: Vec<u8>,
+        file_name: Option<&'a str>,
+    ) -> Result<Self, AttachmentError> {
+        self.add_attachment(data, file_name, "application/octet_stream")
+    }
+
+    fn add_attachment(
+        mut self,
+        data: Vec<u8>,
+        file_name: Option<&'a str>,
+        content_type: &'static str,
+    ) -> Result<Self, AttachmentError> {
+        validate_file_name(file_name)?;
+        self.attachments.push((data, file_name, content_type));
+        Ok(self)
+    }
+
+    ///
+    /// Build the report as it would be sent, with the report callbacks, the grouping key
+    /// and the scrubber applied, like `AppCenter::build_report`
+    ///
+    pub fn build(self) -> Result<AppCenterLogs<'a>, ReportError> {
+        let inner = &self.app_center.inner;
+        let payload = self.payload();
+        inner.build_report(payload)
+    }
+
+    ///
+    /// Send the report from the calling thread, through the callbacks, filters and sampling
+    /// like `AppCenter::report_error`, storing it when the upload fails
+    ///
+    pub fn send(self) -> Result<uuid::Uuid, ReportError> {
+        let inner = &self.app_center.inner;
+        if !inner.is_crashes_enabled() {
+            return Err(ReportError::Disabled);
+        }
+        let payload = self.payload();
+        inner.report(payload, Instant::now())
+    }
+
+    fn payload(self) -> AppCenterLogs<'a> {
+        let inner = &self.app_center.inner;
+
+        let exception = AppCenterException::from_parts(
+            self.exception_type,
+            self.message,
+            self.frames,
+            &inner.frame_options(),
+        );
+        let mut payload = inner.new_payload(exception, self.fatal);
+
+        if let Some(id) = self.user_id {
+            let id = id.map(|id| inner.apply_user_id_mode(&id));
+            payload.set_user_id(id);
+        }
+        for (data, file_name, content_type) in self.attachments {
+            payload.add_attachement_inner(data, file_name, content_type);
+        }
+
+        payload
+    }
+}
@@ -0,0 +1,74 @@
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::time::Duration;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+thread_local! {
+    static IS_REPORTER: Cell<bool> = const { Cell::new(false) };
+}
+
+// The thread crash reports are built and sent from, started with the panic hook so the hook
+// only has to hand the report over. It exits once the instance is dropped.
+pub(crate) struct Reporter {
+    jobs: mpsc::Sender<Job>,
+}
+
+// A report handed to the reporting thread
+pub(crate) struct PendingReport {
+    done: mpsc::Receiver<()>,
+}
+
+impl PendingReport {
+    // Returns early when the job panicked, and after `timeout` when it is stuck, for example
+    // on a lock held by the panicking thread
+    pub(crate) fn wait(self, timeout: Duration) {
+        if let Err(mpsc::RecvTimeoutError::Timeout) = self.done.recv_timeout(timeout) {
+            app_log!(Error, "Crash report not finished in time, giving up on it");
+        }
+    }
+}
+
+impl Reporter {
+    pub(crate) fn start() -> std::io::Result<Self> {
+        let (jobs, queue) = mpsc::channel::<Job>();
+
+        std::thread::Builder::new()
+            .name("app-center-reporter".to_string())
+            .spawn(move || {
+                IS_REPORTER.with(|is_reporter| is_reporter.set(true));
+                for job in queue {
+                    // The report is dropped, the thread keeps serving the next ones
+                    if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+                        app_log!(Error, "Crash report dropped, building it panicked");
+                    }
+                }
+            })?;
+
+        Ok(Reporter { jobs })
+    }
+
+    // Run `job` on the reporting thread. It runs right away instead when the thread is gone,
+    // and `None` is returned then. A panic on the reporting thread comes from a report that
+    // failed to build, reporting it could fail the same way, so the job is dropped.
+    pub(crate) fn submit(&self, job: impl FnOnce() + Send + 'static) -> Option<PendingReport> {
+        if IS_REPORTER.with(Cell::get) {
+            return None;
+        }
+
+        let (done, pending) = mpsc::channel();
+        let job: Job = Box::new(move || {
+            job();
+            let _ = done.send(());
+        });
+
+        match self.jobs.send(job) {
+            Ok(()) => Some(PendingReport { done: pending }),
+            Err(mpsc::SendError(job)) => {
+                job();
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_SCOPE_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    // The scopes pushed on this thread, innermost last
+    static SCOPES: RefCell<Vec<(u64, BTreeMap<String, String>)>> = const { RefCell::new(Vec::new()) };
+}
+
+///
+/// Tags that apply to the reports of the current thread while the guard is alive,
+/// created with `AppCenter::push_scope`. Tags of inner scopes take precedence.
+///
+pub struct ContextGuard {
+    id: u64,
+    // The scope belongs to the thread that pushed it
+    _thread: PhantomData<*const ()>,
+}
+
+impl ContextGuard {
+    pub(crate) fn push() -> Self {
+        let id = NEXT_SCOPE_ID.fetch_add(1, Ordering::Relaxed);
+        SCOPES.with(|scopes| scopes.borrow_mut().push((id, BTreeMap::new())));
+        ContextGuard {
+            id,
+            _thread: PhantomData,
+        }
+    }
+
+    ///
+    /// Set a tag for the lifetime of the scope
+    ///
+    pub fn set_tag<K: Into<String>, V: Into<String>>(&self, key: K, value: V) {
+        SCOPES.with(|scopes| {
+            if let Some((_, tags)) = scopes
+                .borrow_mut()
+                .iter_mut()
+                .find(|(id, _)| *id == self.id)
+            {
+                tags.insert(key.into(), value.into());
+            }
+        });
+    }
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        // Guards are not necessarily dropped in the reverse order they were pushed
+        let _ = SCOPES.try_with(|scopes| scopes.borrow_mut().retain(|(id, _)| *id != self.id));
+    }
+}
+
+// The tags of the scopes of the current thread, merged from the outermost to the innermost
+pub(crate) fn scope_tags() -> BTreeMap<String, String> {
+    SCOPES
+        .try_with(|scopes| match scopes.try_borrow() {
+            Ok(scopes) => scopes.iter().flat_map(|(_, tags)| tags.clone()).collect(),
+            // The panic happened while the scopes were being modified
+            Err(_) => BTreeMap::new(),
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn inner_scope_takes_precedence_until_dropped() {
+        let outer = ContextGuard::push();
+        outer.set_tag("screen", "editor");
+        outer.set_tag("document", "a.txt");
+
+        let inner = ContextGuard::push();
+        inner.set_tag("document", "b.txt");
+        assert_eq!(
+            scope_tags(),
+            tags(&[("document", "b.txt"), ("screen", "editor")])
+        );
+
+        drop(inner);
+        assert_eq!(
+            scope_tags(),
+            tags(&[("document", "a.txt"), ("screen", "editor")])
+        );
+        drop(outer);
+        assert!(scope_tags().is_empty());
+    }
+
+    #[test]
+    fn outer_scope_dropped_first_leaves_the_inner_one() {
+        let outer = ContextGuard::push();
+        outer.set_tag("screen", "editor");
+        let inner = ContextGuard::push();
+        inner.set_tag("document", "b.txt");
+
+        drop(outer);
+        assert_eq!(scope_tags(), tags(&[("document", "b.txt")]));
+
+        // Tags set after the other guard is gone still go to the right scope
+        inner.set_tag("screen", "export");
+        assert_eq!(
+            scope_tags(),
+            tags(&[("document", "b.txt"), ("screen", "export")])
+        );
+        drop(inner);
+        assert!(scope_tags().is_empty());
+    }
+
+    #[test]
+    fn scopes_are_isolated_between_threads() {
+        let scope = ContextGuard::push();
+        scope.set_tag("thread", "main");
+
+        std::thread::spawn(|| {
+            assert!(scope_tags().is_empty());
+            let scope = ContextGuard::push();
+            scope.set_tag("thread", "worker");
+            assert_eq!(scope_tags(), tags(&[("thread", "worker")]));
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(scope_tags(), tags(&[("thread", "main")]));
+    }
+}
@@ -0,0 +1,328 @@
+use super::{AppCenterLog, TypedProperty};
+
+// Keys whose values are redacted by `ScrubTarget::scrub_secrets`, matched case-insensitively
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "api-key",
+    "access_key",
+];
+const REDACTED: &str = "<redacted>";
+
+///
+/// The field of an outgoing log a `ScrubTarget` refers to
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrubField<'s> {
+    /// The type of an exception, `"panic"` for panics
+    ExceptionType,
+    /// The message of an exception
+    ExceptionMessage,
+    /// The file name of a frame of the backtrace
+    FrameFileName,
+    /// The user ID of an error
+    UserId,
+    /// The value of an event property, with its key
+    Property(&'s str),
+    /// The content of an attachment, with its file name
+    Attachment(Option<&'s str>),
+}
+
+enum ScrubValue<'s> {
+    Text(&'s mut String),
+    Bytes(&'s mut Vec<u8>),
+}
+
+///
+/// A mutable view of a field of an outgoing log, handed to the scrubber installed with
+/// `AppCenter::set_scrubber`. Text attachments that are valid UTF-8 are presented as text,
+/// other attachments as bytes.
+///
+/// ```no_run
+/// let app_center = app_center::start!("00000000-0000-0000-0000-000000000000");
+/// app_center.set_scrubber(|target| {
+///     target.scrub_home_dir();
+///     target.scrub_secrets();
+/// });
+/// ```
+///
+pub struct ScrubTarget<'s> {
+    field: ScrubField<'s>,
+    value: ScrubValue<'s>,
+}
+
+impl<'s> ScrubTarget<'s> {
+    pub(crate) fn text(field: ScrubField<'s>, text: &'s mut String) -> Self {
+        ScrubTarget {
+            field,
+            value: ScrubValue::Text(text),
+        }
+    }
+
+    /// The field this is a view of
+    pub fn field(&self) -> ScrubField<'s> {
+        self.field
+    }
+
+    /// The text of the field, `None` for binary attachments
+    pub fn text_mut(&mut self) -> Option<&mut String> {
+        match &mut self.value {
+            ScrubValue::Text(text) => Some(text),
+            ScrubValue::Bytes(_) => None,
+        }
+    }
+
+    /// The content of a binary attachment, `None` for text
+    pub fn bytes_mut(&mut self) -> Option<&mut Vec<u8>> {
+        match &mut self.value {
+            ScrubValue::Bytes(bytes) => Some(bytes),
+            ScrubValue::Text(_) => None,
+        }
+    }
+
+    ///
+    /// Replace the home directory of the current user with `~`, and the user name in other
+    /// `/home/<user>`, `/Users/<user>` and `C:\Users\<user>` paths with `<user>`
+    ///
+    pub fn scrub_home_dir(&mut self) {
+        if let Some(text) = self.text_mut() {
+            *text = scrub_home_dir(text);
+        }
+    }
+
+    ///
+    /// Replace email addresses with `<email>`, and bearer tokens, JSON web tokens and the values
+    /// of keys such as `password=` or `"token":` with `<redacted>`
+    ///
+    pub fn scrub_secrets(&mut self) {
+        if let Some(text) = self.text_mut() {
+            *text = scrub_secrets(text);
+        }
+    }
+}
+
+// Run the scrubber over every field of the logs that can carry personal data
+pub(crate) fn scrub_logs(logs: &mut [AppCenterLog], scrubber: &dyn Fn(&mut ScrubTarget)) {
+    for log in logs {
+        match log {
+            AppCenterLog::ManagedError {
+                user_id, exception, ..
+            } => {
+                exception.scrub(scrubber);
+                if let Some(user_id) = user_id {
+                    scrubber(&mut ScrubTarget::text(ScrubField::UserId, user_id));
+                }
+            }
+            AppCenterLog::HandledError {
+                user_id,
+                exception,
+                properties,
+                ..
+            } => {
+                exception.scrub(scrubber);
+                if let Some(user_id) = user_id {
+                    scrubber(&mut ScrubTarget::text(ScrubField::UserId, user_id));
+                }
+                for (key, value) in properties.iter_mut() {
+                    scrubber(&mut ScrubTarget::text(ScrubField::Property(key), value));
+                }
+            }
+            AppCenterLog::ErrorAttachment {
+                content_type,
+                data,
+                file_name,
+                ..
+            } => {
+                let field = ScrubField::Attachment(file_name.as_deref());
+                let is_text =
+                    content_type.starts_with("text/") || content_type == "application/json";
+
+                match String::from_utf8(std::mem::take(&mut data.data)) {
+                    Ok(mut text) if is_text => {
+                        scrubber(&mut ScrubTarget::text(field, &mut text));
+                        data.data = text.into_bytes();
+                    }
+                    Ok(text) => {
+                        data.data = text.into_bytes();
+                        scrub_bytes(scrubber, field, &mut data.data);
+                    }
+                    Err(err) => {
+                        data.data = err.into_bytes();
+                        scrub_bytes(scrubber, field, &mut data.data);
+                    }
+                }
+            }
+            AppCenterLog::Event {
+                properties,
+                typed_properties,
+                ..
+            } => {
+                for (key, value) in properties.iter_mut().flatten() {
+                    scrubber(&mut ScrubTarget::text(ScrubField::Property(key), value));
+                }
+                for property in typed_properties.iter_mut().flatten() {
+                    if let TypedProperty::String { name, value } = property {
+                        scrubber(&mut ScrubTarget::text(ScrubField::Property(name), value));
+                    }
+                }
+            }
+            AppCenterLog::StartSession { .. } | AppCenterLog::Page { .. } => {}
+        }
+    }
+}
+
+fn scrub_bytes(scrubber: &dyn Fn(&mut ScrubTarget), field: ScrubField, data: &mut Vec<u8>) {
+    scrubber(&mut ScrubTarget {
+        field,
+        value: ScrubValue::Bytes(data),
+    });
+}
+
+fn scrub_home_dir(text: &str) -> String {
+    let mut text = text.to_string();
+
+    for var in ["HOME", "USERPROFILE"] {
+        if let Some(home) = std::env::var_os(var) {
+            let home = home.to_string_lossy();
+            let home = home.trim_end_matches(['/', '\\']);
+            if home.len() > 1 && text.contains(home) {
+                text = text.replace(home, "~");
+            }
+        }
+    }
+
+    for prefix in ["/home/", "/Users/", "\\Users\\"] {
+        text = replace_after(&text, prefix, "<user>");
+    }
+    text
+}
+
+// Replace the path component following every occurrence of `prefix`
+fn replace_after(text: &str, prefix: &str, replacement: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(idx) = rest.find(prefix) {
+        let (head, tail) = rest.split_at(idx + prefix.len());
+        out.push_str(head);
+        let end = tail
+            .find(|c: char| matches!(c, '/' | '\\' | '\'' | '"') || c.is_whitespace())
+            .unwrap_or(tail.len());
+        if end > 0 {
+            out.push_str(replacement);
+        }
+        rest = &tail[end..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn scrub_secrets(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let lower = text.to_ascii_lowercase();
+    let mut ranges = Vec::new();
+
+    for key in SECRET_KEYS {
+        for (idx, _) in lower.match_indices(key) {
+            if let Some(range) = assigned_value(bytes, idx + key.len()) {
+                ranges.push((range, REDACTED));
+            }
+        }
+    }
+
+    for (idx, _) in lower.match_indices("bearer ") {
+        let start = idx + "bearer ".len();
+        let end = value_end(bytes, start);
+        if end > start {
+            ranges.push(((start, end), REDACTED));
+        }
+    }
+
+    for (idx, _) in text.match_indices("eyJ") {
+        let end = (idx..bytes.len())
+            .find(|&i| {
+                !(bytes[i].is_ascii_alphanumeric() || matches!(bytes[i], b'-' | b'_' | b'.'))
+            })
+            .unwrap_or(bytes.len());
+        let dots = bytes[idx..end].iter().filter(|&&b| b == b'.').count();
+        if dots >= 2 {
+            ranges.push(((idx, end), REDACTED));
+        }
+    }
+
+    for (at, _) in text.match_indices('@') {
+        if let Some(range) = email_at(bytes, at) {
+            ranges.push((range, "<email>"));
+        }
+    }
+
+    replace_ranges(text, ranges)
+}
+
+// The range of the value following a key, as in `key=value`, `key: value` or `"key": "value"`
+fn assigned_value(bytes: &[u8], mut i: usize) -> Option<(usize, usize)> {
+    let skip = |i: &mut usize, set: &[u8]| {
+        while *i < bytes.len() && set.contains(&bytes[*i]) {
+            *i += 1;
+        }
+    };
+
+    skip(&mut i, b"\"' ");
+    if i >= bytes.len() || !matches!(bytes[i], b'=' | b':') {
+        return None;
+    }
+    i += 1;
+    skip(&mut i, b"\"' ");
+
+    let end = value_end(bytes, i);
+    (end > i).then_some((i, end))
+}
+
+fn value_end(bytes: &[u8], start: usize) -> usize {
+    (start..bytes.len())
+        .find(|&i| bytes[i].is_ascii_whitespace() || b"&,;\"')}".contains(&bytes[i]))
+        .unwrap_or(bytes.len())
+}
+
+fn email_at(bytes: &[u8], at: usize) -> Option<(usize, usize)> {
+    let is_local = |b: u8| b.is_ascii_alphanumeric() || b"._%+-".contains(&b);
+    let is_domain = |b: u8| b.is_ascii_alphanumeric() || b".-".contains(&b);
+
+    let start = (0..at).rev().take_while(|&i| is_local(bytes[i])).last()?;
+    let mut end = (at + 1..bytes.len())
+        .find(|&i| !is_domain(bytes[i]))
+        .unwrap_or(bytes.len());
+    while end > at + 1 && bytes[end - 1] == b'.' {
+        end -= 1;
+    }
+
+    let domain = &bytes[at + 1..end];
+    let has_dot = domain.iter().skip(1).any(|&b| b == b'.');
+    has_dot.then_some((start, end))
+}
+
+// Replace the byte ranges, which are on character boundaries, skipping overlapping ones
+fn replace_ranges(text: &str, mut ranges: Vec<((usize, usize), &str)>) -> String {
+    if ranges.is_empty() {
+        return text.to_string();
+    }
+    ranges.sort_by_key(|&((start, end), _)| (start, std::cmp::Reverse(end)));
+
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    for ((start, end), replacement) in ranges {
+        if start < pos {
+            continue;
+        }
+        out.push_str(&text[pos..start]);
+        out.push_str(replacement);
+        pos = end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
@@ -0,0 +1,169 @@
+use super::builder::ConfigError;
+use std::fmt;
+use std::str::FromStr;
+
+// The key selecting the secret of the current platform in a combined secret
+#[cfg(target_os = "windows")]
+const PLATFORM_KEY: &str = "windowsdesktop";
+#[cfg(target_os = "macos")]
+const PLATFORM_KEY: &str = "macos";
+#[cfg(target_os = "linux")]
+const PLATFORM_KEY: &str = "linux";
+
+///
+/// An app secret as issued by AppCenter, a UUID such as `0a1b2c3d-4e5f-6071-8293-a4b5c6d7e8f9`.
+/// Parse secrets loaded from configuration files with `str::parse` to validate them
+/// before starting the crash reporter. The `Debug` output only shows the first 8 characters.
+///
+/// Like the official SDKs, a single string can hold the secrets of several platforms, such as
+/// `"windowsdesktop={guid};macos={guid};linux={guid}"`. The entry for the current platform is
+/// selected, the keys are `windowsdesktop`, `macos` and `linux` and are case insensitive.
+///
+#[derive(Clone, PartialEq, Eq)]
+pub struct AppSecret(String);
+
+impl AppSecret {
+    /// The secret as provided
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The secret with all but the first 8 characters replaced with `*`, safe to log
+    pub fn masked(&self) -> String {
+        mask(&self.0)
+    }
+}
+
+impl FromStr for AppSecret {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let secret = for_platform(s)?;
+        if secret.is_empty() {
+            return Err(ConfigError::EmptyAppSecret);
+        }
+
+        if uuid::Uuid::parse_str(secret).is_err() {
+            return Err(ConfigError::InvalidAppSecret(secret.to_string()));
+        }
+
+        Ok(AppSecret(secret.to_string()))
+    }
+}
+
+impl fmt::Debug for AppSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AppSecret").field(&self.masked()).finish()
+    }
+}
+
+impl From<AppSecret> for String {
+    fn from(secret: AppSecret) -> String {
+        secret.0
+    }
+}
+
+pub(crate) fn mask(secret: &str) -> String {
+    secret
+        .chars()
+        .enumerate()
+        .map(|(i, c)| if i < 8 { c } else { '*' })
+        .collect()
+}
+
+// Select the entry of the current platform from a combined secret, a plain secret is
+// returned as is
+pub(crate) fn for_platform(secret: &str) -> Result<&str, ConfigError> {
+    if !secret.contains('=') {
+        return Ok(secret.trim());
+    }
+
+    secret
+        .split(';')
+        .filter_map(|entry| entry.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(PLATFORM_KEY))
+        .map(|(_, value)| value.trim())
+        .ok_or_else(|| ConfigError::MissingPlatformSecret(PLATFORM_KEY.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "0a1b2c3d-4e5f-6071-8293-a4b5c6d7e8f9";
+    const OTHER: &str = "9f8e7d6c-5b4a-3928-1706-f5e4d3c2b1a0";
+
+    #[test]
+    fn bare_uuid_is_returned_as_is() {
+        assert_eq!(for_platform(SECRET), Ok(SECRET));
+        assert_eq!(for_platform(&format!("  {} ", SECRET)), Ok(SECRET));
+    }
+
+    #[test]
+    fn combined_secret_selects_the_platform_entry() {
+        let combined = format!("ios={};{}={}", OTHER, PLATFORM_KEY, SECRET);
+        assert_eq!(for_platform(&combined), Ok(SECRET));
+
+        // Keys are case insensitive and whitespace around entries is ignored
+        let combined = format!(
+            "ios={}; {} = {} ",
+            OTHER,
+            PLATFORM_KEY.to_ascii_uppercase(),
+            SECRET
+        );
+        assert_eq!(for_platform(&combined), Ok(SECRET));
+    }
+
+    #[test]
+    fn combined_secret_without_the_platform_is_rejected() {
+        let combined = format!("ios={};android={}", OTHER, SECRET);
+        assert_eq!(
+            for_platform(&combined),
+            Err(ConfigError::MissingPlatformSecret(PLATFORM_KEY.to_string()))
+        );
+    }
+
+    #[test]
+    fn malformed_segments_are_skipped() {
+        let combined = format!(";{};ios;={};{}={};", OTHER, OTHER, PLATFORM_KEY, SECRET);
+        assert_eq!(for_platform(&combined), Ok(SECRET));
+
+        // Without any `=` the whole string is taken as a bare secret, and fails to parse
+        let combined = format!("{};{}", PLATFORM_KEY, OTHER);
+        assert_eq!(for_platform(&combined), Ok(combined.as_str()));
+        assert!(combined.parse::<AppSecret>().is_err());
+
+        let combined = format!("ios={};{}", OTHER, PLATFORM_KEY);
+        assert_eq!(
+            for_platform(&combined),
+            Err(ConfigError::MissingPlatformSecret(PLATFORM_KEY.to_string()))
+        );
+
+        let combined = format!("ios={};{}=", OTHER, PLATFORM_KEY);
+        assert_eq!(for_platform(&combined), Ok(""));
+        assert_eq!(
+            combined.parse::<AppSecret>(),
+            Err(ConfigError::EmptyAppSecret)
+        );
+    }
+
+    #[test]
+    fn malformed_secret_is_rejected_and_masked() {
+        let error = "0a1b2c3d-4e5f-6071-8293-a4b5c6d7e8f".parse::<AppSecret>();
+        assert_eq!(
+            error,
+            Err(ConfigError::InvalidAppSecret(
+                "0a1b2c3d-4e5f-6071-8293-a4b5c6d7e8f".to_string()
+            ))
+        );
+
+        let message = error.unwrap_err().to_string();
+        assert!(!message.contains("4e5f"), "{}", message);
+        assert!(message.contains(&mask("0a1b2c3d-4e5f-6071-8293-a4b5c6d7e8f")));
+
+        let built = crate::AppCenter::builder("0a1b2c3d-4e5f-6071-8293-a4b5c6d7e8f")
+            .app_version("1.0.0")
+            .build();
+        assert!(matches!(built, Err(ConfigError::InvalidAppSecret(_))));
+    }
+}
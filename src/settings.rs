@@ -0,0 +1,106 @@
+use super::persist::{write_atomic, PurgeSummary};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+
+const SETTINGS_FILE: &str = "settings.json";
+// Ids of the most recently sent stored reports that are remembered
+const MAX_SENT_REPORT_IDS: usize = 200;
+
+/// State that outlives the process, stored in the application data directory
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Settings {
+    #[serde(default)]
+    pub(crate) install_id: Option<uuid::Uuid>,
+    #[serde(default)]
+    pub(crate) launch_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) user_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) crashes_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) analytics_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) sent_report_ids: Vec<uuid::Uuid>,
+}
+
+impl Settings {
+    pub(crate) fn record_sent_report(&mut self, id: uuid::Uuid) {
+        self.sent_report_ids.push(id);
+        let excess = self
+            .sent_report_ids
+            .len()
+            .saturating_sub(MAX_SENT_REPORT_IDS);
+        self.sent_report_ids.drain(..excess);
+    }
+}
+
+pub(crate) struct SettingsStore {
+    path: Option<PathBuf>,
+    // Serializes the updates made by the threads of this process
+    update_lock: Mutex<()>,
+}
+
+impl SettingsStore {
+    // Nothing is stored without the `enabled` feature
+    pub(crate) fn new(dir: Option<PathBuf>) -> Self {
+        SettingsStore {
+            path: dir
+                .filter(|_| cfg!(feature = "enabled"))
+                .map(|d| d.join(SETTINGS_FILE)),
+            update_lock: Mutex::new(()),
+        }
+    }
+
+    /// Load the settings, missing or corrupt settings are treated as empty
+    pub(crate) fn load(&self) -> Settings {
+        self.path
+            .as_ref()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self, settings: &Settings) -> io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let data = serde_json::to_vec(settings).map_err(io::Error::other)?;
+        write_atomic(path, &data)
+    }
+
+    /// Delete the stored settings, including the install id
+    pub(crate) fn purge(&self, summary: &mut PurgeSummary) {
+        let _guard = self
+            .update_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(path) = &self.path {
+            summary.record(path.clone(), fs::remove_file(path));
+        }
+    }
+
+    /// Apply a change to the stored settings
+    pub(crate) fn update<F: FnOnce(&mut Settings)>(&self, f: F) {
+        let _guard = self
+            .update_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut settings = self.load();
+        f(&mut settings);
+        if let Err(err) = self.save(&settings) {
+            app_log!(Warn, "Failed to save settings {:?}: {:?}", self.path, err);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use super::Utils;
+use serde::Serialize;
+use std::path::Path;
+
+// The state of the system at the time of the report, attached as `system.json`.
+// Every value is best effort, values that can't be collected are left out.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SystemState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_memory_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    available_memory_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    process_memory_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thread_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    free_disk_bytes: Option<u64>,
+    uptime_secs: i64,
+    // Board specific configuration on embedded systems
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kernel_cmdline: Option<String>,
+}
+
+impl SystemState {
+    // Only cheap queries are made, since this runs inside the panic hook
+    pub(crate) fn collect(
+        app_launch_timestamp: chrono::DateTime<chrono::Utc>,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        disk: Option<&Path>,
+    ) -> Self {
+        SystemState {
+            total_memory_bytes: Utils::get_total_memory(),
+            available_memory_bytes: Utils::get_available_memory(),
+            process_memory_bytes: Utils::get_process_memory(),
+            thread_count: Utils::get_thread_count(),
+            free_disk_bytes: disk.and_then(Utils::get_free_disk_space),
+            uptime_secs: (timestamp - app_launch_timestamp).num_seconds(),
+            kernel_cmdline: Utils::get_kernel_cmdline(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The system state with the kernel command line read from a fake `/proc/cmdline`
+    fn state_with_cmdline(name: &str, content: &str) -> serde_json::Value {
+        let path = std::env::temp_dir().join(format!(
+            "app-center-cmdline-{}-{}",
+            name,
+            std::process::id()
+        ));
+        std::fs::write(&path, content).unwrap();
+        let now = chrono::Utc::now();
+        let state = SystemState {
+            kernel_cmdline: Utils::read_kernel_cmdline(&path),
+            ..SystemState::collect(now, now, None)
+        };
+        let _ = std::fs::remove_file(&path);
+        serde_json::to_value(&state).unwrap()
+    }
+
+    #[test]
+    fn kernel_cmdline_is_in_the_attachment() {
+        let state = state_with_cmdline(
+            "board",
+            "console=ttyAMA0,115200 root=/dev/mmcblk0p2 rootwait\n",
+        );
+        assert_eq!(
+            state["kernelCmdline"],
+            "console=ttyAMA0,115200 root=/dev/mmcblk0p2 rootwait"
+        );
+    }
+
+    #[test]
+    fn empty_kernel_cmdline_is_left_out() {
+        let state = state_with_cmdline("empty", "\n");
+        assert!(state.get("kernelCmdline").is_none());
+    }
+}
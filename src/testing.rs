@@ -0,0 +1,178 @@
+use super::{HttpRequest, HttpResponse, HttpSender, SendError};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+///
+/// An `HttpSender` that keeps the requests in memory instead of sending them, for the
+/// integration tests of applications. Clones share the captured requests, so one clone can be
+/// handed to `AppCenterBuilder::http_sender` and the other kept for the assertions. Every
+/// request is answered with HTTP 200 unless other responses are queued. Requires the
+/// `testing` feature.
+///
+/// ```no_run
+/// use app_center::{AppCenter, CapturingTransport};
+///
+/// let transport = CapturingTransport::new();
+/// let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+///     .app_version("1.0.0")
+///     .auto_start_session(false)
+///     .http_sender(transport.clone())
+///     .build()
+///     .unwrap();
+/// app_center.set_report_callback(|logs| {
+///     logs.add_binary_attachement(b"state".to_vec(), Some("state.bin"))
+///         .unwrap();
+/// });
+///
+/// let error = std::io::Error::new(std::io::ErrorKind::Other, "disk full");
+/// app_center.report_error(&error).unwrap();
+///
+/// let logs = transport.logs();
+/// // The error comes first, followed by its attachments
+/// assert_eq!(logs[0]["type"], "handledError");
+/// assert_eq!(logs[0]["exception"]["message"], "disk full");
+/// assert!(logs
+///     .iter()
+///     .any(|log| log["type"] == "errorAttachment" && log["fileName"] == "state.bin"));
+/// ```
+///
+#[derive(Clone, Default)]
+pub struct CapturingTransport {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    requests: Vec<CapturedRequest>,
+    // Answers for the next requests, before the default one
+    responses: VecDeque<Result<HttpResponse, String>>,
+    default_response: Option<HttpResponse>,
+}
+
+///
+/// A request captured by a `CapturingTransport`
+///
+#[derive(Debug, Clone)]
+pub struct CapturedRequest {
+    endpoint: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+impl CapturedRequest {
+    /// The URL the request was sent to
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// The value of the header `name`, compared case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every header of the request
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// The raw JSON body
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// When the request was captured
+    pub fn timestamp(&self) -> chrono::DateTime<chrono::Utc> {
+        self.timestamp
+    }
+
+    /// The body parsed back into JSON, `{"logs": [...]}`
+    pub fn json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+impl CapturingTransport {
+    /// A transport that answers every request with HTTP 200
+    pub fn new() -> Self {
+        CapturingTransport::default()
+    }
+
+    /// Answer the next request, after the ones already queued, with `status` and `body`,
+    /// for example 429 to simulate throttling
+    pub fn respond_with(&self, status: u16, body: &str) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .responses
+            .push_back(Ok(HttpResponse::new(status, body)));
+    }
+
+    /// Fail the next request, after the ones already queued, as if AppCenter was unreachable
+    pub fn fail_next(&self, message: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.responses.push_back(Err(message.to_string()));
+    }
+
+    /// Answer the requests with `status` and `body` once the queued responses are used up,
+    /// instead of HTTP 200
+    pub fn set_default_response(&self, status: u16, body: &str) {
+        self.state.lock().unwrap().default_response = Some(HttpResponse::new(status, body));
+    }
+
+    /// The requests captured so far, including the ones that were answered with a failure
+    pub fn requests(&self) -> Vec<CapturedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Every log of the captured requests, parsed back into JSON, in the order they were sent.
+    /// Bodies that are not valid JSON are skipped.
+    pub fn logs(&self) -> Vec<serde_json::Value> {
+        let state = self.state.lock().unwrap();
+        state
+            .requests
+            .iter()
+            .filter_map(|request| request.json().ok())
+            .filter_map(
+                |mut json| match json.get_mut("logs").map(serde_json::Value::take) {
+                    Some(serde_json::Value::Array(logs)) => Some(logs),
+                    _ => None,
+                },
+            )
+            .flatten()
+            .collect()
+    }
+
+    /// Forget the captured requests, the queued responses are kept
+    pub fn clear(&self) {
+        self.state.lock().unwrap().requests.clear();
+    }
+}
+
+impl HttpSender for CapturingTransport {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, SendError> {
+        let mut state = self.state.lock().unwrap();
+
+        state.requests.push(CapturedRequest {
+            endpoint: request.endpoint().to_string(),
+            headers: request
+                .headers()
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect(),
+            body: request.body().to_vec(),
+            timestamp: chrono::Utc::now(),
+        });
+
+        match state.responses.pop_front() {
+            Some(Ok(response)) => Ok(response),
+            Some(Err(message)) => Err(SendError::from(Box::from(message))),
+            None => Ok(state
+                .default_response
+                .clone()
+                .unwrap_or_else(|| HttpResponse::new(200, ""))),
+        }
+    }
+}
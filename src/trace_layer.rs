@@ -0,0 +1,138 @@
+use super::logger::{LogBuffer, LogCapacity};
+use std::fmt::Write;
+use tracing::field::{Field, Visit};
+use tracing::span;
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+///
+/// A `tracing_subscriber` layer keeping the most recent events in memory, so they are attached
+/// to every report as `log.txt`. Shares its buffer with `AppCenterLogger`.
+///
+/// ```no_run
+/// use app_center::{AppCenterLayer, LogCapacity};
+/// use tracing_subscriber::layer::SubscriberExt;
+///
+/// let subscriber = tracing_subscriber::Registry::default()
+///     .with(AppCenterLayer::new(LogCapacity::Lines(200)).with_spans(true));
+/// tracing::subscriber::set_global_default(subscriber).unwrap();
+/// ```
+///
+pub struct AppCenterLayer {
+    buffer: &'static LogBuffer,
+    level: Level,
+    spans: bool,
+}
+
+impl AppCenterLayer {
+    ///
+    /// Record events at `INFO` and above, without spans
+    ///
+    pub fn new(capacity: LogCapacity) -> Self {
+        AppCenterLayer {
+            buffer: LogBuffer::shared(capacity),
+            level: Level::INFO,
+            spans: false,
+        }
+    }
+
+    ///
+    /// Record events at `level` and above
+    ///
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    ///
+    /// Also record when spans are entered and exited
+    ///
+    pub fn with_spans(mut self, spans: bool) -> Self {
+        self.spans = spans;
+        self
+    }
+}
+
+impl<S> Layer<S> for AppCenterLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        let metadata = event.metadata();
+        if *metadata.level() > self.level {
+            return;
+        }
+
+        let mut fields = FieldFormatter::default();
+        event.record(&mut fields);
+        self.buffer
+            .push_record(metadata.level(), metadata.target(), &fields.finish());
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        self.record_span(id, ctx, "enter");
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        self.record_span(id, ctx, "exit");
+    }
+}
+
+impl AppCenterLayer {
+    fn record_span<S>(&self, id: &span::Id, ctx: Context<'_, S>, action: &str)
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        if !self.spans {
+            return;
+        }
+
+        if let Some(span) = ctx.span(id) {
+            let metadata = span.metadata();
+            if *metadata.level() <= self.level {
+                let message = format!("{} {}", action, metadata.name());
+                self.buffer
+                    .push_record(metadata.level(), metadata.target(), &message);
+            }
+        }
+    }
+}
+
+// Formats the message of an event followed by its other fields as `key=value`
+#[derive(Default)]
+struct FieldFormatter {
+    message: String,
+    fields: String,
+}
+
+impl FieldFormatter {
+    fn finish(self) -> String {
+        match (self.message.is_empty(), self.fields.is_empty()) {
+            (_, true) => self.message,
+            (true, false) => self.fields,
+            (false, false) => format!("{} {}", self.message, self.fields),
+        }
+    }
+}
+
+impl Visit for FieldFormatter {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            if !self.fields.is_empty() {
+                self.fields.push(' ');
+            }
+            let _ = write!(self.fields, "{}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+}
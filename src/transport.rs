@@ -0,0 +1,151 @@
+use super::SendError;
+use std::time::Duration;
+
+///
+/// A request to the AppCenter ingestion endpoint, handed to an `HttpSender`
+///
+#[derive(Debug, Clone)]
+pub struct HttpRequest<'a> {
+    pub(crate) endpoint: &'a str,
+    pub(crate) headers: Vec<(&'static str, String)>,
+    pub(crate) body: &'a [u8],
+    pub(crate) timeout: Duration,
+}
+
+impl<'a> HttpRequest<'a> {
+    pub(crate) fn new(
+        endpoint: &'a str,
+        app_secret: &str,
+        install_id: &uuid::Uuid,
+        body: &'a [u8],
+        timeout: Duration,
+    ) -> Self {
+        HttpRequest {
+            endpoint,
+            headers: vec![
+                ("Content-Type", "application/json".to_string()),
+                ("app-secret", app_secret.to_string()),
+                ("install-id", install_id.to_string()),
+            ],
+            body,
+            timeout,
+        }
+    }
+
+    /// The URL to POST to
+    pub fn endpoint(&self) -> &str {
+        self.endpoint
+    }
+
+    /// The headers of the request, including the app secret and the install id
+    pub fn headers(&self) -> &[(&'static str, String)] {
+        &self.headers
+    }
+
+    /// The JSON body of the request
+    pub fn body(&self) -> &[u8] {
+        self.body
+    }
+
+    /// How long the request may take, including connecting
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+///
+/// The answer of the AppCenter ingestion endpoint
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    status: u16,
+    body: String,
+}
+
+impl HttpResponse {
+    /// A response with the status code and the body
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        HttpResponse {
+            status,
+            body: body.into(),
+        }
+    }
+
+    /// The HTTP status code
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// The body of the response
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// Whether the status is 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+///
+/// Uploads reports and other logs to AppCenter, see `AppCenterBuilder::http_sender`.
+/// Called from the panicking thread and from background threads, so it must not rely on
+/// an async runtime of the application. An unsuccessful HTTP status is a response, not an error.
+///
+pub trait HttpSender: Send + Sync {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, SendError>;
+}
+
+#[cfg(feature = "send")]
+pub(crate) type ClientConfigurator =
+    Box<dyn Fn(reqwest::blocking::ClientBuilder) -> reqwest::blocking::ClientBuilder + Send + Sync>;
+
+// The default sender, with a fresh client for every upload so no runtime thread is kept
+// around between crashes
+#[cfg(feature = "send")]
+pub(crate) struct ReqwestSender {
+    pub(crate) connect_timeout: Duration,
+    #[cfg(feature = "http2")]
+    pub(crate) use_http2: bool,
+    pub(crate) configure_http_client: Option<ClientConfigurator>,
+}
+
+#[cfg(feature = "send")]
+impl ReqwestSender {
+    fn client(&self, timeout: Duration) -> reqwest::Result<reqwest::blocking::Client> {
+        let builder = reqwest::blocking::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(timeout);
+
+        #[cfg(feature = "http2")]
+        let builder = if self.use_http2 {
+            builder.http2_prior_knowledge()
+        } else {
+            builder
+        };
+
+        let builder = match &self.configure_http_client {
+            Some(configure) => configure(builder),
+            None => builder,
+        };
+
+        builder.build()
+    }
+}
+
+#[cfg(feature = "send")]
+impl HttpSender for ReqwestSender {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, SendError> {
+        let client = self.client(request.timeout)?;
+
+        let mut builder = client.post(request.endpoint).timeout(request.timeout);
+        for (name, value) in &request.headers {
+            builder = builder.header(*name, value.as_str());
+        }
+
+        let resp = builder.body(request.body.to_vec()).send()?;
+        let status = resp.status().as_u16();
+        let body = resp.text()?;
+        Ok(HttpResponse::new(status, body))
+    }
+}
@@ -1,6 +1,109 @@
+use std::path::PathBuf;
+
 pub(crate) struct Utils {}
 
-#[cfg(windows)]
+impl Utils {
+    /// Retrieve the directory where the state of the application `app_id` is persisted
+    pub(crate) fn get_data_dir(app_id: &uuid::Uuid) -> Option<PathBuf> {
+        Self::get_user_data_dir().map(|d| d.join("appcenter").join(app_id.to_string()))
+    }
+
+    /// Retrieve the name of the current executable
+    pub(crate) fn get_process_name() -> Option<String> {
+        std::env::current_exe()
+            .ok()?
+            .file_name()?
+            .to_str()
+            .map(|s| s.to_string())
+    }
+
+    /// Retrieve the name of the current executable from the first command line argument
+    pub(crate) fn get_process_name_from_arg0() -> Option<String> {
+        let arg0 = std::env::args_os().next()?;
+        std::path::Path::new(&arg0)
+            .file_name()?
+            .to_str()
+            .map(|s| s.to_string())
+    }
+
+    /// Retrieve the current working directory
+    pub(crate) fn get_cwd() -> Option<String> {
+        std::env::current_dir()
+            .ok()?
+            .to_str()
+            .map(|s| s.to_string())
+    }
+
+    /// Read a kernel command line such as `/proc/cmdline`, trimmed
+    #[cfg_attr(not(all(feature = "enabled", target_os = "linux")), allow(dead_code))]
+    pub(crate) fn read_kernel_cmdline(path: &std::path::Path) -> Option<String> {
+        let cmdline = std::fs::read_to_string(path).ok()?;
+        Some(cmdline.trim().to_string()).filter(|c| !c.is_empty())
+    }
+
+    /// Validate a two letter ISO 3166 country code, normalized to upper case
+    pub(crate) fn normalize_country_code(code: &str) -> Option<String> {
+        let code = code.trim();
+        if code.len() == 2 && code.chars().all(|c| c.is_ascii_alphabetic()) {
+            Some(code.to_ascii_uppercase())
+        } else {
+            None
+        }
+    }
+
+    /// Retrieve the country from the region of a locale such as `de_DE.UTF-8` or `fr-CA`
+    #[cfg_attr(any(windows, not(feature = "enabled")), allow(dead_code))]
+    fn get_country_from_locale(locale: &str) -> Option<String> {
+        let locale = locale.split(['.', '@']).next()?;
+        let region = locale.split(['_', '-']).nth(1)?;
+        Self::normalize_country_code(region)
+    }
+
+    /// The lowercase hex SHA-256 digest of `data`
+    pub(crate) fn sha256_hex(data: &[u8]) -> String {
+        use sha2::{Digest, Sha256};
+        Sha256::digest(data)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// Match a string against a glob pattern, where `*` matches any sequence of characters
+    /// and `?` matches a single character
+    pub(crate) fn glob_match(pattern: &str, s: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let s: Vec<char> = s.chars().collect();
+
+        let (mut p, mut i) = (0, 0);
+        // Position of the last `*` in the pattern, and the position in the string it matched up to
+        let mut backtrack = None;
+
+        while i < s.len() {
+            match pattern.get(p) {
+                Some('*') => {
+                    backtrack = Some((p, i));
+                    p += 1;
+                }
+                Some(&c) if c == '?' || c == s[i] => {
+                    p += 1;
+                    i += 1;
+                }
+                _ => match backtrack {
+                    Some((star, matched)) => {
+                        p = star + 1;
+                        i = matched + 1;
+                        backtrack = Some((star, matched + 1));
+                    }
+                    None => return false,
+                },
+            }
+        }
+
+        pattern[p..].iter().all(|&c| c == '*')
+    }
+}
+
+#[cfg(all(feature = "enabled", windows))]
 impl Utils {
     /// Retrieve the system locale or return en_US as default value
     pub(crate) fn get_locale() -> String {
@@ -21,12 +124,108 @@ impl Utils {
         locale.into_string().unwrap_or("en_US".to_string())
     }
 
+    fn get_user_data_dir() -> Option<PathBuf> {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    }
+
+    fn get_memory_status() -> Option<winapi::um::sysinfoapi::MEMORYSTATUSEX> {
+        let mut status: winapi::um::sysinfoapi::MEMORYSTATUSEX = unsafe { std::mem::zeroed() };
+        status.dwLength = std::mem::size_of_val(&status) as _;
+        match unsafe { winapi::um::sysinfoapi::GlobalMemoryStatusEx(&mut status) } {
+            0 => None,
+            _ => Some(status),
+        }
+    }
+
+    /// Retrieve the physical memory of the system in bytes
+    pub(crate) fn get_total_memory() -> Option<u64> {
+        Self::get_memory_status().map(|s| s.ullTotalPhys)
+    }
+
+    /// Retrieve the physical memory available for new allocations in bytes
+    pub(crate) fn get_available_memory() -> Option<u64> {
+        Self::get_memory_status().map(|s| s.ullAvailPhys)
+    }
+
+    /// Retrieve the working set of the current process in bytes
+    pub(crate) fn get_process_memory() -> Option<u64> {
+        let mut counters: winapi::um::psapi::PROCESS_MEMORY_COUNTERS =
+            unsafe { std::mem::zeroed() };
+        match unsafe {
+            winapi::um::psapi::GetProcessMemoryInfo(
+                winapi::um::processthreadsapi::GetCurrentProcess(),
+                &mut counters,
+                std::mem::size_of_val(&counters) as _,
+            )
+        } {
+            0 => None,
+            _ => Some(counters.WorkingSetSize as u64),
+        }
+    }
+
+    /// Not collected on Windows
+    pub(crate) fn get_thread_count() -> Option<u64> {
+        None
+    }
+
+    /// Not available on Windows
+    pub(crate) fn get_kernel_cmdline() -> Option<String> {
+        None
+    }
+
+    /// Retrieve the space available to the user on the volume holding `path`
+    pub(crate) fn get_free_disk_space(path: &std::path::Path) -> Option<u64> {
+        use std::os::windows::ffi::OsStrExt;
+
+        let path: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let mut available: winapi::um::winnt::ULARGE_INTEGER = unsafe { std::mem::zeroed() };
+        match unsafe {
+            winapi::um::fileapi::GetDiskFreeSpaceExW(
+                path.as_ptr(),
+                &mut available,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        } {
+            0 => None,
+            _ => Some(unsafe { *available.QuadPart() }),
+        }
+    }
+
+    /// Retrieve the country of the user from the geographical location setting
+    pub(crate) fn get_country_code() -> Option<String> {
+        const GEOCLASS_NATION: u32 = 16;
+        const GEO_ISO2: u32 = 4;
+        const GEOID_NOT_AVAILABLE: i32 = -1;
+
+        let geo_id = unsafe { winapi::um::winnls::GetUserGeoID(GEOCLASS_NATION) };
+        if geo_id == GEOID_NOT_AVAILABLE {
+            return None;
+        }
+
+        let mut code = [0u16; 8];
+        match unsafe {
+            winapi::um::winnls::GetGeoInfoW(geo_id, GEO_ISO2, code.as_mut_ptr(), code.len() as _, 0)
+        } {
+            n if n > 1 => {
+                Self::normalize_country_code(&String::from_utf16_lossy(&code[..(n - 1) as usize]))
+            }
+            _ => None,
+        }
+    }
+
     /// Retrieve the current process PID
     pub(crate) fn get_pid() -> u32 {
         unsafe { winapi::um::processthreadsapi::GetCurrentProcessId() }
     }
 
     pub(crate) fn get_os_version() -> String {
+        let (major, minor, build) = Self::get_nt_version();
+        format!("{}.{}.{}", major, minor, build)
+    }
+
+    /// Retrieve the major, minor and build numbers of the system
+    pub(crate) fn get_nt_version() -> (u32, u32, u32) {
         #[link(name = "Ntdll")]
         extern "system" {
             fn RtlGetNtVersionNumbers(
@@ -44,17 +243,47 @@ impl Utils {
             RtlGetNtVersionNumbers(&mut major, &mut minor, &mut build);
         }
 
-        format!("{}.{}.{}", major, minor, build & 0x0fffffff)
+        (major, minor, build & 0x0fffffff)
     }
 
-    pub fn get_model() -> String {
+    /// Check if the system is 64-bit, also from a 32-bit process running under WOW64
+    pub(crate) fn is_64bit_os() -> bool {
+        if cfg!(target_pointer_width = "64") {
+            return true;
+        }
+
+        let mut wow64 = 0;
+        match unsafe {
+            winapi::um::wow64apiset::IsWow64Process(
+                winapi::um::processthreadsapi::GetCurrentProcess(),
+                &mut wow64,
+            )
+        } {
+            0 => false,
+            _ => wow64 != 0,
+        }
+    }
+
+    /// Retrieve the feature update of the system, such as `22H2`, or the build number on
+    /// releases that predate the display version
+    pub(crate) fn get_os_build() -> String {
+        const CURRENT_VERSION: &str = "SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion";
+
+        Self::read_registry_string(CURRENT_VERSION, "DisplayVersion")
+            .or_else(|| Self::read_registry_string(CURRENT_VERSION, "ReleaseId"))
+            .or_else(|| Self::read_registry_string(CURRENT_VERSION, "CurrentBuildNumber"))
+            .unwrap_or_else(|| "<Unknown>".to_string())
+    }
+
+    // Read a string value under HKEY_LOCAL_MACHINE
+    fn read_registry_string(sub_key: &str, value: &str) -> Option<String> {
         use std::ffi::CString;
 
-        let mut model_name = [0u8; 1024];
-        let mut len = model_name.len() as _;
+        let mut data = [0u8; 1024];
+        let mut len = data.len() as _;
 
-        let sub_key = CString::new("SYSTEM\\HardwareConfig\\Current").unwrap();
-        let value = CString::new("SystemProductName").unwrap();
+        let sub_key = CString::new(sub_key).ok()?;
+        let value = CString::new(value).ok()?;
 
         match unsafe {
             winapi::um::winreg::RegGetValueA(
@@ -63,22 +292,58 @@ impl Utils {
                 value.as_ptr(),
                 winapi::um::winreg::RRF_RT_REG_SZ,
                 std::ptr::null_mut(),
-                model_name.as_mut_ptr() as _,
+                data.as_mut_ptr() as _,
                 &mut len,
             )
         } {
-            0 if len > 1 => String::from_utf8_lossy(&model_name[..(len - 1) as usize]).to_string(),
-            _ => "<Unknown>".to_string(),
+            0 if len > 1 => Some(String::from_utf8_lossy(&data[..(len - 1) as usize]).to_string()),
+            _ => None,
         }
     }
+
+    pub fn get_model() -> String {
+        Self::read_registry_string("SYSTEM\\HardwareConfig\\Current", "SystemProductName")
+            .unwrap_or_else(|| "<Unknown>".to_string())
+    }
 }
 
-#[cfg(target_os = "linux")]
+#[cfg(all(feature = "enabled", unix))]
+impl Utils {
+    /// Retrieve the space available to the user on the volume holding `path`
+    pub(crate) fn get_free_disk_space(path: &std::path::Path) -> Option<u64> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        match unsafe { libc::statvfs(path.as_ptr(), &mut stat) } {
+            0 => Some(stat.f_bavail as u64 * stat.f_frsize as u64),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the country from the region of the locale set in the environment
+    pub(crate) fn get_country_code() -> Option<String> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|locale| !locale.is_empty())
+            .and_then(|locale| Self::get_country_from_locale(&locale))
+    }
+}
+
+#[cfg(all(feature = "enabled", target_os = "linux"))]
 impl Utils {
     pub(crate) fn get_locale() -> String {
         "en_US".to_string()
     }
 
+    fn get_user_data_dir() -> Option<PathBuf> {
+        match std::env::var_os("XDG_DATA_HOME") {
+            Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+            _ => std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share")),
+        }
+    }
+
     /// Retrieve the current process PID
     pub(crate) fn get_pid() -> u32 {
         unsafe { libc::getpid() as _ }
@@ -88,17 +353,107 @@ impl Utils {
         "Linux".to_string()
     }
 
+    /// Retrieve the release of the running kernel, such as `5.15.0-58-generic`
+    pub(crate) fn get_os_build() -> String {
+        let mut name: libc::utsname = unsafe { std::mem::zeroed() };
+        match unsafe { libc::uname(&mut name) } {
+            0 => unsafe { std::ffi::CStr::from_ptr(name.release.as_ptr()) }
+                .to_string_lossy()
+                .to_string(),
+            _ => "<Unknown>".to_string(),
+        }
+    }
+
+    // Read a `Key: value` line of a file in /proc, such as `MemTotal:  16318412 kB`
+    fn read_proc_value(path: &str, key: &str) -> Option<u64> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        let line = contents.lines().find(|l| l.starts_with(key))?;
+        let mut value = line[key.len()..].trim_start_matches(':').split_whitespace();
+        let number: u64 = value.next()?.parse().ok()?;
+        match value.next() {
+            Some("kB") => Some(number * 1024),
+            _ => Some(number),
+        }
+    }
+
+    /// Retrieve the physical memory of the system in bytes
+    pub(crate) fn get_total_memory() -> Option<u64> {
+        Self::read_proc_value("/proc/meminfo", "MemTotal")
+    }
+
+    /// Retrieve the memory available for new allocations in bytes
+    pub(crate) fn get_available_memory() -> Option<u64> {
+        Self::read_proc_value("/proc/meminfo", "MemAvailable")
+    }
+
+    /// Retrieve the resident memory of the current process in bytes
+    pub(crate) fn get_process_memory() -> Option<u64> {
+        Self::read_proc_value("/proc/self/status", "VmRSS")
+    }
+
+    /// Retrieve the number of threads of the current process
+    pub(crate) fn get_thread_count() -> Option<u64> {
+        Self::read_proc_value("/proc/self/status", "Threads")
+    }
+
+    /// Retrieve the command line the kernel was booted with
+    pub(crate) fn get_kernel_cmdline() -> Option<String> {
+        Self::read_kernel_cmdline(std::path::Path::new("/proc/cmdline"))
+    }
+
     pub fn get_model() -> String {
         "Computer".to_string()
     }
 }
 
-#[cfg(target_os = "macos")]
+#[cfg(all(feature = "enabled", target_os = "macos"))]
 impl Utils {
     pub(crate) fn get_locale() -> String {
         "en_US".to_string()
     }
 
+    fn get_user_data_dir() -> Option<PathBuf> {
+        std::env::var_os("HOME").map(|h| PathBuf::from(h).join("Library/Application Support"))
+    }
+
+    /// Retrieve the physical memory of the system in bytes
+    pub(crate) fn get_total_memory() -> Option<u64> {
+        let mut memsize: u64 = 0;
+        let mut len = std::mem::size_of::<u64>();
+        match unsafe {
+            libc::sysctlbyname(
+                b"hw.memsize\0".as_ptr() as _,
+                &mut memsize as *mut u64 as _,
+                &mut len,
+                std::ptr::null_mut(),
+                0,
+            )
+        } {
+            0 => Some(memsize),
+            _ => None,
+        }
+    }
+
+    /// Not collected on macOS
+    pub(crate) fn get_available_memory() -> Option<u64> {
+        None
+    }
+
+    /// Not collected on macOS
+    pub(crate) fn get_process_memory() -> Option<u64> {
+        None
+    }
+
+    /// Not collected on macOS
+    pub(crate) fn get_thread_count() -> Option<u64> {
+        None
+    }
+
+    /// Not available on macOS
+    pub(crate) fn get_kernel_cmdline() -> Option<String> {
+        None
+    }
+
     /// Retrieve the current process PID
     pub(crate) fn get_pid() -> u32 {
         unsafe { libc::getpid() as _ }
@@ -144,6 +499,25 @@ impl Utils {
         }
     }
 
+    /// Retrieve the build number of the system, such as `22G91`
+    pub(crate) fn get_os_build() -> String {
+        let mut build = [0u8; 256];
+        let mut len = build.len() as _;
+
+        match unsafe {
+            libc::sysctlbyname(
+                b"kern.osversion\0".as_ptr() as _,
+                build.as_mut_ptr() as _,
+                &mut len,
+                std::ptr::null_mut(),
+                0,
+            )
+        } {
+            0 if len > 1 => String::from_utf8_lossy(&build[..(len - 1) as usize]).to_string(),
+            _ => "<Unknown>".to_string(),
+        }
+    }
+
     pub fn get_model() -> String {
         let mut model_name = [0u8; 1024];
         let mut len = model_name.len() as _;
@@ -162,3 +536,59 @@ impl Utils {
         }
     }
 }
+
+// The system is not queried without the `enabled` feature
+#[cfg(not(feature = "enabled"))]
+impl Utils {
+    pub(crate) fn get_locale() -> String {
+        "en_US".to_string()
+    }
+
+    fn get_user_data_dir() -> Option<PathBuf> {
+        None
+    }
+
+    pub(crate) fn get_total_memory() -> Option<u64> {
+        None
+    }
+
+    pub(crate) fn get_available_memory() -> Option<u64> {
+        None
+    }
+
+    pub(crate) fn get_process_memory() -> Option<u64> {
+        None
+    }
+
+    pub(crate) fn get_thread_count() -> Option<u64> {
+        None
+    }
+
+    pub(crate) fn get_kernel_cmdline() -> Option<String> {
+        None
+    }
+
+    pub(crate) fn get_free_disk_space(_path: &std::path::Path) -> Option<u64> {
+        None
+    }
+
+    pub(crate) fn get_country_code() -> Option<String> {
+        None
+    }
+
+    pub(crate) fn get_pid() -> u32 {
+        std::process::id()
+    }
+
+    pub(crate) fn get_os_version() -> String {
+        "<Unknown>".to_string()
+    }
+
+    pub(crate) fn get_os_build() -> String {
+        "<Unknown>".to_string()
+    }
+
+    pub fn get_model() -> String {
+        "<Unknown>".to_string()
+    }
+}
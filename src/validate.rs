@@ -0,0 +1,269 @@
+use super::{
+    AppCenterException, AppCenterLog, AppCenterLogs, Device, TypedProperty, MAX_EVENT_NAME_LENGTH,
+    MAX_FILE_NAME_LENGTH, MAX_PROPERTIES, MAX_PROPERTY_LENGTH,
+};
+use chrono::Datelike;
+
+// Limits of the ingestion API that the client doesn't already enforce
+const MAX_USER_ID_LENGTH: usize = 256;
+const MAX_ATTACHMENT_BYTES: usize = 7 * 1024 * 1024;
+
+///
+/// The constraint of the ingestion API a field breaks, see `validate_payload`
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constraint {
+    /// The field is required and is empty
+    Required,
+    /// The field is longer than this many characters
+    MaxLength(usize),
+    /// The field has more than this many entries
+    MaxCount(usize),
+    /// The field is larger than this many bytes
+    MaxBytes(usize),
+    /// The timestamp can't be written as an ISO 8601 date, or comes after the time of the log
+    Timestamp,
+}
+
+impl std::fmt::Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Constraint::Required => write!(f, "is required"),
+            Constraint::MaxLength(max) => write!(f, "is longer than {} characters", max),
+            Constraint::MaxCount(max) => write!(f, "has more than {} entries", max),
+            Constraint::MaxBytes(max) => write!(f, "is larger than {} bytes", max),
+            Constraint::Timestamp => write!(f, "is not a valid timestamp"),
+        }
+    }
+}
+
+///
+/// A field of a report AppCenter would reject, see `validate_payload`
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    log_index: usize,
+    field: String,
+    constraint: Constraint,
+}
+
+impl ValidationIssue {
+    /// The index of the log in the payload, the error comes first and its attachments follow
+    pub fn log_index(&self) -> usize {
+        self.log_index
+    }
+
+    /// The path of the field in the JSON of the log, such as `device.appVersion`
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// The constraint the field breaks
+    pub fn constraint(&self) -> Constraint {
+        self.constraint
+    }
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "log {}: `{}` {}",
+            self.log_index, self.field, self.constraint
+        )
+    }
+}
+
+///
+/// Check a report against the documented constraints of the AppCenter ingestion API: the
+/// required fields, the limits on the length of fields and on the number of properties, the
+/// size of attachments and the timestamps. An empty result doesn't guarantee that the report
+/// is accepted, but a report with issues is likely to be rejected with HTTP 400. Reports are
+/// checked before they are sent too, and the issues are logged.
+///
+/// ```no_run
+/// let app_center = app_center::start!("00000000-0000-0000-0000-000000000000");
+/// let error = std::io::Error::new(std::io::ErrorKind::Other, "disk full");
+/// let report = app_center.build_report(&error).unwrap();
+/// for issue in app_center::validate_payload(&report) {
+///     eprintln!("{}", issue);
+/// }
+/// ```
+///
+pub fn validate_payload(payload: &AppCenterLogs) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    for (index, log) in payload.logs.iter().enumerate() {
+        let mut check = Check {
+            index,
+            issues: &mut issues,
+        };
+
+        match log {
+            AppCenterLog::ManagedError {
+                user_id,
+                app_launch_timestamp,
+                timestamp,
+                device,
+                exception,
+                ..
+            }
+            | AppCenterLog::HandledError {
+                user_id,
+                app_launch_timestamp,
+                timestamp,
+                device,
+                exception,
+                ..
+            } => {
+                check.timestamp("timestamp", timestamp);
+                check.timestamp("appLaunchTimestamp", app_launch_timestamp);
+                if app_launch_timestamp > timestamp {
+                    check.issue("appLaunchTimestamp", Constraint::Timestamp);
+                }
+                if let Some(user_id) = user_id {
+                    check.max_length("userId", user_id, MAX_USER_ID_LENGTH);
+                }
+                check.device(device);
+                check.exception("exception", exception);
+
+                if let AppCenterLog::HandledError { properties, .. } = log {
+                    if properties.len() > MAX_PROPERTIES {
+                        check.issue("properties", Constraint::MaxCount(MAX_PROPERTIES));
+                    }
+                    for (key, value) in properties {
+                        check.property("properties", key, Some(value));
+                    }
+                }
+            }
+            AppCenterLog::ErrorAttachment {
+                device,
+                content_type,
+                data,
+                file_name,
+                ..
+            } => {
+                check.device(device);
+                check.required("contentType", content_type);
+                if data.data.is_empty() {
+                    check.issue("data", Constraint::Required);
+                } else if data.data.len() > MAX_ATTACHMENT_BYTES {
+                    check.issue("data", Constraint::MaxBytes(MAX_ATTACHMENT_BYTES));
+                }
+                if let Some(file_name) = file_name {
+                    check.max_length("fileName", file_name, MAX_FILE_NAME_LENGTH);
+                }
+            }
+            AppCenterLog::StartSession {
+                timestamp, device, ..
+            } => {
+                check.timestamp("timestamp", timestamp);
+                check.device(device);
+            }
+            AppCenterLog::Event {
+                name,
+                timestamp,
+                device,
+                properties,
+                typed_properties,
+                ..
+            } => {
+                check.timestamp("timestamp", timestamp);
+                check.device(device);
+                check.required("name", name);
+                check.max_length("name", name, MAX_EVENT_NAME_LENGTH);
+
+                if let Some(properties) = properties {
+                    if properties.len() > MAX_PROPERTIES {
+                        check.issue("properties", Constraint::MaxCount(MAX_PROPERTIES));
+                    }
+                    for (key, value) in properties {
+                        check.property("properties", key, Some(value));
+                    }
+                }
+                if let Some(properties) = typed_properties {
+                    if properties.len() > MAX_PROPERTIES {
+                        check.issue("typedProperties", Constraint::MaxCount(MAX_PROPERTIES));
+                    }
+                    for property in properties {
+                        let value = match property {
+                            TypedProperty::String { value, .. } => Some(value.as_str()),
+                            _ => None,
+                        };
+                        check.property("typedProperties", property.name(), value);
+                    }
+                }
+            }
+            AppCenterLog::Page {
+                name,
+                timestamp,
+                device,
+                ..
+            } => {
+                check.timestamp("timestamp", timestamp);
+                check.device(device);
+                check.required("name", name);
+                check.max_length("name", name, MAX_EVENT_NAME_LENGTH);
+            }
+        }
+    }
+
+    issues
+}
+
+// Records the issues of one log
+struct Check<'i> {
+    index: usize,
+    issues: &'i mut Vec<ValidationIssue>,
+}
+
+impl Check<'_> {
+    fn issue(&mut self, field: impl Into<String>, constraint: Constraint) {
+        self.issues.push(ValidationIssue {
+            log_index: self.index,
+            field: field.into(),
+            constraint,
+        });
+    }
+
+    fn required(&mut self, field: &str, value: &str) {
+        if value.is_empty() {
+            self.issue(field, Constraint::Required);
+        }
+    }
+
+    fn max_length(&mut self, field: &str, value: &str, max: usize) {
+        if value.chars().count() > max {
+            self.issue(field, Constraint::MaxLength(max));
+        }
+    }
+
+    // Serialized as RFC 3339, which only has room for four digit years
+    fn timestamp(&mut self, field: &str, timestamp: &chrono::DateTime<chrono::Utc>) {
+        if !(0..=9999).contains(&timestamp.year()) {
+            self.issue(field, Constraint::Timestamp);
+        }
+    }
+
+    fn device(&mut self, device: &Device) {
+        for (name, value) in device.required_fields() {
+            self.required(&format!("device.{}", name), value);
+        }
+    }
+
+    fn exception(&mut self, field: &str, exception: &AppCenterException) {
+        self.required(&format!("{}.type", field), exception.exception_type());
+        for (i, inner) in exception.inner_exceptions().iter().enumerate() {
+            self.exception(&format!("{}.innerExceptions[{}]", field, i), inner);
+        }
+    }
+
+    fn property(&mut self, field: &str, key: &str, value: Option<&str>) {
+        let field = format!("{}[{:?}]", field, key);
+        self.required(&field, key);
+        self.max_length(&field, key, MAX_PROPERTY_LENGTH);
+        if let Some(value) = value {
+            self.max_length(&field, value, MAX_PROPERTY_LENGTH);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+// An `anyhow::Error` is reported with its chain of causes as nested exceptions, outermost first
+#![cfg(all(feature = "testing", feature = "anyhow"))]
+
+use anyhow::Context;
+use app_center::{AppCenter, CapturingTransport};
+
+fn save() -> anyhow::Result<()> {
+    Err(std::io::Error::other("disk full")).context("writing the cache")?;
+    Ok(())
+}
+
+fn export() -> anyhow::Result<()> {
+    save().context("exporting the document")
+}
+
+#[test]
+fn context_chain_is_reported_outermost_first() {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    let error = export().unwrap_err();
+    assert_eq!(error.chain().count(), 3);
+    app_center.report_anyhow(&error).unwrap();
+
+    let logs = transport.logs();
+    assert_eq!(logs[0]["type"], "handledError");
+
+    // Walk down the nested exceptions
+    let mut messages = Vec::new();
+    let mut exception = &logs[0]["exception"];
+    loop {
+        messages.push(exception["message"].as_str().unwrap().to_string());
+        match exception["innerExceptions"].as_array() {
+            Some(inner) => {
+                assert_eq!(inner.len(), 1);
+                exception = &inner[0];
+            }
+            None => break,
+        }
+    }
+    assert_eq!(
+        messages,
+        ["exporting the document", "writing the cache", "disk full"]
+    );
+
+    // The frames of the report are those of the outermost exception only
+    assert!(logs[0]["exception"]["frames"].is_array());
+    assert!(exception
+        .get("frames")
+        .is_none_or(|frames| frames.as_array().unwrap().is_empty()));
+}
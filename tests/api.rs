@@ -0,0 +1,389 @@
+// Every public item is used the same way with and without the `enabled` feature, so switching
+// the feature requires no change to the application. The types are the same in both builds. Checked by building this file with
+// `--no-default-features` and with the default features.
+
+use app_center::prelude::*;
+use app_center::{
+    validate_payload, AppSecret, AttachmentError, Base64Variant, CapabilitySet, Constraint,
+    ContextGuard, Device, DeviceBuilder, EventProperties, EventValidation, EventValidationError,
+    ExceptionFrame, FlushError, HttpRequest, HttpResponse, HttpSender, LogCapacity, LogLevel,
+    LogSink, PendingSummary, PurgeSummary, QueuedReport, ReportBuilder, ReportCallbackHandle,
+    ReportError, ReportingMode, ResolutionMode, ScrubField, ScrubTarget, SendError, UserIdMode,
+    ValidationIssue, VerifyError, LOG_TARGET,
+};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+struct NullSender;
+
+impl HttpSender for NullSender {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, SendError> {
+        let _: (&str, &[(&'static str, String)], &[u8], Duration) = (
+            request.endpoint(),
+            request.headers(),
+            request.body(),
+            request.timeout(),
+        );
+        let boxed: Box<dyn std::error::Error + Send + Sync> = "unreachable".into();
+        let error = SendError::from(boxed);
+        let _: (Option<u16>, Option<&str>) = (error.http_status(), error.response_body());
+        Ok(HttpResponse::new(200, ""))
+    }
+}
+
+// Type checked only, running it would install the panic hook
+#[allow(dead_code)]
+fn use_every_item() -> Result<(), Box<dyn std::error::Error>> {
+    let app_center: AppCenter = app_center::start!("00000000-0000-0000-0000-000000000000");
+    let _ = app_center::start!("00000000-0000-0000-0000-000000000000", "1.0.0");
+    let _ = app_center::start!("00000000-0000-0000-0000-000000000000", "1.0.0", "42");
+    let _ = AppCenter::start("00000000-0000-0000-0000-000000000000", "1.0.0");
+    let _: Option<AppCenter> = AppCenter::shared();
+    app_center::set_user_id(Some("user"));
+
+    let builder: AppCenterBuilder = AppCenter::builder("00000000-0000-0000-0000-000000000000")
+        .app_version("1.0.0")
+        .app_build("42")
+        .build_metadata("commit", "branch", "run")
+        .mirror_app_secrets(vec![String::new()])
+        .device(
+            DeviceBuilder::new()
+                .model("model")
+                .os_name("os")
+                .os_version("1")
+                .os_build("1")
+                .locale("en_US")
+                .app_version("1.0.0")
+                .app_build("42")
+                .sdk_name("sdk")
+                .sdk_version("1"),
+        )
+        .process_name("app")
+        .process_name_from_arg0(true)
+        .endpoint("https://in.appcenter.ms/logs?Api-Version=1.0.0")
+        .dry_run(false)
+        .dry_run_pretty(false)
+        .test_crash_in_release(false)
+        .pretty_panic_output(false)
+        .timeouts(Duration::from_secs(1), Duration::from_secs(2))
+        .http_sender(NullSender)
+        .crash_dir("crashes")
+        .data_dir(std::env::temp_dir().join("app-center-api"))
+        .enabled(true)
+        .persist_enabled(false)
+        .auto_start_session(false)
+        .snapshot_env_vars(vec!["PATH".to_string()])
+        .include_cwd(false)
+        .attach_system_state(false)
+        .ignore_messages(Vec::new())
+        .ignore_modules(Vec::new())
+        .persist_user_id(false)
+        .resolution_mode(ResolutionMode::Lazy)
+        .include_raw_symbols(false)
+        .skip_frames_matching("tokio::")
+        .strip_path_prefix("/build")
+        .strip_default_path_prefixes(true)
+        .sample_rate(1.0)
+        .event_validation(EventValidation::Strict)
+        .reporting_mode(ReportingMode::ReleaseOnly)
+        .user_id_mode(UserIdMode::Custom(Arc::new(|id| id.to_string())))
+        .base64_variant(Base64Variant::UrlSafe)
+        .max_send_attempts(3)
+        .max_breadcrumbs(10)
+        .log_level(LogLevel::Error)
+        .log_sink(|_level, _message| {});
+    let app_center2: Result<AppCenter, ConfigError> = builder.build();
+    let _ = AppCenter::builder("").start();
+    let _ = format!("{:?}", app_center2);
+
+    app_center.set_report_callback(|logs| logs.set_user_id(None));
+    let handle: ReportCallbackHandle = app_center.add_report_callback(|logs| {
+        let _ = logs.add_binary_attachement(Vec::new(), Some("data.bin"));
+    });
+    handle.unregister();
+
+    let _: (&str, String, &'static str, Option<String>) = (
+        app_center.app_secret(),
+        app_center.app_secret_masked(),
+        app_center.app_version(),
+        app_center.app_build(),
+    );
+    app_center.set_app_build("43");
+    let _: uuid::Uuid = app_center.install_id();
+    let _: u32 = app_center.launch_count();
+    let _: bool = app_center.is_analytics_alive();
+    app_center.set_enabled(true);
+    app_center.set_crashes_enabled(true);
+    app_center.set_analytics_enabled(true);
+    let _: (bool, bool, bool) = (
+        app_center.is_crashes_enabled(),
+        app_center.is_analytics_enabled(),
+        app_center.is_enabled(),
+    );
+    app_center.set_log_level(LogLevel::Off);
+    let _: ReportingMode = app_center.reporting_mode();
+    app_center.set_report_filter(|logs| !logs.message().is_empty());
+    app_center.set_scrubber(|target: &mut ScrubTarget| {
+        let _: ScrubField = target.field();
+        let _: Option<&mut String> = target.text_mut();
+        let _: Option<&mut Vec<u8>> = target.bytes_mut();
+        target.scrub_home_dir();
+        target.scrub_secrets();
+    });
+    app_center.set_grouping_key_fn(|exception| {
+        let _ = (
+            exception.exception_type(),
+            exception.message(),
+            exception.inner_exceptions(),
+            exception.frames(),
+        );
+        exception.default_grouping_key()
+    });
+    app_center.set_user_id(Some("user"));
+    app_center.set_user_id(None::<String>);
+    app_center.set_property("key", "value");
+    app_center.remove_property("key");
+    app_center.set_tag("key", "value");
+    app_center.remove_tag("key");
+    let scope: ContextGuard = app_center.push_scope();
+    scope.set_tag("key", "value");
+    app_center.set_persist_user_id(false);
+    app_center.set_ignored_messages(Vec::new());
+    app_center.set_ignored_modules(Vec::new());
+    app_center.set_resolution_mode(ResolutionMode::Eager);
+    app_center.set_user_id_mode(UserIdMode::Sha256);
+    app_center.set_base64_variant(Base64Variant::Standard);
+    app_center.set_crash_dir(Some("crashes"));
+    app_center.set_crash_dir(None::<String>);
+
+    let error = std::io::Error::other("error");
+    let _: Result<uuid::Uuid, ReportError> = app_center.report_error(&error);
+    #[cfg(feature = "anyhow")]
+    let _: Result<uuid::Uuid, ReportError> = app_center.report_anyhow(&anyhow::anyhow!("error"));
+    #[cfg(feature = "eyre")]
+    {
+        let _: Result<uuid::Uuid, ReportError> = app_center.report_eyre(&eyre::eyre!("error"));
+        app_center::AppCenterEyreHook::new(&app_center)
+            .base_hook(eyre::DefaultHandler::default_with)
+            .install()?;
+    }
+
+    let mut logs: AppCenterLogs = app_center.build_report(&error)?;
+    let _ = (
+        logs.current_page(),
+        logs.to_json_bytes()?,
+        logs.to_json_pretty()?,
+        logs.error_id(),
+        logs.timestamp(),
+        logs.user_id(),
+        logs.exception_type(),
+        logs.frames(),
+    );
+    let device: &Device = logs.device();
+    let _ = (
+        device.model(),
+        device.os_name(),
+        device.os_version(),
+        device.os_build(),
+        device.locale(),
+        device.app_version(),
+        device.app_build(),
+        device.country_code(),
+        device.windows_version(),
+        device.windows_build(),
+        device.is_64bit(),
+    );
+    logs.set_force_send(true);
+    logs.set_message("message".to_string());
+    logs.set_exception_type("type".to_string());
+    logs.set_user_id(Some("user".to_string()));
+    logs.retain_frames(|frame| frame.line_number().is_some());
+    let issues: Vec<ValidationIssue> = validate_payload(&logs);
+    for issue in &issues {
+        let _: (usize, &str, Constraint) = (issue.log_index(), issue.field(), issue.constraint());
+        let _ = issue.to_string();
+    }
+    let _: Result<(), AttachmentError> = logs.add_text_attachement("text", Some("log.txt"));
+
+    let frame = ExceptionFrame::new(Some("main".to_string()), None, Some(1));
+    let _: (u64, Option<&str>, Option<&str>, bool, Option<&str>) = (
+        frame.stable_hash(),
+        frame.method_name(),
+        frame.file_name(),
+        frame.is_inline(),
+        frame.raw_symbol(),
+    );
+    let builder: ReportBuilder = app_center
+        .report_builder()
+        .exception_type("type")
+        .message("message")
+        .frames(ExceptionFrame::collect_backtrace())
+        .fatal(false)
+        .user_id(None)
+        .add_text_attachment("text", Some("log.txt"))?
+        .add_binary_attachment(Vec::new(), None)?;
+    let _: AppCenterLogs = builder.build()?;
+    let _: Result<uuid::Uuid, ReportError> = app_center.report_builder().send();
+
+    let _: Option<uuid::Uuid> = app_center.last_error_id();
+    app_center.generate_test_crash();
+    let _: Result<uuid::Uuid, VerifyError> = app_center.send_test_report();
+    app_center.add_breadcrumb("category", "message", Some(BTreeMap::new()));
+    let breadcrumb = app_center::Breadcrumb::new("category", "message")
+        .with_level(app_center::BreadcrumbLevel::Debug)
+        .with_data(BTreeMap::new());
+    let _ = (
+        breadcrumb.level(),
+        breadcrumb.category(),
+        breadcrumb.message(),
+        breadcrumb.data(),
+    );
+    drop(app_center.add_breadcrumb_async(breadcrumb));
+    app_center.track_event("event", None)?;
+    app_center.track_page("page")?;
+    let properties = EventProperties::new()
+        .string("key", "value")
+        .long("long", 1)
+        .double("double", 1.0)
+        .bool("bool", true);
+    let _: (usize, bool) = (properties.len(), properties.is_empty());
+    let result: Result<(), EventValidationError> =
+        app_center.track_event_with_properties("event", properties);
+    result?;
+    app_center.verify_connection()?;
+    app_center.shutdown();
+    app_center.renew_session();
+    app_center.reset_launch_timestamp();
+    app_center.set_country_code(Some("US".to_string()));
+    app_center.set_max_send_attempts(3);
+    let _: PendingSummary = app_center.send_pending_reports();
+    let _: Result<usize, FlushError> = app_center.flush_queued_reports();
+    let queued: Vec<QueuedReport> = app_center.list_queued_reports();
+    for report in queued {
+        let _ = (
+            report.id,
+            report.timestamp,
+            report.size_bytes,
+            report.attempts,
+        );
+        app_center.delete_queued_report(report.id)?;
+    }
+    let summary: PurgeSummary = app_center.purge_local_data()?;
+    let _ = (summary.deleted, summary.errors);
+
+    let _: CapabilitySet = AppCenter::capabilities();
+    let sink: LogSink = Arc::new(|_level, _message| {});
+    let _ = (sink, LOG_TARGET, LogCapacity::Bytes(1024));
+    app_center::AppCenterLogger::init(Box::new(NullLogger), LogCapacity::Lines(10))?;
+
+    #[cfg(feature = "tracing")]
+    {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let layer = app_center::AppCenterLayer::new(LogCapacity::Lines(10))
+            .with_level(tracing::Level::DEBUG)
+            .with_spans(true);
+        let _ = tracing_subscriber::Registry::default().with(layer);
+    }
+
+    std::panic::set_hook(Box::new(|info| {
+        let exception = app_center::AppCenterException::from_panic_info(info);
+        let _ = format!("{:?}", exception);
+    }));
+
+    Ok(())
+}
+
+#[cfg(feature = "macros")]
+#[app_center::catch]
+fn caught() -> Option<u32> {
+    panic!("caught")
+}
+
+#[cfg(feature = "macros")]
+#[app_center::catch(fatal = true)]
+fn fatal() -> u32 {
+    1
+}
+
+#[cfg(feature = "macros")]
+#[app_center::catch]
+async fn caught_async() -> u32 {
+    1
+}
+
+struct NullLogger;
+
+impl log::Log for NullLogger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        false
+    }
+    fn log(&self, _: &log::Record) {}
+    fn flush(&self) {}
+}
+
+#[test]
+fn public_api_is_the_same_in_both_configurations() {
+    let _ = use_every_item as fn() -> Result<(), Box<dyn std::error::Error>>;
+
+    let capabilities = AppCenter::capabilities();
+    assert_eq!(
+        capabilities.contains(CapabilitySet::REPORTING),
+        cfg!(feature = "enabled")
+    );
+    assert!(capabilities.contains(CapabilitySet::LOG_INTEGRATION));
+
+    assert_eq!("".parse::<AppSecret>(), Err(ConfigError::EmptyAppSecret));
+    let invalid = "0a1b2c3d-not-a-uuid".parse::<AppSecret>().unwrap_err();
+    assert_eq!(
+        invalid.to_string(),
+        "the app secret \"0a1b2c3d***********\" is not a UUID"
+    );
+    let secret: AppSecret = "0a1b2c3d-4e5f-6071-8293-a4b5c6d7e8f9".parse().unwrap();
+    assert_eq!(secret.masked(), "0a1b2c3d****************************");
+    assert_eq!(String::from(secret.clone()), secret.as_str());
+
+    assert!(HttpResponse::new(204, "").is_success());
+    assert_eq!(
+        EventProperties::new().string("a", "1").long("a", 2).len(),
+        1
+    );
+    assert_eq!(VerifyError::Disabled.to_string(), "reporting is disabled");
+    assert_eq!(ReportError::Disabled.to_string(), "reporting is disabled");
+    assert_eq!(LogLevel::default(), LogLevel::Debug);
+
+    #[cfg(feature = "macros")]
+    {
+        drop(caught_async());
+        assert_eq!(caught(), None);
+        assert_eq!(fatal(), 1);
+    }
+}
+
+// The placeholder secret turns reporting off in both configurations, the callers are told
+#[test]
+fn reports_are_refused_when_reporting_is_off() {
+    let app_center = AppCenter::builder("00000000-0000-0000-0000-000000000000")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .start();
+
+    let error = std::io::Error::other("error");
+    assert_eq!(app_center.report_error(&error), Err(ReportError::Disabled));
+    #[cfg(feature = "anyhow")]
+    assert_eq!(
+        app_center.report_anyhow(&anyhow::anyhow!("error")),
+        Err(ReportError::Disabled)
+    );
+    #[cfg(feature = "eyre")]
+    assert_eq!(
+        app_center.report_eyre(&eyre::eyre!("error")),
+        Err(ReportError::Disabled)
+    );
+    assert_eq!(
+        app_center.report_builder().send(),
+        Err(ReportError::Disabled)
+    );
+    assert_eq!(app_center.last_error_id(), None);
+}
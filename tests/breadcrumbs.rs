@@ -0,0 +1,67 @@
+// Breadcrumbs added concurrently from async tasks all make it into the report, with the
+// `tokio` feature and without it
+
+use app_center::{AppCenter, Breadcrumb};
+use std::collections::BTreeSet;
+
+const TASKS: usize = 8;
+const PER_TASK: usize = 25;
+
+// The messages of the breadcrumbs attached to the report
+fn breadcrumb_messages(app_center: &AppCenter) -> BTreeSet<String> {
+    let error = std::io::Error::other("disk full");
+    let report = app_center.build_report(&error).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&report.to_json_bytes().unwrap()).unwrap();
+    let attachment = json["logs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|log| log["fileName"] == "breadcrumbs.json")
+        .expect("no breadcrumbs attachment");
+    let data = base64::decode(attachment["data"].as_str().unwrap()).unwrap();
+    let sessions: serde_json::Value = serde_json::from_slice(&data).unwrap();
+
+    sessions
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|session| session["breadcrumbs"].as_array().unwrap().iter())
+        .map(|breadcrumb| breadcrumb["message"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn breadcrumbs_are_added_from_concurrent_tasks() {
+    // The placeholder secret disables reporting, reports can still be built
+    let app_center = AppCenter::builder("00000000-0000-0000-0000-000000000000")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .max_breadcrumbs(TASKS * PER_TASK)
+        .start();
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let tasks: Vec<_> = (0..TASKS)
+            .map(|task| {
+                let app_center = app_center.clone();
+                tokio::spawn(async move {
+                    for i in 0..PER_TASK {
+                        let breadcrumb = Breadcrumb::new("task", format!("{}-{}", task, i));
+                        app_center.add_breadcrumb_async(breadcrumb).await;
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+    });
+
+    let expected: BTreeSet<String> = (0..TASKS)
+        .flat_map(|task| (0..PER_TASK).map(move |i| format!("{}-{}", task, i)))
+        .collect();
+    assert_eq!(breadcrumb_messages(&app_center), expected);
+}
@@ -0,0 +1,65 @@
+// The report callback stays installed, it runs once for every report, handled errors and
+// panics alike
+#![cfg(feature = "testing")]
+
+use app_center::{AppCenter, CapturingTransport};
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// The ids of the errors captured so far and the ids their `state.txt` attachments refer to,
+// waiting for `count` errors
+fn errors(transport: &CapturingTransport, count: usize) -> (Vec<String>, Vec<String>) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let logs = transport.logs();
+        let ids: Vec<String> = logs
+            .iter()
+            .filter(|log| log["type"] == "handledError" || log["type"] == "managedError")
+            .map(|log| log["id"].as_str().unwrap().to_string())
+            .collect();
+        if ids.len() >= count || Instant::now() > deadline {
+            let attached = logs
+                .iter()
+                .filter(|log| log["type"] == "errorAttachment" && log["fileName"] == "state.txt")
+                .map(|log| log["errorId"].as_str().unwrap().to_string())
+                .collect();
+            return (ids, attached);
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn callback_runs_for_every_report() {
+    panic::set_hook(Box::new(|_| {}));
+
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+    app_center.set_report_callback(move |logs| {
+        let call = counter.fetch_add(1, Ordering::SeqCst) + 1;
+        logs.add_binary_attachement(format!("call {}", call).into_bytes(), Some("state.txt"))
+            .unwrap();
+    });
+
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+    let (ids, attached) = errors(&transport, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(attached, ids);
+
+    let _ = panic::catch_unwind(|| panic!("export failed"));
+    let (ids, attached) = errors(&transport, 2);
+    assert_eq!(ids.len(), 2);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(attached, ids);
+}
@@ -0,0 +1,109 @@
+// Panics caught by `#[app_center::catch]` are reported as handled errors, also in async
+// functions polled by an executor, the ones of fatal scopes as crashes even inside a
+// non-fatal scope, and a panic swallowed inside a scope is never reported later
+#![cfg(all(feature = "testing", feature = "macros"))]
+
+use app_center::{AppCenter, CapturingTransport};
+use std::panic;
+use std::time::{Duration, Instant};
+
+#[app_center::catch]
+fn handled() -> Option<u32> {
+    panic!("handled")
+}
+
+#[app_center::catch(fatal = true)]
+fn fatal() {
+    panic!("fatal")
+}
+
+#[app_center::catch]
+fn fatal_inside_handled() -> bool {
+    fatal();
+    true
+}
+
+#[app_center::catch]
+fn swallowed() -> u32 {
+    let _ = panic::catch_unwind(|| panic!("swallowed"));
+    1
+}
+
+// Panics in a later poll, after the first one returned `Pending`
+#[app_center::catch]
+async fn handled_async() -> Option<u32> {
+    tokio::task::yield_now().await;
+    panic!("handled async")
+}
+
+// Unwinds without running the panic hook, so nothing is stashed for this scope
+#[app_center::catch]
+fn resumed() -> u32 {
+    panic::resume_unwind(Box::new("resumed"))
+}
+
+// The types and messages of the errors captured so far, waiting for the ones sent from the
+// reporting thread
+fn errors(transport: &CapturingTransport, count: usize) -> Vec<(String, String)> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let errors: Vec<_> = transport
+            .logs()
+            .iter()
+            .filter(|log| log["type"] == "handledError" || log["type"] == "managedError")
+            .map(|log| {
+                (
+                    log["type"].as_str().unwrap().to_string(),
+                    log["exception"]["message"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        if errors.len() >= count || Instant::now() > deadline {
+            return errors;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn panics_are_reported_by_their_scope() {
+    panic::set_hook(Box::new(|_| {}));
+
+    let transport = CapturingTransport::new();
+    let _app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    assert_eq!(handled(), None);
+    let reported = errors(&transport, 1);
+    assert_eq!(reported.len(), 1, "{:?}", reported);
+    assert_eq!(reported[0].0, "handledError");
+    assert!(reported[0].1.contains("'handled'"), "{:?}", reported);
+    transport.clear();
+
+    // Reported once, as a crash, then caught by the enclosing scope
+    assert!(!fatal_inside_handled());
+    let reported = errors(&transport, 1);
+    assert_eq!(reported.len(), 1, "{:?}", reported);
+    assert_eq!(reported[0].0, "managedError");
+    assert!(reported[0].1.contains("'fatal'"), "{:?}", reported);
+    transport.clear();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    assert_eq!(runtime.block_on(handled_async()), None);
+    let reported = errors(&transport, 1);
+    assert_eq!(reported.len(), 1, "{:?}", reported);
+    assert_eq!(reported[0].0, "handledError");
+    assert!(reported[0].1.contains("'handled async'"), "{:?}", reported);
+    transport.clear();
+
+    assert_eq!(swallowed(), 1);
+    assert_eq!(resumed(), 0);
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(errors(&transport, 0), vec![]);
+}
@@ -0,0 +1,66 @@
+// The panic hook doesn't keep the instance alive: once the last handle is dropped, panics
+// are no longer reported and only go to the original hook
+#![cfg(feature = "enabled")]
+
+use app_center::{AppCenter, HttpRequest, HttpResponse, HttpSender, SendError};
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+struct CountingSender(Arc<AtomicUsize>);
+
+impl HttpSender for CountingSender {
+    fn send(&self, _: &HttpRequest) -> Result<HttpResponse, SendError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(HttpResponse::new(200, ""))
+    }
+}
+
+static ORIGINAL_HOOK_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn panics_after_the_last_handle_is_dropped_go_to_the_original_hook() {
+    panic::set_hook(Box::new(|_| {
+        ORIGINAL_HOOK_CALLS.fetch_add(1, Ordering::SeqCst);
+    }));
+
+    let sent = Arc::new(AtomicUsize::new(0));
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .crash_dir(std::env::temp_dir().join("app-center-dropped"))
+        .http_sender(CountingSender(Arc::clone(&sent)))
+        .build()
+        .unwrap();
+    // Owned by the instance, released when it is
+    let callback_state = Arc::new(());
+    let held = Arc::clone(&callback_state);
+    app_center.set_report_callback(move |_| {
+        let _ = &held;
+    });
+
+    let _ = panic::catch_unwind(|| panic!("reported"));
+    assert_eq!(sent.load(Ordering::SeqCst), 1);
+    assert_eq!(ORIGINAL_HOOK_CALLS.load(Ordering::SeqCst), 1);
+
+    // A clone keeps it alive
+    let clone = app_center.clone();
+    drop(app_center);
+    let _ = panic::catch_unwind(|| panic!("reported by the clone"));
+    assert_eq!(sent.load(Ordering::SeqCst), 2);
+    assert_eq!(ORIGINAL_HOOK_CALLS.load(Ordering::SeqCst), 2);
+
+    drop(clone);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Arc::strong_count(&callback_state) > 1 {
+        assert!(Instant::now() < deadline, "the instance was kept alive");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    // The hook is still installed but skips straight to the original one
+    let _ = panic::catch_unwind(|| panic!("not reported"));
+    assert_eq!(ORIGINAL_HOOK_CALLS.load(Ordering::SeqCst), 3);
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(sent.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,45 @@
+// The environment variables matching the patterns of `snapshot_env_vars` are attached to
+// every report as `environment.json`, with their values at `build()` time. The environment
+// is shared by the tests of a binary, so this one has its own.
+#![cfg(feature = "testing")]
+
+use app_center::{AppCenter, CapturingTransport};
+use serde_json::{json, Value};
+
+// The content of the `environment.json` attachment of the last report
+fn environment(transport: &CapturingTransport) -> Value {
+    let logs = transport.logs();
+    let attachment = logs
+        .iter()
+        .find(|log| log["type"] == "errorAttachment" && log["fileName"] == "environment.json")
+        .expect("no environment.json attachment");
+    let data = base64::decode(attachment["data"].as_str().unwrap()).unwrap();
+    serde_json::from_slice(&data).unwrap()
+}
+
+#[test]
+fn matching_env_vars_are_reported_as_they_were_at_build() {
+    std::env::set_var("APP_CENTER_TEST_CHANNEL", "beta");
+    std::env::set_var("APP_CENTER_TEST_REGION", "eu");
+    std::env::set_var("OTHER_APP_CENTER_TEST", "left out");
+
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .snapshot_env_vars(vec!["APP_CENTER_TEST_*".to_string()])
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    // Changed after the snapshot, like code about to panic could
+    std::env::set_var("APP_CENTER_TEST_CHANNEL", "changed");
+    std::env::set_var("APP_CENTER_TEST_ADDED", "late");
+
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+    assert_eq!(
+        environment(&transport),
+        json!({ "APP_CENTER_TEST_CHANNEL": "beta", "APP_CENTER_TEST_REGION": "eu" })
+    );
+}
@@ -0,0 +1,85 @@
+// Analytics events are uploaded in the shape of the ingestion schema
+#![cfg(feature = "testing")]
+
+use app_center::{AppCenter, CapturingTransport, DeviceBuilder, FixedClock, SequentialIds};
+use chrono::TimeZone;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+// An instance with deterministic ids, time and device, and the transport it sends to
+fn app_center() -> (AppCenter, CapturingTransport) {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .id_source(SequentialIds::new())
+        .clock(FixedClock(
+            chrono::Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap(),
+        ))
+        .device(
+            DeviceBuilder::new()
+                .model("test")
+                .os_name("Linux")
+                .os_version("6.1")
+                .locale("en_US")
+                .app_version("1.0.0")
+                .app_build("42")
+                .sdk_version("0.0.0"),
+        )
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+    (app_center, transport)
+}
+
+#[test]
+fn event_has_the_shape_of_the_ingestion_schema() {
+    let (app_center, transport) = app_center();
+    let properties: BTreeMap<String, String> = [("format", "pdf"), ("pages", "12")]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    app_center
+        .track_event("export_completed", Some(properties))
+        .unwrap();
+    app_center.track_event("settings_opened", None).unwrap();
+    app_center.shutdown();
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 1, "the events are sent in one batch");
+    let device = json!({
+        "model": "test",
+        "sdkName": "appcenter.custom",
+        "sdkVersion": "0.0.0",
+        "osName": "Linux",
+        "osVersion": "6.1",
+        "osBuild": "",
+        "locale": "en_US",
+        "appVersion": "1.0.0",
+        "appBuild": "42",
+    });
+    assert_eq!(
+        requests[0].json().unwrap(),
+        json!({
+            "logs": [
+                {
+                    "type": "event",
+                    "id": "00000000-0000-0000-0000-000000000002",
+                    "name": "export_completed",
+                    "timestamp": "2024-01-02T03:04:05Z",
+                    "sid": "00000000-0000-0000-0000-000000000001",
+                    "device": device,
+                    "properties": { "format": "pdf", "pages": "12" },
+                },
+                {
+                    "type": "event",
+                    "id": "00000000-0000-0000-0000-000000000003",
+                    "name": "settings_opened",
+                    "timestamp": "2024-01-02T03:04:05Z",
+                    "sid": "00000000-0000-0000-0000-000000000001",
+                    "device": device,
+                },
+            ]
+        })
+    );
+}
@@ -0,0 +1,52 @@
+// `AppCenterEyreHook` reports errors where they are created, formatted by `color_eyre`
+#![cfg(all(feature = "testing", feature = "eyre"))]
+
+use app_center::{AppCenter, AppCenterEyreHook, CapturingTransport};
+
+fn load_config() -> eyre::Result<Vec<u8>> {
+    Err(std::io::Error::other("config.toml is missing"))?;
+    Ok(Vec::new())
+}
+
+#[test]
+fn errors_are_reported_with_color_eyre_as_the_base_hook() {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    let (_, eyre_hook) = color_eyre::config::HookBuilder::default()
+        .theme(color_eyre::config::Theme::new())
+        .into_hooks();
+    AppCenterEyreHook::new(&app_center)
+        .base_hook(eyre_hook.into_eyre_hook())
+        .install()
+        .unwrap();
+
+    let error = load_config().unwrap_err();
+    let logs = transport.logs();
+    // Reported once, followed by its attachments
+    assert_eq!(
+        logs.iter()
+            .filter(|log| log["type"] == "handledError")
+            .count(),
+        1
+    );
+    assert_eq!(logs[0]["type"], "handledError");
+    assert_eq!(logs[0]["exception"]["message"], "config.toml is missing");
+    assert_eq!(
+        app_center.last_error_id().unwrap().to_string(),
+        logs[0]["id"]
+    );
+
+    // Formatted by color_eyre, which numbers the causes, not by eyre's default handler
+    let formatted = format!("{:?}", error);
+    assert!(
+        formatted.contains("   0: config.toml is missing"),
+        "{}",
+        formatted
+    );
+}
@@ -0,0 +1,207 @@
+// With deterministic ids, time and device, the logs sent to AppCenter are the same on every
+// run and every machine
+#![cfg(feature = "testing")]
+
+use app_center::{
+    AppCenter, CapturingTransport, DeviceBuilder, EventProperties, ExceptionFrame, FixedClock,
+    SequentialIds,
+};
+use chrono::TimeZone;
+
+const DEVICE: &str = concat!(
+    r#"{"model":"test","sdkName":"appcenter.custom","sdkVersion":"0.0.0","osName":"Linux","#,
+    r#""osVersion":"6.1","osBuild":"","locale":"en_US","appVersion":"1.0.0","appBuild":"42"}"#,
+);
+
+fn golden() -> String {
+    [
+        r#"{"logs":["#,
+        r#"{"type":"page","id":"00000000-0000-0000-0000-000000000002","name":"settings","#,
+        r#""timestamp":"2024-01-02T03:04:05Z","sid":"00000000-0000-0000-0000-000000000001","#,
+        r#""device":"#,
+        DEVICE,
+        r#"},"#,
+        r#"{"type":"event","id":"00000000-0000-0000-0000-000000000003","#,
+        r#""name":"export_completed","timestamp":"2024-01-02T03:04:05Z","#,
+        r#""sid":"00000000-0000-0000-0000-000000000001","device":"#,
+        DEVICE,
+        r#","typedProperties":[{"type":"string","name":"format","value":"pdf"},"#,
+        r#"{"type":"long","name":"pages","value":12}]}"#,
+        r#"]}"#,
+    ]
+    .concat()
+}
+
+// The body of every request sent for a page view and an event
+fn capture() -> Vec<String> {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .id_source(SequentialIds::new())
+        .clock(FixedClock(
+            chrono::Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap(),
+        ))
+        .device(
+            DeviceBuilder::new()
+                .model("test")
+                .os_name("Linux")
+                .os_version("6.1")
+                .locale("en_US")
+                .app_version("1.0.0")
+                .app_build("42")
+                .sdk_version("0.0.0"),
+        )
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    app_center.track_page("settings").unwrap();
+    app_center
+        .track_event_with_properties(
+            "export_completed",
+            EventProperties::new()
+                .string("format", "pdf")
+                .long("pages", 12),
+        )
+        .unwrap();
+    app_center.shutdown();
+
+    transport
+        .requests()
+        .iter()
+        .map(|request| String::from_utf8(request.body().to_vec()).unwrap())
+        .collect()
+}
+
+// A crash with two attachments, the device is repeated in full in every attachment. The
+// values that depend on the process and the build are taken from the report itself.
+fn golden_crash(report: &serde_json::Value) -> String {
+    let error = &report["logs"][0];
+    let build_info = report["logs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|log| log["fileName"] == "build_info.json")
+        .unwrap();
+    let attachment = |id: u32, content_type: &str, data: &str, file_name: &str| {
+        format!(
+            concat!(
+                r#"{{"type":"errorAttachment","id":"00000000-0000-0000-0000-00000000000{}","#,
+                r#""errorId":"00000000-0000-0000-0000-000000000002","device":{},"#,
+                r#""contentType":"{}","data":"{}","fileName":"{}"}}"#,
+            ),
+            id, DEVICE, content_type, data, file_name
+        )
+    };
+
+    [
+        r#"{"logs":["#.to_string(),
+        format!(
+            concat!(
+                r#"{{"type":"managedError","id":"00000000-0000-0000-0000-000000000002","#,
+                r#""userId":"user","appLaunchTimestamp":"2024-01-02T03:04:05Z","#,
+                r#""timestamp":"2024-01-02T03:04:05Z","fatal":true,"processId":{},"#,
+                r#""processName":"{}","device":{},"#,
+                r#""exception":{{"type":"libfoo::Error","message":"foo_open failed","#,
+                r#""frames":[{{"methodName":"foo_open","lineNumber":42,"fileName":"foo.c"}}]}},"#,
+                r#""groupingKey":"{}","isFirstLaunch":true,"#,
+                r#""sid":"00000000-0000-0000-0000-000000000001","hookExecutionMs":{}}},"#,
+            ),
+            std::process::id(),
+            error["processName"].as_str().unwrap(),
+            DEVICE,
+            error["groupingKey"].as_str().unwrap(),
+            error["hookExecutionMs"],
+        ),
+        attachment(
+            3,
+            "application/json",
+            // {"launch_count":"1"}
+            "eyJsYXVuY2hfY291bnQiOiIxIn0=",
+            "properties.json",
+        ),
+        ",".to_string(),
+        attachment(
+            4,
+            "application/json",
+            build_info["data"].as_str().unwrap(),
+            "build_info.json",
+        ),
+        ",".to_string(),
+        attachment(5, "text/plain", "bW9kZT1ydw==", "foo.txt"),
+        ",".to_string(),
+        attachment(6, "application/octet_stream", "AAEC", "foo.bin"),
+        r#"]}"#.to_string(),
+    ]
+    .concat()
+}
+
+// The body sent for a crash built with `ReportBuilder`, on the first launch
+fn capture_crash() -> String {
+    let data_dir = std::env::temp_dir().join(format!("app-center-golden-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&data_dir);
+
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .attach_system_state(false)
+        .max_breadcrumbs(0)
+        .data_dir(&data_dir)
+        .id_source(SequentialIds::new())
+        .clock(FixedClock(
+            chrono::Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap(),
+        ))
+        .device(
+            DeviceBuilder::new()
+                .model("test")
+                .os_name("Linux")
+                .os_version("6.1")
+                .locale("en_US")
+                .app_version("1.0.0")
+                .app_build("42")
+                .sdk_version("0.0.0"),
+        )
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    app_center
+        .report_builder()
+        .exception_type("libfoo::Error")
+        .message("foo_open failed")
+        .frames(vec![ExceptionFrame::new(
+            Some("foo_open".to_string()),
+            Some("foo.c".to_string()),
+            Some(42),
+        )])
+        .fatal(true)
+        .user_id(Some("user".to_string()))
+        .add_text_attachment("mode=rw", Some("foo.txt"))
+        .unwrap()
+        .add_binary_attachment(vec![0, 1, 2], Some("foo.bin"))
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let _ = std::fs::remove_dir_all(&data_dir);
+    String::from_utf8(transport.requests()[0].body().to_vec()).unwrap()
+}
+
+#[test]
+fn crash_with_attachments_matches_the_golden_json() {
+    let body = capture_crash();
+    let report: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body, golden_crash(&report));
+}
+
+#[test]
+fn logs_match_the_golden_json() {
+    assert_eq!(capture(), vec![golden()]);
+}
+
+#[test]
+fn logs_are_the_same_on_every_run() {
+    assert_eq!(capture(), capture());
+}
@@ -0,0 +1,64 @@
+// The panic hook hands the report over and runs the original hook right away, the report is
+// built and sent from the reporting thread while the panicking thread waits
+#![cfg(feature = "enabled")]
+
+use app_center::{AppCenter, HttpRequest, HttpResponse, HttpSender, SendError};
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Stands in for the network
+const SEND_DELAY: Duration = Duration::from_millis(300);
+const HOOK_BUDGET: Duration = Duration::from_millis(50);
+
+struct SlowSender(Arc<AtomicUsize>);
+
+impl HttpSender for SlowSender {
+    fn send(&self, _: &HttpRequest) -> Result<HttpResponse, SendError> {
+        std::thread::sleep(SEND_DELAY);
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(HttpResponse::new(200, ""))
+    }
+}
+
+#[test]
+fn original_hook_runs_before_the_report_is_built() {
+    static PANICKED_AT: Mutex<Option<Instant>> = Mutex::new(None);
+    static HOOK_REACHED: Mutex<Vec<Duration>> = Mutex::new(Vec::new());
+
+    // Becomes the original hook of the instance
+    panic::set_hook(Box::new(|_| {
+        let panicked_at = PANICKED_AT.lock().unwrap().unwrap();
+        HOOK_REACHED.lock().unwrap().push(panicked_at.elapsed());
+    }));
+
+    let sent = Arc::new(AtomicUsize::new(0));
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .crash_dir(std::env::temp_dir().join("app-center-hook-latency"))
+        .http_sender(SlowSender(Arc::clone(&sent)))
+        .build()
+        .unwrap();
+
+    for i in 1..=3 {
+        let result = panic::catch_unwind(|| {
+            *PANICKED_AT.lock().unwrap() = Some(Instant::now());
+            panic!("hook latency");
+        });
+        assert!(result.is_err());
+
+        // The hook returns once the report is sent
+        assert_eq!(sent.load(Ordering::SeqCst), i);
+    }
+
+    for reached in HOOK_REACHED.lock().unwrap().iter() {
+        assert!(
+            *reached < HOOK_BUDGET,
+            "original hook reached after {:?}",
+            reached
+        );
+    }
+    drop(app_center);
+}
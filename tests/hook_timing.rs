@@ -0,0 +1,55 @@
+// Crash reports carry the time from the entry of the panic hook to the serialization of the
+// report as `hookExecutionMs`, handled errors don't
+#![cfg(feature = "testing")]
+
+use app_center::{AppCenter, CapturingTransport};
+use std::panic;
+use std::time::{Duration, Instant};
+
+// Slows down building the report, which the measured time includes
+const CALLBACK_DELAY: Duration = Duration::from_millis(50);
+
+fn error_log(transport: &CapturingTransport, r#type: &str) -> serde_json::Value {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        if let Some(log) = transport
+            .logs()
+            .into_iter()
+            .find(|log| log["type"] == r#type)
+        {
+            return log;
+        }
+        assert!(Instant::now() < deadline, "no {} was sent", r#type);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn hook_execution_time_is_reported_for_crashes() {
+    panic::set_hook(Box::new(|_| {}));
+
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+    app_center.set_report_callback(|_| std::thread::sleep(CALLBACK_DELAY));
+
+    let _ = panic::catch_unwind(|| panic!("export failed"));
+    let crash = error_log(&transport, "managedError");
+    let elapsed = crash["hookExecutionMs"]
+        .as_u64()
+        .expect("no hookExecutionMs in the crash report");
+    assert!(
+        elapsed >= CALLBACK_DELAY.as_millis() as u64,
+        "{} ms",
+        elapsed
+    );
+
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+    let handled = error_log(&transport, "handledError");
+    assert!(handled.get("hookExecutionMs").is_none());
+}
@@ -0,0 +1,72 @@
+// The reqwest client is customized by `configure_http_client`, the changes reach the wire
+#![cfg(feature = "send")]
+
+use app_center::AppCenter;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+
+// Answers a single request with HTTP 200 and hands over its headers, lowercased names
+fn serve_once(listener: TcpListener) -> mpsc::Receiver<Vec<(String, String)>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut headers = Vec::new();
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        loop {
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            let (name, value) = header.split_once(':').unwrap();
+            headers.push((name.to_ascii_lowercase(), value.trim().to_string()));
+        }
+
+        let length = headers
+            .iter()
+            .find(|(name, _)| name == "content-length")
+            .map_or(0, |(_, value)| value.parse().unwrap());
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+
+        let mut stream = reader.into_inner();
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+            .unwrap();
+        tx.send(headers).unwrap();
+    });
+    rx
+}
+
+#[test]
+fn user_agent_set_by_configure_http_client_is_sent() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}/logs", listener.local_addr().unwrap());
+    let headers = serve_once(listener);
+
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .endpoint(endpoint)
+        .configure_http_client(|builder| builder.user_agent("editor/2.1 (app-center)"))
+        .build()
+        .unwrap();
+    app_center.verify_connection().unwrap();
+
+    let headers = headers.recv().unwrap();
+    let user_agent = headers.iter().find(|(name, _)| name == "user-agent");
+    assert_eq!(
+        user_agent.map(|(_, value)| value.as_str()),
+        Some("editor/2.1 (app-center)"),
+        "{:?}",
+        headers
+    );
+    assert!(headers.iter().any(
+        |(name, value)| name == "app-secret" && value == "12345678-1234-1234-1234-123456789abc"
+    ));
+}
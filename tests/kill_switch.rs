@@ -0,0 +1,47 @@
+// Reporting is disabled for placeholder app secrets and by `APPCENTER_DISABLE=1`. The
+// environment is shared by the tests of a binary, so this one has its own.
+#![cfg(feature = "testing")]
+
+use app_center::{AppCenter, CapturingTransport, ReportError};
+
+const SECRET: &str = "12345678-1234-1234-1234-123456789abc";
+
+fn app_center(secret: &str, transport: &CapturingTransport) -> AppCenter {
+    AppCenter::builder(secret)
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .start()
+}
+
+// Whether the instance reports, checking that it sends exactly when it says it is enabled
+fn reports(secret: &str) -> bool {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(secret, &transport);
+    let error = std::io::Error::other("disk full");
+    let result = app_center.report_error(&error);
+    if app_center.is_enabled() {
+        assert!(result.is_ok());
+        assert!(!transport.logs().is_empty());
+    } else {
+        assert!(matches!(result, Err(ReportError::Disabled)));
+        assert!(transport.requests().is_empty());
+    }
+    app_center.is_enabled()
+}
+
+#[test]
+fn placeholder_secret_and_kill_switch_disable_reporting() {
+    std::env::remove_var("APPCENTER_DISABLE");
+    assert!(reports(SECRET));
+    assert!(!reports(""));
+    assert!(!reports("00000000-0000-0000-0000-000000000000"));
+
+    std::env::set_var("APPCENTER_DISABLE", "1");
+    assert!(!reports(SECRET));
+
+    // Only `1` disables
+    std::env::set_var("APPCENTER_DISABLE", "0");
+    assert!(reports(SECRET));
+    std::env::remove_var("APPCENTER_DISABLE");
+}
@@ -0,0 +1,77 @@
+// The edits of a report callback through the mutators of `AppCenterLogs` are what is uploaded
+#![cfg(feature = "testing")]
+
+use app_center::{AppCenter, CapturingTransport, ExceptionFrame};
+use serde_json::json;
+
+fn frame(method_name: &str, file_name: &str) -> ExceptionFrame {
+    ExceptionFrame::new(
+        Some(method_name.to_string()),
+        Some(file_name.to_string()),
+        Some(1),
+    )
+}
+
+#[test]
+fn callback_edits_are_serialized() {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+    app_center.set_user_id(Some("alice"));
+    app_center.set_report_callback(|logs| {
+        let message = logs.message().replace("/home/alice", "~");
+        logs.set_message(message);
+        logs.set_exception_type("RedactedError".to_string());
+        logs.set_user_id(Some("anonymous".to_string()));
+        logs.retain_frames(|frame| {
+            frame
+                .file_name()
+                .is_none_or(|file| !file.starts_with("/home/alice"))
+        });
+    });
+
+    app_center
+        .report_builder()
+        .exception_type("std::io::Error")
+        .message("cannot open /home/alice/report.pdf")
+        .frames(vec![
+            frame("my_app::open", "src/open.rs"),
+            frame("plugin::load", "/home/alice/plugins/src/lib.rs"),
+            frame("my_app::main", "src/main.rs"),
+        ])
+        .send()
+        .unwrap();
+
+    let log = &transport.logs()[0];
+    assert_eq!(log["userId"], "anonymous");
+    assert_eq!(log["exception"]["type"], "RedactedError");
+    assert_eq!(log["exception"]["message"], "cannot open ~/report.pdf");
+    assert_eq!(
+        log["exception"]["frames"],
+        json!([
+            { "methodName": "my_app::open", "lineNumber": 1, "fileName": "src/open.rs" },
+            { "methodName": "my_app::main", "lineNumber": 1, "fileName": "src/main.rs" },
+        ])
+    );
+}
+
+#[test]
+fn removed_user_id_is_serialized_as_null() {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+    app_center.set_user_id(Some("alice"));
+    app_center.set_report_callback(|logs| logs.set_user_id(None));
+
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+    assert!(transport.logs()[0]["userId"].is_null());
+}
@@ -0,0 +1,83 @@
+// `AppCenterException::from_panic_info` and `ExceptionFrame::collect_backtrace` build what the
+// installed panic hook reports
+#![cfg(feature = "testing")]
+
+use app_center::{AppCenter, AppCenterException, CapturingTransport, ExceptionFrame};
+use serde_json::json;
+use std::panic;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static BUILT: Mutex<Option<AppCenterException>> = Mutex::new(None);
+static COLLECTED: Mutex<Vec<ExceptionFrame>> = Mutex::new(Vec::new());
+
+#[inline(never)]
+fn open_document() {
+    panic!("document is corrupt");
+}
+
+#[test]
+fn from_panic_info_matches_the_hook() {
+    panic::set_hook(Box::new(|_| {}));
+    let transport = CapturingTransport::new();
+    let _app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    // A custom hook in front of the one of the instance
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        *BUILT.lock().unwrap() = Some(AppCenterException::from_panic_info(info));
+        *COLLECTED.lock().unwrap() = ExceptionFrame::collect_backtrace();
+        hook(info);
+    }));
+    let _ = panic::catch_unwind(open_document);
+    let _ = panic::take_hook();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let reported = loop {
+        if let Some(log) = transport
+            .logs()
+            .into_iter()
+            .find(|log| log["type"] == "managedError")
+        {
+            break log["exception"].clone();
+        }
+        assert!(Instant::now() < deadline, "the panic was not reported");
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    let built = serde_json::to_value(BUILT.lock().unwrap().as_ref().unwrap()).unwrap();
+
+    assert_eq!(built["type"], reported["type"]);
+    assert_eq!(built["message"], reported["message"]);
+    assert!(built["message"]
+        .as_str()
+        .unwrap()
+        .contains("panicked at 'document is corrupt'"));
+
+    // Captured in different hooks, the frames are the same from the panicking function on
+    let from_panic = |exception: &serde_json::Value| {
+        let frames = exception["frames"].as_array().unwrap().clone();
+        let start = frames
+            .iter()
+            .position(|frame| {
+                frame["methodName"]
+                    .as_str()
+                    .is_some_and(|name| name.contains("open_document"))
+            })
+            .expect("no frame of the panicking function");
+        frames[start..].to_vec()
+    };
+    let frames = from_panic(&built);
+    assert_eq!(frames, from_panic(&reported));
+    let collected = json!({ "frames": *COLLECTED.lock().unwrap() });
+    assert_eq!(frames, from_panic(&collected));
+    // With the default path prefixes stripped, like the reports of the hook
+    assert!(!frames[0]["fileName"]
+        .as_str()
+        .unwrap()
+        .starts_with(env!("CARGO_MANIFEST_DIR")));
+}
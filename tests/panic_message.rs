@@ -0,0 +1,64 @@
+// The message of a panic has the template of the default panic hook,
+// `thread '<name>' panicked at '<payload>', <file>:<line>`, whatever the thread and payload
+#![cfg(feature = "enabled")]
+
+use app_center::AppCenterException;
+use std::panic;
+use std::sync::Mutex;
+
+static MESSAGE: Mutex<Option<String>> = Mutex::new(None);
+
+// The message of the exception built for the panic of `f` on an unnamed thread
+fn message_of(f: impl FnOnce() + Send + 'static) -> String {
+    let _ = std::thread::spawn(f).join();
+    MESSAGE.lock().unwrap().take().expect("no panic")
+}
+
+#[test]
+fn message_has_the_template_of_the_default_hook() {
+    panic::set_hook(Box::new(|info| {
+        let exception = AppCenterException::from_panic_info(info);
+        *MESSAGE.lock().unwrap() = Some(exception.message().to_string());
+    }));
+
+    // A panic on a named thread, with a `&str` payload
+    let line = line!() + 3;
+    let _ = std::thread::Builder::new()
+        .name("exporter".to_string())
+        .spawn(|| panic!("export failed"))
+        .unwrap()
+        .join();
+    assert_eq!(
+        MESSAGE.lock().unwrap().take().unwrap(),
+        format!(
+            "thread 'exporter' panicked at 'export failed', tests/panic_message.rs:{}",
+            line
+        )
+    );
+
+    // An unnamed thread, with a `String` payload
+    let line = line!() + 2;
+    let message = message_of(|| {
+        panic!("{} pages left", 3);
+    });
+    assert_eq!(
+        message,
+        format!(
+            "thread '<unnamed>' panicked at '3 pages left', tests/panic_message.rs:{}",
+            line
+        )
+    );
+
+    // A payload that is not a string
+    let line = line!() + 2;
+    let message = message_of(|| {
+        panic::panic_any(42_u32);
+    });
+    assert_eq!(
+        message,
+        format!(
+            "thread '<unnamed>' panicked at 'Box<dyn Any>', tests/panic_message.rs:{}",
+            line
+        )
+    );
+}
@@ -0,0 +1,81 @@
+// Reports stored by previous runs are dropped after the maximum number of attempts, or once
+// they are older than the retention period, without being sent
+#![cfg(feature = "testing")]
+
+use app_center::{AppCenter, CapturingTransport};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PAYLOAD: &str = r#"{"logs":[]}"#;
+
+fn crash_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "app-center-pending-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn app_center(dir: &Path, transport: &CapturingTransport) -> AppCenter {
+    AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .crash_dir(dir)
+        .max_send_attempts(3)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap()
+}
+
+// A report with an envelope created at `created_at`
+fn write_report(dir: &Path, created_at: chrono::DateTime<chrono::Utc>) -> PathBuf {
+    let path = dir.join(format!("{}.json", uuid::Uuid::new_v4()));
+    let envelope = format!(
+        r#"{{"formatVersion":1,"createdAt":"{}"}}"#,
+        created_at.to_rfc3339()
+    );
+    fs::write(&path, format!("{}\n{}", envelope, PAYLOAD)).unwrap();
+    path
+}
+
+#[test]
+fn reports_expire_after_the_maximum_attempts() {
+    let dir = crash_dir("attempts");
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&dir, &transport);
+
+    let report = write_report(&dir, chrono::Utc::now());
+    let meta = report.with_extension("meta.json");
+    fs::write(&meta, r#"{"attempts":3,"lastAttempt":null}"#).unwrap();
+
+    let summary = app_center.send_pending_reports();
+    assert_eq!(summary.expired, 1);
+    assert_eq!(summary.sent, 0);
+    assert!(transport.requests().is_empty());
+    assert!(!report.exists());
+    assert!(!meta.exists());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn reports_expire_after_the_retention_period() {
+    let dir = crash_dir("retention");
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&dir, &transport);
+
+    let stale = write_report(&dir, chrono::Utc::now() - chrono::Duration::days(31));
+    let fresh = write_report(&dir, chrono::Utc::now() - chrono::Duration::days(29));
+
+    let summary = app_center.send_pending_reports();
+    assert_eq!(summary.expired, 1);
+    assert_eq!(summary.sent, 1);
+    assert_eq!(transport.requests().len(), 1);
+    assert!(!stale.exists());
+    assert!(!fresh.exists());
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,35 @@
+// A typical application only needs the prelude, no other path of the crate is used here
+
+use app_center::prelude::*;
+
+fn start() -> Result<AppCenter, ConfigError> {
+    // The placeholder secret disables reporting, reports can still be built
+    let builder: AppCenterBuilder = AppCenter::builder("00000000-0000-0000-0000-000000000000")
+        .app_version("1.0.0")
+        .auto_start_session(false);
+    let app_center: AppCenter = builder.build()?;
+    app_center.set_report_callback(|logs: &mut AppCenterLogs| logs.set_user_id(None));
+    Ok(app_center)
+}
+
+#[test]
+fn prelude_is_enough_for_an_application() {
+    let app_center = start().unwrap();
+
+    let breadcrumb: Breadcrumb =
+        Breadcrumb::new("export", "disk almost full").with_level(BreadcrumbLevel::Warning);
+    assert_eq!(breadcrumb.level(), BreadcrumbLevel::Warning);
+    assert_eq!(
+        Breadcrumb::new("export", "started").level(),
+        BreadcrumbLevel::Info
+    );
+
+    let json = serde_json::to_value(&breadcrumb).unwrap();
+    assert_eq!(json["level"], "warning");
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(app_center.add_breadcrumb_async(breadcrumb));
+    app_center.add_breadcrumb("export", "finished", None);
+}
@@ -0,0 +1,59 @@
+// Frames carry the mangled symbol as `rawSymbol` next to the demangled `methodName` when
+// `include_raw_symbols` is set
+#![cfg(feature = "testing")]
+
+use app_center::{AppCenter, CapturingTransport};
+use serde_json::Value;
+
+// The frames of a report sent from this test
+fn reported_frames(include_raw_symbols: bool) -> Vec<Value> {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .include_raw_symbols(include_raw_symbols)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+    app_center
+        .report_builder()
+        .message("symbols")
+        .send()
+        .unwrap();
+    transport.logs()[0]["exception"]["frames"]
+        .as_array()
+        .unwrap()
+        .clone()
+}
+
+#[test]
+#[cfg_attr(
+    not(debug_assertions),
+    ignore = "the frames are only named with debug info"
+)]
+fn raw_symbols_are_reported_when_included() {
+    let frames = reported_frames(true);
+    let frame = frames
+        .iter()
+        .find(|frame| {
+            frame["methodName"]
+                .as_str()
+                .is_some_and(|name| name.contains("reported_frames"))
+        })
+        .unwrap_or_else(|| panic!("{:#?}", frames));
+    let raw_symbol = frame["rawSymbol"].as_str().expect("no rawSymbol");
+    // Mangled with the legacy or the v0 scheme
+    assert!(
+        raw_symbol.starts_with("_ZN") || raw_symbol.starts_with("_R"),
+        "{}",
+        raw_symbol
+    );
+    assert!(raw_symbol.contains("reported_frames"), "{}", raw_symbol);
+}
+
+#[test]
+fn raw_symbols_are_left_out_by_default() {
+    let frames = reported_frames(false);
+    assert!(!frames.is_empty());
+    assert!(frames.iter().all(|frame| frame.get("rawSymbol").is_none()));
+}
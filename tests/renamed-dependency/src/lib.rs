@@ -0,0 +1,34 @@
+//! `start!` expands to paths through `$crate`, so it compiles when the dependency is renamed
+//! and no `app_center` crate is in scope. `catch` is given the path with `crate = crash`.
+
+#[cfg(test)]
+mod tests {
+    // The placeholder secret disables reporting, no hook is installed
+    const SECRET: &str = "00000000-0000-0000-0000-000000000000";
+
+    #[test]
+    fn start_compiles_with_a_renamed_dependency() {
+        let app_center: crash::AppCenter = crash::start!(SECRET);
+        assert!(!app_center.is_enabled());
+        let app_center: crash::AppCenter = crash::start!(SECRET, "1.2.0");
+        assert!(!app_center.is_enabled());
+        let app_center: crash::AppCenter = crash::start!(SECRET, "1.2.0", "4567");
+        assert!(!app_center.is_enabled());
+    }
+
+    #[crash::catch(crate = crash)]
+    fn caught() -> u32 {
+        panic!("caught")
+    }
+
+    #[crash::catch(crate = crash, fatal = true)]
+    fn fatal() -> u32 {
+        1
+    }
+
+    #[test]
+    fn catch_compiles_with_a_renamed_dependency() {
+        assert_eq!(caught(), 0);
+        assert_eq!(fatal(), 1);
+    }
+}
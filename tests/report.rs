@@ -0,0 +1,323 @@
+// Reporting an error returns the id of the report, or why it was not reported, and sends
+// what the configuration of the instance puts in it
+#![cfg(feature = "testing")]
+
+use app_center::{
+    AppCenter, Base64Variant, CapturingTransport, FixedClock, ReportError, UserIdMode,
+};
+use chrono::TimeZone;
+
+fn app_center(transport: &CapturingTransport) -> AppCenter {
+    AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn reported_error_returns_its_id() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+
+    let error = std::io::Error::other("disk full");
+    let id = app_center.report_error(&error).unwrap();
+    assert_eq!(app_center.last_error_id(), Some(id));
+    assert_eq!(transport.logs()[0]["id"], id.to_string());
+}
+
+#[test]
+fn last_error_id_is_the_id_of_the_last_sent_report() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    assert_eq!(app_center.last_error_id(), None);
+
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+    let first = transport.logs()[0]["id"].clone();
+    assert_eq!(app_center.last_error_id().unwrap().to_string(), first);
+
+    transport.clear();
+    app_center
+        .report_builder()
+        .message("quota exceeded")
+        .send()
+        .unwrap();
+    let second = transport.logs()[0]["id"].clone();
+    assert_ne!(second, first);
+    assert_eq!(app_center.last_error_id().unwrap().to_string(), second);
+
+    // A discarded report doesn't replace it
+    app_center.set_report_filter(|_| false);
+    assert!(app_center.report_error(&error).is_err());
+    assert_eq!(app_center.last_error_id().unwrap().to_string(), second);
+}
+
+#[test]
+fn user_id_is_sent_as_its_sha256_digest() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    app_center.set_user_id_mode(UserIdMode::Sha256);
+    app_center.set_user_id(Some("user@example.com"));
+
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+    assert_eq!(
+        transport.logs()[0]["userId"],
+        "b4c9a289323b21a01c3e940f150eb9b8c542587f1abfd8f0e1cc1ffc5e475514"
+    );
+}
+
+#[test]
+fn filtered_error_is_discarded() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    app_center.set_report_filter(|_| false);
+
+    let error = std::io::Error::other("disk full");
+    assert_eq!(app_center.report_error(&error), Err(ReportError::Discarded));
+    assert!(transport.requests().is_empty());
+}
+
+#[test]
+fn disabled_reporting_is_refused() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    app_center.set_crashes_enabled(false);
+
+    let error = std::io::Error::other("disk full");
+    assert_eq!(app_center.report_error(&error), Err(ReportError::Disabled));
+    assert_eq!(
+        app_center.report_builder().send(),
+        Err(ReportError::Disabled)
+    );
+    assert!(transport.requests().is_empty());
+}
+
+// Unwinds without running the panic hook, which would report the panic of the scrubber
+#[test]
+fn report_is_dropped_when_the_scrubber_panics() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    app_center.set_scrubber(|_| std::panic::resume_unwind(Box::new("scrubber")));
+
+    let error = std::io::Error::other("disk full");
+    assert!(matches!(
+        app_center.build_report(&error),
+        Err(ReportError::Discarded)
+    ));
+    assert!(matches!(
+        app_center.report_builder().message("disk full").build(),
+        Err(ReportError::Discarded)
+    ));
+    assert_eq!(app_center.report_error(&error), Err(ReportError::Discarded));
+    assert!(transport.requests().is_empty());
+}
+
+#[test]
+fn working_directory_is_reported_when_included() {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .include_cwd(true)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+    let cwd = std::env::current_dir().unwrap();
+    assert_eq!(
+        transport.logs()[0]["properties"]["cwd"],
+        cwd.to_str().unwrap()
+    );
+}
+
+#[test]
+fn working_directory_is_not_reported_by_default() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+    let log = &transport.logs()[0];
+    assert_eq!(log["type"], "handledError");
+    assert!(log["properties"].get("cwd").is_none());
+}
+
+#[test]
+fn ignored_messages_can_be_changed_at_runtime() {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .ignore_messages(vec!["unexpected EOF".to_string()])
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+    let report = |message: &str| app_center.report_builder().message(message).send();
+
+    assert!(matches!(
+        report("parse error: unexpected EOF"),
+        Err(ReportError::Discarded)
+    ));
+    app_center.set_ignored_messages(vec!["invalid token".to_string()]);
+    assert!(report("parse error: unexpected EOF").is_ok());
+    assert!(matches!(
+        report("parse error: invalid token"),
+        Err(ReportError::Discarded)
+    ));
+}
+
+#[test]
+fn launch_timestamp_is_reset_to_the_current_time() {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .launch_timestamp(chrono::Utc.with_ymd_and_hms(2023, 6, 7, 8, 9, 10).unwrap())
+        .clock(FixedClock(
+            chrono::Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap(),
+        ))
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+    let log = &transport.logs()[0];
+    assert_eq!(log["appLaunchTimestamp"], "2023-06-07T08:09:10Z");
+    assert_eq!(log["timestamp"], "2024-01-02T03:04:05Z");
+
+    transport.clear();
+    app_center.reset_launch_timestamp();
+    app_center.report_error(&error).unwrap();
+    assert_eq!(
+        transport.logs()[0]["appLaunchTimestamp"],
+        "2024-01-02T03:04:05Z"
+    );
+}
+
+#[test]
+fn user_id_set_on_a_clone_is_shared() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    let clone = app_center.clone();
+    clone.set_user_id(Some("user"));
+
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+    clone.report_error(&error).unwrap();
+    let errors: Vec<_> = transport
+        .logs()
+        .into_iter()
+        .filter(|log| log["type"] == "handledError")
+        .collect();
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().all(|log| log["userId"] == "user"));
+
+    // And the other way around
+    app_center.set_user_id(Some("other"));
+    transport.clear();
+    clone.report_error(&error).unwrap();
+    assert_eq!(transport.logs()[0]["userId"], "other");
+}
+
+#[test]
+fn grouping_key_fn_result_is_serialized() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    app_center.set_grouping_key_fn(|_| "export-pipeline".to_string());
+
+    app_center
+        .report_builder()
+        .message("crashed")
+        .fatal(true)
+        .send()
+        .unwrap();
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+
+    let errors: Vec<_> = transport
+        .logs()
+        .into_iter()
+        .filter(|log| log["type"] == "managedError" || log["type"] == "handledError")
+        .collect();
+    assert_eq!(errors.len(), 2);
+    for log in errors {
+        assert_eq!(log["groupingKey"], "export-pipeline", "{}", log["type"]);
+    }
+}
+
+#[test]
+fn app_build_is_serialized_in_the_device() {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .app_build("1234")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+    assert_eq!(transport.logs()[0]["device"]["appBuild"], "1234");
+
+    // Every log of the report has the updated build
+    transport.clear();
+    app_center.set_app_build("1235");
+    app_center.report_error(&error).unwrap();
+    let logs = transport.logs();
+    assert!(logs.len() > 1);
+    for log in logs {
+        assert_eq!(log["device"]["appBuild"], "1235", "{}", log["type"]);
+    }
+}
+
+#[test]
+fn pretty_json_parses_back_to_the_sent_json() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    app_center.set_user_id(Some("user"));
+    app_center.add_breadcrumb("export", "started", None);
+
+    let error = std::io::Error::other("disk full");
+    let report = app_center.build_report(&error).unwrap();
+    let pretty = report.to_json_pretty().unwrap();
+    assert!(pretty.lines().count() > 1);
+
+    let compact: serde_json::Value =
+        serde_json::from_slice(&report.to_json_bytes().unwrap()).unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+    assert_eq!(parsed, compact);
+}
+
+#[test]
+fn attachments_are_encoded_with_the_selected_base64_variant() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    app_center.set_report_callback(|logs| {
+        logs.add_binary_attachement(vec![0xfb, 0xff, 0xbf, 0xfb, 0xf0], Some("state.bin"))
+            .unwrap();
+    });
+    let state = |transport: &CapturingTransport| {
+        transport
+            .logs()
+            .into_iter()
+            .find(|log| log["type"] == "errorAttachment" && log["fileName"] == "state.bin")
+            .unwrap()["data"]
+            .clone()
+    };
+
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+    assert_eq!(state(&transport), "+/+/+/A=");
+
+    transport.clear();
+    app_center.set_base64_variant(Base64Variant::UrlSafe);
+    app_center.report_error(&error).unwrap();
+    assert_eq!(state(&transport), "-_-_-_A=");
+}
@@ -0,0 +1,143 @@
+// A synthetic report built from parts is sent like the other reports, with the device, ids
+// and timestamps filled in
+#![cfg(feature = "testing")]
+
+use app_center::{
+    AppCenter, CapturingTransport, DeviceBuilder, ExceptionFrame, FixedClock, SequentialIds,
+};
+use chrono::TimeZone;
+use serde_json::json;
+
+#[test]
+fn synthetic_report_json() {
+    // A first launch
+    let data_dir =
+        std::env::temp_dir().join(format!("app-center-report-builder-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&data_dir);
+
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .data_dir(&data_dir)
+        .attach_system_state(false)
+        .max_breadcrumbs(0)
+        .id_source(SequentialIds::new())
+        .clock(FixedClock(
+            chrono::Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap(),
+        ))
+        .device(
+            DeviceBuilder::new()
+                .model("test")
+                .os_name("Linux")
+                .os_version("6.1")
+                .locale("en_US")
+                .app_version("1.0.0")
+                .app_build("42")
+                .sdk_version("0.0.0"),
+        )
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    let id = app_center
+        .report_builder()
+        .exception_type("libfoo::Error")
+        .message("foo_open failed: No such file or directory")
+        .frames(vec![
+            ExceptionFrame::new(
+                Some("foo_open".to_string()),
+                Some("foo.c".to_string()),
+                Some(42),
+            ),
+            ExceptionFrame::new(Some("main".to_string()), None, None),
+        ])
+        .fatal(true)
+        .user_id(Some("user".to_string()))
+        .add_text_attachment("mode=rw", Some("foo.txt"))
+        .unwrap()
+        .add_binary_attachment(vec![0, 1, 2], Some("foo.bin"))
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let device = json!({
+        "model": "test",
+        "sdkName": "appcenter.custom",
+        "sdkVersion": "0.0.0",
+        "osName": "Linux",
+        "osVersion": "6.1",
+        "osBuild": "",
+        "locale": "en_US",
+        "appVersion": "1.0.0",
+        "appBuild": "42",
+    });
+    let logs = transport.logs();
+    assert_eq!(id.to_string(), "00000000-0000-0000-0000-000000000002");
+    assert_eq!(
+        logs[0],
+        json!({
+            "type": "managedError",
+            "id": "00000000-0000-0000-0000-000000000002",
+            "sid": "00000000-0000-0000-0000-000000000001",
+            "timestamp": "2024-01-02T03:04:05Z",
+            "appLaunchTimestamp": "2024-01-02T03:04:05Z",
+            "device": device,
+            "userId": "user",
+            "fatal": true,
+            "isFirstLaunch": true,
+            "processId": std::process::id(),
+            "processName": logs[0]["processName"],
+            "hookExecutionMs": logs[0]["hookExecutionMs"],
+            "groupingKey": logs[0]["groupingKey"],
+            "exception": {
+                "type": "libfoo::Error",
+                "message": "foo_open failed: No such file or directory",
+                "frames": [
+                    { "methodName": "foo_open", "fileName": "foo.c", "lineNumber": 42 },
+                    { "methodName": "main" },
+                ],
+            },
+        })
+    );
+    assert!(logs[0]["processName"]
+        .as_str()
+        .unwrap()
+        .starts_with("report_builder"));
+    assert!(logs[0]["groupingKey"].is_string());
+    assert!(logs[0]["hookExecutionMs"].is_u64());
+
+    // The attachments of the builder follow the ones of the instance
+    let attachments: Vec<_> = logs[1..]
+        .iter()
+        .filter(|log| log["fileName"] == "foo.txt" || log["fileName"] == "foo.bin")
+        .collect();
+    assert!(logs[1..]
+        .iter()
+        .all(|log| log["type"] == "errorAttachment" && log["errorId"] == logs[0]["id"]));
+    assert_eq!(
+        attachments,
+        [
+            &json!({
+                "type": "errorAttachment",
+                "id": attachments[0]["id"],
+                "errorId": "00000000-0000-0000-0000-000000000002",
+                "contentType": "text/plain",
+                "data": "bW9kZT1ydw==",
+                "fileName": "foo.txt",
+                "device": device,
+            }),
+            &json!({
+                "type": "errorAttachment",
+                "id": attachments[1]["id"],
+                "errorId": "00000000-0000-0000-0000-000000000002",
+                "contentType": "application/octet_stream",
+                "data": "AAEC",
+                "fileName": "foo.bin",
+                "device": device,
+            }),
+        ]
+    );
+
+    let _ = std::fs::remove_dir_all(&data_dir);
+}
@@ -0,0 +1,52 @@
+// A report that panics while it is built on the reporting thread is dropped, without being
+// reported again, and the reports after it are still sent
+#![cfg(feature = "enabled")]
+
+use app_center::{AppCenter, HttpRequest, HttpResponse, HttpSender, SendError};
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+struct CountingSender(Arc<AtomicUsize>);
+
+impl HttpSender for CountingSender {
+    fn send(&self, _: &HttpRequest) -> Result<HttpResponse, SendError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(HttpResponse::new(200, ""))
+    }
+}
+
+#[test]
+fn panicking_report_is_dropped() {
+    panic::set_hook(Box::new(|_| {}));
+
+    let sent = Arc::new(AtomicUsize::new(0));
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .crash_dir(std::env::temp_dir().join("app-center-reporter-panic"))
+        .timeouts(Duration::from_secs(5), Duration::from_secs(5))
+        .http_sender(CountingSender(Arc::clone(&sent)))
+        .build()
+        .unwrap();
+
+    let fail = Arc::new(AtomicBool::new(true));
+    let filter_fail = Arc::clone(&fail);
+    app_center.set_report_filter(move |_| {
+        if filter_fail.swap(false, Ordering::SeqCst) {
+            panic!("filter failure");
+        }
+        true
+    });
+
+    let started = Instant::now();
+    let _ = panic::catch_unwind(|| panic!("first"));
+    // Not held up until the wait for the report times out
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(sent.load(Ordering::SeqCst), 0);
+
+    let _ = panic::catch_unwind(|| panic!("second"));
+    assert_eq!(sent.load(Ordering::SeqCst), 1);
+    drop(app_center);
+}
@@ -0,0 +1,142 @@
+// The property names of `AppCenterLogs::json_schema` are the ones the logs are serialized
+// with: every serialized field is described, and every required one is serialized
+#![cfg(all(feature = "schema", feature = "testing"))]
+
+use app_center::{AppCenter, AppCenterLogs, CapturingTransport, EventProperties};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+fn definition<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    match schema["$ref"].as_str() {
+        Some(reference) => {
+            let name = reference.trim_start_matches("#/definitions/");
+            &root["definitions"][name]
+        }
+        None => schema,
+    }
+}
+
+// Check `value` against `schema`, collecting the `type` of the tagged variants it matched
+fn check(root: &Value, schema: &Value, value: &Value, path: &str, tags: &mut BTreeSet<String>) {
+    let schema = definition(root, schema);
+
+    if let Some(variants) = schema["oneOf"].as_array() {
+        let tag = value["type"]
+            .as_str()
+            .unwrap_or_else(|| panic!("{}: no type", path));
+        let variant = variants
+            .iter()
+            .find(|variant| variant["properties"]["type"]["enum"][0] == tag)
+            .unwrap_or_else(|| panic!("{}: type {:?} is not in the schema", path, tag));
+        tags.insert(tag.to_string());
+        return check(root, variant, value, path, tags);
+    }
+
+    match value {
+        Value::Object(object) => {
+            let properties = schema["properties"]
+                .as_object()
+                .unwrap_or_else(|| panic!("{}: not an object in the schema", path));
+            for (key, value) in object {
+                let property = properties
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{}.{} is not in the schema", path, key));
+                // Maps such as `properties` have no fixed names
+                if property["additionalProperties"].is_object() {
+                    continue;
+                }
+                check(root, property, value, &format!("{}.{}", path, key), tags);
+            }
+            for required in schema["required"].as_array().into_iter().flatten() {
+                let required = required.as_str().unwrap();
+                assert!(
+                    object.contains_key(required),
+                    "{}.{} is required by the schema but not serialized",
+                    path,
+                    required
+                );
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                check(
+                    root,
+                    &schema["items"],
+                    item,
+                    &format!("{}[{}]", path, i),
+                    tags,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+#[test]
+fn serialized_names_match_the_schema() {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+    app_center.set_report_callback(|logs| {
+        logs.add_binary_attachement(b"state".to_vec(), Some("state.bin"))
+            .unwrap();
+    });
+    app_center.add_breadcrumb("export", "started", None);
+    app_center.set_user_id(Some("user"));
+
+    app_center.verify_connection().unwrap();
+    let error = std::io::Error::other("disk full");
+    app_center.report_error(&error).unwrap();
+    app_center
+        .report_builder()
+        .message("crashed")
+        .fatal(true)
+        .send()
+        .unwrap();
+    let properties = BTreeMap::from([("format".to_string(), "pdf".to_string())]);
+    app_center
+        .track_event("exported", Some(properties))
+        .unwrap();
+    app_center
+        .track_event_with_properties(
+            "exported",
+            EventProperties::new()
+                .string("format", "pdf")
+                .long("pages", 12)
+                .double("ratio", 0.5)
+                .bool("compressed", true)
+                .date_time("started", chrono::Utc::now()),
+        )
+        .unwrap();
+    app_center.track_page("settings").unwrap();
+    app_center.shutdown();
+
+    let schema = serde_json::to_value(AppCenterLogs::json_schema()).unwrap();
+    let mut tags = BTreeSet::new();
+    for request in transport.requests() {
+        check(&schema, &schema, &request.json().unwrap(), "", &mut tags);
+    }
+
+    // Every kind of log and typed property was checked
+    let expected: BTreeSet<String> = [
+        "managedError",
+        "handledError",
+        "errorAttachment",
+        "startSession",
+        "event",
+        "page",
+        "string",
+        "long",
+        "double",
+        "boolean",
+        "dateTime",
+    ]
+    .iter()
+    .map(|tag| tag.to_string())
+    .collect();
+    assert_eq!(tags, expected);
+}
@@ -0,0 +1,62 @@
+// A `startSession` log is sent from a background thread when the instance is built, and for
+// every renewed session
+#![cfg(feature = "testing")]
+
+use app_center::{AppCenter, CapturingTransport};
+use std::time::{Duration, Instant};
+
+const SECRET: &str = "12345678-1234-1234-1234-123456789abc";
+
+// The `startSession` logs captured so far, waiting for `count` of them
+fn sessions(transport: &CapturingTransport, count: usize) -> Vec<serde_json::Value> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let sessions: Vec<_> = transport
+            .logs()
+            .into_iter()
+            .filter(|log| log["type"] == "startSession")
+            .collect();
+        if sessions.len() >= count || Instant::now() > deadline {
+            return sessions;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn session_is_started_after_build() {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder(SECRET)
+        .app_version("1.0.0")
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    let started = sessions(&transport, 1);
+    assert_eq!(started.len(), 1);
+    let session = &started[0];
+    assert!(uuid::Uuid::parse_str(session["id"].as_str().unwrap()).is_ok());
+    assert!(uuid::Uuid::parse_str(session["sid"].as_str().unwrap()).is_ok());
+    assert!(session["timestamp"].is_string());
+    assert_eq!(session["device"]["appVersion"], "1.0.0");
+
+    // A new session gets a new id
+    app_center.renew_session();
+    let started = sessions(&transport, 2);
+    assert_eq!(started.len(), 2);
+    assert_ne!(started[0]["sid"], started[1]["sid"]);
+}
+
+#[test]
+fn no_session_is_started_when_disabled() {
+    let transport = CapturingTransport::new();
+    let _app_center = AppCenter::builder(SECRET)
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(sessions(&transport, 0).is_empty());
+}
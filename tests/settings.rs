@@ -0,0 +1,69 @@
+// The settings are kept in the data directory across launches, and a malformed app secret is
+// never joined to the default path
+#![cfg(feature = "testing")]
+
+use app_center::{AppCenter, CapturingTransport};
+use std::fs;
+use std::path::PathBuf;
+
+const SECRET: &str = "12345678-1234-1234-1234-123456789abc";
+
+fn data_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "app-center-settings-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn launch_count_is_kept_in_the_data_dir() {
+    let dir = data_dir("launch-count");
+    let transport = CapturingTransport::new();
+    let launch = || {
+        AppCenter::builder(SECRET)
+            .app_version("1.0.0")
+            .auto_start_session(false)
+            .data_dir(&dir)
+            .http_sender(transport.clone())
+            .build()
+            .unwrap()
+    };
+
+    let first = launch();
+    assert_eq!(first.launch_count(), 1);
+    let install_id = first.install_id();
+    drop(first);
+
+    let second = launch();
+    assert_eq!(second.launch_count(), 2);
+    assert_eq!(second.install_id(), install_id);
+    assert!(dir.join("settings.json").is_file());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn malformed_secret_is_not_joined_to_the_data_dir() {
+    let home = data_dir("home");
+    fs::create_dir_all(&home).unwrap();
+    std::env::set_var("XDG_DATA_HOME", &home);
+
+    let app_center = AppCenter::builder("../../escaped")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(CapturingTransport::new())
+        .start();
+    assert!(!app_center.is_enabled());
+    assert_eq!(app_center.launch_count(), 1);
+    drop(app_center);
+
+    // `{home}/appcenter/../../escaped`
+    assert!(!home.parent().unwrap().join("escaped").exists());
+    assert!(fs::read_dir(&home).unwrap().next().is_none());
+
+    let _ = fs::remove_dir_all(&home);
+}
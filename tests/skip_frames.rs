@@ -0,0 +1,66 @@
+// Frames matching the patterns of `skip_frames_matching` are left out of the reports
+#![cfg(feature = "testing")]
+
+use app_center::{AppCenter, CapturingTransport};
+
+// The method names of the frames of a report sent from within a tokio runtime
+fn frames_reported_in_tokio(skip_patterns: &[&str]) -> Vec<String> {
+    let transport = CapturingTransport::new();
+    let mut builder = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone());
+    for pattern in skip_patterns {
+        builder = builder.skip_frames_matching(pattern);
+    }
+    let app_center = builder.build().unwrap();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime
+        .block_on(async { app_center.report_builder().message("timeout").send() })
+        .unwrap();
+
+    transport.logs()[0]["exception"]["frames"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|frame| frame["methodName"].as_str())
+        .map(|name| name.to_string())
+        .collect()
+}
+
+fn is_tokio_frame(name: &str) -> bool {
+    name.contains("tokio::")
+}
+
+#[test]
+#[cfg_attr(
+    not(debug_assertions),
+    ignore = "the tokio frames are only named with debug info"
+)]
+fn tokio_frames_are_skipped() {
+    // Without the pattern the runtime shows up in the backtrace
+    let frames = frames_reported_in_tokio(&[]);
+    assert!(
+        frames.iter().any(|name| is_tokio_frame(name)),
+        "{:#?}",
+        frames
+    );
+
+    let frames = frames_reported_in_tokio(&["tokio::"]);
+    assert!(
+        !frames.iter().any(|name| is_tokio_frame(name)),
+        "{:#?}",
+        frames
+    );
+    // The other frames are kept
+    assert!(
+        frames
+            .iter()
+            .any(|name| name.contains("tokio_frames_are_skipped")),
+        "{:#?}",
+        frames
+    );
+}
@@ -0,0 +1,69 @@
+// The events recorded by `AppCenterLayer` before a panic are attached to its report as
+// `log.txt`, with their fields, the spans when enabled, and nothing below the level
+#![cfg(all(feature = "testing", feature = "tracing"))]
+
+use app_center::{AppCenter, AppCenterLayer, CapturingTransport, LogCapacity};
+use std::panic;
+use std::time::{Duration, Instant};
+use tracing::Level;
+use tracing_subscriber::layer::SubscriberExt;
+
+// The contents of `log.txt` attached to the crash, waiting for the reporting thread
+fn crash_log(transport: &CapturingTransport) -> String {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let logs = transport.logs();
+        if let Some(attachment) = logs
+            .iter()
+            .find(|log| log["type"] == "errorAttachment" && log["fileName"] == "log.txt")
+        {
+            assert!(logs.iter().any(|log| log["type"] == "managedError"));
+            let data = base64::decode(attachment["data"].as_str().unwrap()).unwrap();
+            return String::from_utf8(data).unwrap();
+        }
+        assert!(Instant::now() < deadline, "no log.txt attachment");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn events_before_a_panic_are_attached() {
+    let transport = CapturingTransport::new();
+    let _app_center = AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap();
+
+    let subscriber = tracing_subscriber::Registry::default().with(
+        AppCenterLayer::new(LogCapacity::Lines(100))
+            .with_level(Level::INFO)
+            .with_spans(true),
+    );
+    tracing::subscriber::with_default(subscriber, || {
+        tracing::debug!("too verbose");
+        tracing::info!(document = "report.pdf", "opened");
+        let span = tracing::info_span!("export");
+        let _entered = span.enter();
+        tracing::error!(code = 28, "disk full");
+
+        let result = panic::catch_unwind(|| panic!("export failed"));
+        assert!(result.is_err());
+    });
+
+    let log = crash_log(&transport);
+    let lines: Vec<&str> = log.lines().collect();
+    assert!(!log.contains("too verbose"), "{}", log);
+    assert!(
+        lines[0].contains("INFO") && lines[0].ends_with(": opened document=\"report.pdf\""),
+        "{}",
+        log
+    );
+    assert!(lines[1].ends_with(": enter export"), "{}", log);
+    assert!(
+        lines[2].contains("ERROR") && lines[2].ends_with(": disk full code=28"),
+        "{}",
+        log
+    );
+}
@@ -0,0 +1,106 @@
+// `verify_connection` and `send_test_report` classify every outcome as a `VerifyError`
+#![cfg(feature = "testing")]
+
+use app_center::{AppCenter, CapturingTransport, VerifyError};
+
+fn app_center(transport: &CapturingTransport) -> AppCenter {
+    AppCenter::builder("12345678-1234-1234-1234-123456789abc")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn successful_connection_sends_a_session() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+
+    assert!(matches!(app_center.verify_connection(), Ok(())));
+    assert_eq!(transport.logs()[0]["type"], "startSession");
+}
+
+#[test]
+fn placeholder_secret_is_disabled() {
+    let transport = CapturingTransport::new();
+    let app_center = AppCenter::builder("00000000-0000-0000-0000-000000000000")
+        .app_version("1.0.0")
+        .auto_start_session(false)
+        .http_sender(transport.clone())
+        .start();
+
+    assert!(matches!(
+        app_center.verify_connection(),
+        Err(VerifyError::Disabled)
+    ));
+    assert!(matches!(
+        app_center.send_test_report(),
+        Err(VerifyError::Disabled)
+    ));
+    assert!(transport.requests().is_empty());
+}
+
+#[test]
+fn rejected_secret_is_unauthorized() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    transport.respond_with(401, "");
+
+    assert!(matches!(
+        app_center.verify_connection(),
+        Err(VerifyError::Unauthorized)
+    ));
+}
+
+#[test]
+fn refused_request_is_forbidden() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    transport.respond_with(403, "");
+
+    assert!(matches!(
+        app_center.verify_connection(),
+        Err(VerifyError::Forbidden)
+    ));
+}
+
+#[test]
+fn other_unsuccessful_status_is_kept() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    transport.respond_with(503, "unavailable");
+
+    assert!(matches!(
+        app_center.verify_connection(),
+        Err(VerifyError::Status(503))
+    ));
+}
+
+#[test]
+fn unreachable_server_is_a_network_error() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    transport.fail_next("connection refused");
+
+    match app_center.verify_connection() {
+        Err(VerifyError::Network(err)) => {
+            assert!(err.to_string().contains("connection refused"), "{}", err)
+        }
+        other => panic!("expected a network error, got {:?}", other),
+    }
+}
+
+// Unwinds without running the panic hook, which would report the panic of the scrubber
+#[test]
+fn test_report_dropped_by_the_scrubber_is_discarded() {
+    let transport = CapturingTransport::new();
+    let app_center = app_center(&transport);
+    app_center.set_scrubber(|_| std::panic::resume_unwind(Box::new("scrubber")));
+
+    assert!(matches!(
+        app_center.send_test_report(),
+        Err(VerifyError::Discarded)
+    ));
+    assert!(transport.requests().is_empty());
+}